@@ -42,12 +42,13 @@ pub const fn sint_in_range<const BITS: BitCt>(value: i64) -> bool {
         && value <= (i64::MAX >> ((i64::BITS - BITS) as i64))
 }
 
+/// returns true if the low `ALIGN` bits of `value` are zero
 pub const fn uint_low_zeros<const ALIGN: BitCt>(value: u64) -> bool {
-    value & (((ALIGN * ALIGN) as u64) - 1) == 0
+    debug_assert!(ALIGN < 64);
+    value & ((1 << ALIGN) - 1) == 0
 }
 pub const fn sint_low_zeros<const ALIGN: BitCt>(value: i64) -> bool {
-    let uval = value as u64;
-    uval & (((ALIGN * ALIGN) as u64) - 1) == 0
+    uint_low_zeros::<ALIGN>(value as u64)
 }
 
 pub const fn uint_max_value<const BITS: BitCt>() -> u64 {
@@ -168,4 +169,15 @@ fn it_checks_sint_in_range() {
         assert!(sint_in_range::<64>(i64::MIN));
         assert!(sint_in_range::<64>(i64::MAX));
     }
+
+    #[test]
+    fn it_checks_low_zeros() {
+        assert!(uint_low_zeros::<0>(7));
+        assert!(!uint_low_zeros::<1>(3));
+        assert!(uint_low_zeros::<1>(2));
+        assert!(!uint_low_zeros::<3>(12));
+        assert!(uint_low_zeros::<3>(16));
+        assert!(sint_low_zeros::<2>(-8));
+        assert!(!sint_low_zeros::<2>(-6));
+    }
 }
@@ -1,32 +1,38 @@
-use super::parse::ast::{
-    self,
-    expr::{self, IdentInt},
+use super::{
+    emit::LabelResolver,
+    eval,
+    parse::ast::{
+        self,
+        expr::{IdentInt, Index},
+    },
 };
 use crate::{
     code::{self, Span},
     enum_str::EnumStr,
     inst::{
-        label,
-        operand::{op, CondKind, ExtendKind, GprKind, GprSize, Kind, Ops, ShiftKind},
-        NarrowError, NarrowVariant,
+        operand::{
+            op, BarrierKind, BtiKind, CondKind, ExtendKind, GprKind, GprSize, IndexMode, Kind, Ops,
+            ShiftKind, Size, VecLanes, VecSize,
+        },
+        sysreg, NarrowError, NarrowVariant, TargetFeatures, UnknownName,
     },
 };
 
-pub struct ArgParser<'src, 'i> {
-    src: &'src code::Source,
-    intern: &'i mut label::Intern,
+pub struct ArgParser<'src, 'i, 'bump> {
+    src: &'src code::SourceManager,
+    labels: &'i mut LabelResolver<'bump>,
     narrow: NarrowVariant,
 }
 
-impl<'ast, 'src, 'i> ArgParser<'src, 'i> {
+impl<'ast, 'src, 'i, 'bump> ArgParser<'src, 'i, 'bump> {
     pub fn new(
-        src: &'src code::Source,
-        intern: &'i mut label::Intern,
+        src: &'src code::SourceManager,
+        labels: &'i mut LabelResolver<'bump>,
         narrow: NarrowVariant,
     ) -> Self {
         Self {
             src,
-            intern,
+            labels,
             narrow,
         }
     }
@@ -38,17 +44,93 @@ fn parse_cond(&mut self, s: Span) -> Option<op::Cond> {
         CondKind::from_str_lower_or_upper(self.src.span(s)).map(op::Cond)
     }
 
+    fn parse_sysreg(&self, span: Span) -> Option<op::SysReg> {
+        if !self.allow(Kind::SysReg) {
+            return None;
+        }
+        sysreg::parse(self.src.span(span)).map(op::SysReg)
+    }
+
+    /// option name of a barrier, a name that is neither an option nor a constant is an error
+    /// rather than a label
+    fn parse_barrier(&mut self, span: Span) -> Option<Ops> {
+        if !self.allow(Kind::Barrier) {
+            return None;
+        }
+        let name = self.src.span(span);
+        if let Some(kind) = BarrierKind::from_str_lower_or_upper(name) {
+            return Some(op::Barrier(kind).into());
+        }
+        if self.labels.is_const(name) {
+            return None;
+        }
+        self.src
+            .report(span, format_args!("invalid barrier option `{name}`"));
+        Some(Ops::Error)
+    }
+
+    fn parse_bti_target(&self, span: Span) -> Option<op::BtiTarget> {
+        if !self.allow(Kind::BtiTarget) {
+            return None;
+        }
+        BtiKind::from_str_lower_or_upper(self.src.span(span)).map(op::BtiTarget)
+    }
+
+    /// extension that `.arch_extension` enables, or disables with a `no` prefix
+    fn parse_arch_extension(&self, span: Span) -> Option<Ops> {
+        if !self.allow(Kind::ArchExtension) {
+            return None;
+        }
+        let Some((features, enable)) = TargetFeatures::toggle(self.src.span(span)) else {
+            self.src.report(span, "unknown architecture extension");
+            return Some(Ops::Error);
+        };
+        Some(Ops::ArchExtension { features, enable })
+    }
+
+    /// `armv8.2-a+crc` of `.arch` or `cortex-a53+crypto` of `.cpu`
+    fn parse_target(&self, span: Span) -> Option<Ops> {
+        let name = self.src.span(span);
+        let (target, what) = if self.allow(Kind::Arch) {
+            (TargetFeatures::arch(name).map(Ops::Arch), "architecture")
+        } else if self.allow(Kind::Cpu) {
+            (TargetFeatures::cpu(name).map(Ops::Cpu), "processor")
+        } else {
+            return None;
+        };
+        Some(target.unwrap_or_else(|unknown| {
+            let msg = match unknown {
+                UnknownName::Base(name) => format!("unknown {what} `{name}`"),
+                UnknownName::Extension(name) => format!("unknown architecture extension `{name}`"),
+            };
+            self.src.report(span, msg);
+            Ops::Error
+        }))
+    }
+
     fn parse_gpr(&self, span: Span) -> Option<op::Gpr> {
         if !self.allow(Kind::Gpr) {
             return None;
         }
+        self.gpr(span)
+    }
+
+    fn gpr(&self, span: Span) -> Option<op::Gpr> {
         let s = self.src.span(span);
         match s.as_bytes() {
-            b"SP" | b"sp" | b"ZR" | b"zr" => Some(op::Gpr {
+            b"SP" | b"sp" => Some(op::Gpr {
                 reg: GprKind::SP,
                 size: GprSize::B8,
             }),
-            b"WSP" | b"wsp" | b"WZR" | b"wzr" => Some(op::Gpr {
+            b"WSP" | b"wsp" => Some(op::Gpr {
+                reg: GprKind::SP,
+                size: GprSize::B4,
+            }),
+            b"XZR" | b"xzr" => Some(op::Gpr {
+                reg: GprKind::ZR,
+                size: GprSize::B8,
+            }),
+            b"WZR" | b"wzr" => Some(op::Gpr {
                 reg: GprKind::ZR,
                 size: GprSize::B4,
             }),
@@ -76,29 +158,203 @@ fn parse_gpr(&self, span: Span) -> Option<op::Gpr> {
         }
     }
 
-    fn parse_shift(&self, expr: ast::expr::IdentInt) -> Option<op::Shift> {
+    /// scalar FP register, `b0`, `h0`, `s0`, `d0` or `q0`
+    fn parse_dpr(&self, span: Span) -> Option<op::Dpr> {
+        if !self.allow(Kind::Dpr) {
+            return None;
+        }
+        self.dpr(span)
+    }
+
+    fn dpr(&self, span: Span) -> Option<op::Dpr> {
+        let (prefix, rest) = self.src.span(span).as_bytes().split_first()?;
+        let size = match prefix.to_ascii_lowercase() {
+            b'b' => Size::B1,
+            b'h' => Size::B2,
+            b's' => Size::B4,
+            b'd' => Size::B8,
+            b'q' => Size::B16,
+            _ => return None,
+        };
+        let reg = byte_str_to_u8(rest).filter(|&reg| !rest.is_empty() && reg <= 31)?;
+        Some(op::Dpr { reg, size })
+    }
+
+    /// `v0` to `v31`
+    fn vector_reg(&self, span: Span) -> Option<u8> {
+        match self.src.span(span).as_bytes() {
+            [b'v' | b'V', rest @ ..] if !rest.is_empty() => {
+                byte_str_to_u8(rest).filter(|&reg| reg <= 31)
+            }
+            _ => None,
+        }
+    }
+
+    /// `4s` or `16b`, the element size and the bytes in the vector
+    fn arrangement(&self, span: Span) -> Option<(VecSize, VecLanes)> {
+        Some(match self.src.span(span).to_ascii_lowercase().as_str() {
+            "8b" => (VecSize::B1, VecLanes::B8),
+            "16b" => (VecSize::B1, VecLanes::B16),
+            "4h" => (VecSize::B2, VecLanes::B8),
+            "8h" => (VecSize::B2, VecLanes::B16),
+            "2s" => (VecSize::B4, VecLanes::B8),
+            "4s" => (VecSize::B4, VecLanes::B16),
+            "1d" => (VecSize::B8, VecLanes::B8),
+            "2d" => (VecSize::B8, VecLanes::B16),
+            "1q" => (VecSize::B16, VecLanes::B16),
+            _ => return None,
+        })
+    }
+
+    /// `v0.4s`, or the lane `v0.s[1]`
+    fn parse_vector(&mut self, reg: Span, arrangement: Span, index: Option<&ast::Expr>) -> Ops {
+        let Some(reg) = self.vector_reg(reg) else {
+            self.src.report(reg, "expected a vector register");
+            return Ops::Error;
+        };
+        let Some(index) = index else {
+            if !self.allow(Kind::Vr) {
+                self.src.report(arrangement, "unexpected vector register");
+                return Ops::Error;
+            }
+            let Some((size, lanes)) = self.arrangement(arrangement) else {
+                self.src
+                    .report(arrangement, "expected an arrangement like `4s` or `16b`");
+                return Ops::Error;
+            };
+            return Ops::Vr { reg, size, lanes };
+        };
+        if !self.allow(Kind::IdxVr) {
+            self.src.report(arrangement, "unexpected vector lane");
+            return Ops::Error;
+        }
+        let size = match self.src.span(arrangement).as_bytes() {
+            b"b" | b"B" => VecSize::B1,
+            b"h" | b"H" => VecSize::B2,
+            b"s" | b"S" => VecSize::B4,
+            b"d" | b"D" => VecSize::B8,
+            _ => {
+                self.src
+                    .report(arrangement, "expected a lane size, `b`, `h`, `s` or `d`");
+                return Ops::Error;
+            }
+        };
+        let index = match self.parse_const(index).map(u8::try_from) {
+            Some(Ok(index)) if op::IdxVr::index_in_range(index, size) => index,
+            Some(..) => {
+                self.src.report(arrangement, "lane index is out of range");
+                return Ops::Error;
+            }
+            None => return Ops::Error,
+        };
+        Ops::IdxVr { reg, size, index }
+    }
+
+    /// `{v0.4s, v1.4s}` or `{v0.4s-v1.4s}`, one to four registers with the same arrangement
+    fn parse_reg_list(&mut self, regs: &[ast::Expr], group: Span, range: bool) -> Ops {
+        if !self.allow(Kind::VrList) {
+            self.src.report(group, "unexpected register list");
+            return Ops::Error;
+        }
+        let mut vectors = Vec::with_capacity(regs.len());
+        for expr in regs {
+            match *expr {
+                ast::Expr::Vector {
+                    reg,
+                    arrangement,
+                    index: None,
+                } => match (self.vector_reg(reg), self.arrangement(arrangement)) {
+                    (Some(reg), Some(arrangement)) => vectors.push((reg, arrangement)),
+                    _ => {
+                        self.src
+                            .report(reg, "expected a vector register like `v0.4s`");
+                        return Ops::Error;
+                    }
+                },
+                ast::Expr::Error => return Ops::Error,
+                _ => {
+                    let span = expr_span(expr).unwrap_or(group);
+                    self.src
+                        .report(span, "expected a vector register like `v0.4s`");
+                    return Ops::Error;
+                }
+            }
+        }
+        let (first, (size, lanes)) = vectors[0];
+        if vectors
+            .iter()
+            .any(|&(_, arrangement)| arrangement != (size, lanes))
+        {
+            self.src
+                .report(group, "registers in a list must have the same arrangement");
+            return Ops::Error;
+        }
+        // v31 wraps around to v0
+        let next = |reg: u8, n: usize| ((reg as usize + n) % 32) as u8;
+        let count = match range {
+            true => (vectors[1].0 as usize + 32 - first as usize) % 32 + 1,
+            false => vectors.len(),
+        };
+        let consecutive = range
+            || vectors
+                .iter()
+                .enumerate()
+                .all(|(i, &(reg, _))| reg == next(first, i));
+        if !consecutive || count > 4 {
+            self.src
+                .report(group, "expected one to four consecutive registers");
+            return Ops::Error;
+        }
+        Ops::VrList {
+            reg: first,
+            count: count as u8,
+            size,
+            lanes,
+        }
+    }
+
+    /// `#1.5`, `#-2.0` or an integer, which are only floats where an integer is not expected
+    fn parse_fp_imm(&mut self, expr: &ast::Expr) -> Option<op::FpImm> {
+        use ast::{Expr, UnaryOp};
+        let value = match *expr {
+            Expr::FloatLiteral { value, .. } => value,
+            Expr::Unary {
+                op: UnaryOp::Neg,
+                expr: Expr::FloatLiteral { value, .. },
+                ..
+            } => -value,
+            _ => self.parse_const(expr)? as f64,
+        };
+        Some(op::FpImm(value))
+    }
+
+    fn parse_shift(&mut self, expr: &IdentInt) -> Option<op::Shift> {
         if !self.allow(Kind::Shift) {
             return None;
         }
         let s = self.src.span(expr.span);
         let kind = ShiftKind::from_str_lower_or_upper(s)?;
-        let amount = u8::try_from(expr.int.value).unwrap();
+        let amount = self.parse_amount(expr)?;
         Some(op::Shift { kind, amount })
     }
 
-    fn parse_extend(&self, span: Span, int: Option<ast::expr::IntLiteral>) -> Option<op::Extend> {
+    fn parse_extend(&mut self, span: Span, int: Option<&IdentInt>) -> Option<op::Extend> {
         if !self.allow(Kind::Extend) {
             return None;
         }
+        self.extend(span, int)
+    }
+
+    fn extend(&mut self, span: Span, int: Option<&IdentInt>) -> Option<op::Extend> {
         let s = self.src.span(span);
-        let kind = ExtendKind::from_str_lower_or_upper(s).or_else(|| {
-            if s.as_bytes() == b"LSL" {
-                Some(ExtendKind::UXTX)
-            } else {
-                None
-            }
-        })?;
-        let left_shift_amount = int.map(|i| u8::try_from(i.value).unwrap());
+        let kind = match s.as_bytes() {
+            b"LSL" | b"lsl" => ExtendKind::UXTX,
+            _ => ExtendKind::from_str_lower_or_upper(s)?,
+        };
+        let left_shift_amount = match int {
+            Some(int) => Some(self.parse_amount(int)?),
+            None => None,
+        };
         Some(op::Extend {
             kind,
             left_shift_amount,
@@ -110,33 +366,274 @@ fn parse_label(&mut self, span: Span) -> Option<op::Label> {
             return None;
         }
         let s = self.src.span(span);
-        Some(op::Label(self.intern.get_or_intern(s)))
+        Some(op::Label(self.labels.key(s)))
     }
 
-    fn parse_ident(&mut self, span: Span) -> Ops {
+    /// 64-bit register or `sp` that an address is relative to
+    fn address_base(&mut self, expr: &ast::Expr) -> Option<GprKind> {
+        let (ast::Expr::Ident { span } | ast::Expr::IdentInt(IdentInt { span, .. })) = expr else {
+            self.src.report(expr_span(expr)?, "expected a base register");
+            return None;
+        };
+        match self.gpr(*span) {
+            Some(op::Gpr {
+                reg: reg @ (GprKind::R(..) | GprKind::SP),
+                size: GprSize::B8,
+            }) => Some(reg),
+            _ => {
+                self.src
+                    .report(*span, "base register must be an X register or `sp`");
+                None
+            }
+        }
+    }
+
+    /// `LSL #3` or `SXTW` after an index register
+    fn address_extend(&mut self, expr: &ast::Expr, index_size: GprSize) -> Option<op::Extend> {
+        let extend = match expr {
+            ast::Expr::Ident { span } => self.extend(*span, None),
+            ast::Expr::IdentInt(int) => self.extend(int.span, Some(int)),
+            _ => None,
+        };
+        let Some(extend) = extend else {
+            self.src.report(expr_span(expr)?, "expected an extend");
+            return None;
+        };
+        let (valid, msg) = match index_size {
+            GprSize::B4 => (
+                matches!(extend.kind, ExtendKind::UXTW | ExtendKind::SXTW),
+                "W index register must be extended with UXTW or SXTW",
+            ),
+            GprSize::B8 => (
+                matches!(extend.kind, ExtendKind::UXTX | ExtendKind::SXTX),
+                "X index register can only be extended with LSL or SXTX",
+            ),
+        };
+        if !valid {
+            self.src.report(expr_span(expr)?, msg);
+            return None;
+        }
+        Some(extend)
+    }
+
+    fn parse_address(&mut self, args: &[ast::Expr], index: Index, group: Span) -> Ops {
+        let [base, rest @ ..] = args else {
+            unreachable!("parser requires a base register");
+        };
+        let Some(base) = self.address_base(base) else {
+            return Ops::Error;
+        };
+        // register offset
+        if let [offset, rest @ ..] = rest
+            && let ast::Expr::Ident { span } = offset
+            && let Some(index_reg) = self.gpr(*span)
+        {
+            if !self.allow(Kind::AddrReg) {
+                self.src.report(group, "unexpected register offset");
+                return Ops::Error;
+            }
+            if !matches!(index, Index::Offset) {
+                self.src
+                    .report(group, "address with a register offset can't be written back");
+                return Ops::Error;
+            }
+            let extend = match rest {
+                [] if index_reg.size == GprSize::B4 => {
+                    self.src
+                        .report(*span, "W index register must be extended with UXTW or SXTW");
+                    return Ops::Error;
+                }
+                [] => None,
+                [extend] => match self.address_extend(extend, index_reg.size) {
+                    Some(extend) => Some(extend),
+                    None => return Ops::Error,
+                },
+                _ => {
+                    self.src.report(group, "too many arguments in the address");
+                    return Ops::Error;
+                }
+            };
+            return Ops::AddrReg {
+                base,
+                index: index_reg,
+                extend,
+            };
+        }
+
+        // post-index by a register, of LD1 and ST1
+        if let ([], Index::Post(ast::Expr::Ident { span })) = (rest, index)
+            && let Some(index_reg) = self.gpr(*span)
+        {
+            if !self.allow(Kind::AddrPostReg) {
+                self.src.report(*span, "unexpected register post-index");
+                return Ops::Error;
+            }
+            return Ops::AddrPostReg {
+                base,
+                index: index_reg,
+            };
+        }
+
+        if !self.allow(Kind::AddrImm) {
+            self.src.report(group, "unexpected address");
+            return Ops::Error;
+        }
+        let (offset, mode) = match (rest, index) {
+            ([], Index::Offset) => (op::Imm::new(0), IndexMode::Offset),
+            ([], Index::Pre) => {
+                self.src.report(group, "expected an offset to write back");
+                return Ops::Error;
+            }
+            ([], Index::Post(offset)) => match self.parse_imm(offset) {
+                Some(offset) => (offset, IndexMode::Post),
+                None => return Ops::Error,
+            },
+            ([offset], Index::Offset | Index::Pre) => match self.parse_imm(offset) {
+                Some(offset) if matches!(index, Index::Pre) => (offset, IndexMode::Pre),
+                Some(offset) => (offset, IndexMode::Offset),
+                None => return Ops::Error,
+            },
+            ([..], Index::Post(..)) => {
+                self.src
+                    .report(group, "post-indexed address can only have a base register");
+                return Ops::Error;
+            }
+            _ => {
+                self.src.report(group, "too many arguments in the address");
+                return Ops::Error;
+            }
+        };
+        Ops::AddrImm { base, offset, mode }
+    }
+
+    /// fold the expression, or defer it if it references a label that is not defined yet
+    fn parse_imm(&mut self, expr: &ast::Expr) -> Option<op::Imm> {
+        if let ast::Expr::Reloc { modifier, label } = *expr {
+            if !self.src.span(modifier).eq_ignore_ascii_case("lo12") {
+                self.src
+                    .report(modifier, "expected `:lo12:` in an immediate");
+                return None;
+            }
+            return Some(op::Imm {
+                value: 0,
+                deferred: None,
+                lo12: Some(self.labels.key(self.src.span(label))),
+            });
+        }
+        let mut nodes = eval::Nodes::new();
+        let result = eval::lower(self.src, self.labels, expr, &mut nodes)
+            .and_then(|_| self.labels.eval(&nodes));
+        match result {
+            Ok(value) => Some(op::Imm::new(value)),
+            Err(eval::Error::Undefined(..)) => Some(op::Imm {
+                value: 0,
+                deferred: Some(self.labels.defer(&nodes)),
+                lo12: None,
+            }),
+            Err(e) => {
+                e.report(self.src);
+                None
+            }
+        }
+    }
+
+    /// expression that has to be known now, like a shift amount
+    fn parse_const(&mut self, expr: &ast::Expr) -> Option<i64> {
+        let mut nodes = eval::Nodes::new();
+        let result = eval::lower(self.src, self.labels, expr, &mut nodes)
+            .and_then(|_| self.labels.eval(&nodes));
+        result.map_err(|e| e.report(self.src)).ok()
+    }
+
+    fn parse_amount(&mut self, expr: &IdentInt) -> Option<u8> {
+        let value = self.parse_const(expr.expr)?;
+        match u8::try_from(value) {
+            Ok(amount) => Some(amount),
+            Err(..) => {
+                self.src.report(expr.span, "shift amount is out of range");
+                None
+            }
+        }
+    }
+
+    fn parse_ident(&mut self, expr: &ast::Expr, span: Span) -> Ops {
         if let Some(op) = self.parse_gpr(span) {
             return op.into();
         }
+        if let Some(op) = self.parse_dpr(span) {
+            return op.into();
+        }
         if let Some(op) = self.parse_extend(span, None) {
             return op.into();
         }
         if let Some(op) = self.parse_cond(span) {
             return op.into();
         }
+        if let Some(op) = self.parse_sysreg(span) {
+            return op.into();
+        }
+        if let Some(op) = self.parse_barrier(span) {
+            return op;
+        }
+        if let Some(op) = self.parse_bti_target(span) {
+            return op.into();
+        }
+        if let Some(op) = self.parse_arch_extension(span) {
+            return op;
+        }
+        if let Some(op) = self.parse_target(span) {
+            return op;
+        }
+        if self.allow(Kind::Imm) && self.labels.is_const(self.src.span(span)) {
+            return self.parse_imm(expr).map_or(Ops::Error, Ops::from);
+        }
+        // a register where the instruction only takes a value is not a label of that name
+        if self.gpr(span).is_some() || self.dpr(span).is_some() || self.vector_reg(span).is_some() {
+            let msg = if self.allow(Kind::Imm) {
+                "expected an immediate, the register form is not supported"
+            } else if self.allow(Kind::Label) || self.allow(Kind::AddrLabel) {
+                "expected a label, not a register"
+            } else {
+                "unexpected register"
+            };
+            self.src.report(span, msg);
+            return Ops::Error;
+        }
+        if self.allow(Kind::AddrLabel) {
+            return Ops::AddrLabel(self.labels.key(self.src.span(span)));
+        }
         if let Some(op) = self.parse_label(span) {
             return op.into();
         }
-        todo!()
+        if self.allow(Kind::Imm) {
+            return self.parse_imm(expr).map_or(Ops::Error, Ops::from);
+        }
+        self.src.report(span, "unexpected operand");
+        Ops::Error
+    }
+
+    /// `:pg_hi21:` is the label of `ADRP`, `:lo12:` an immediate
+    fn parse_reloc(&mut self, expr: &ast::Expr, modifier: Span, label: Span) -> Ops {
+        if self.src.span(modifier).eq_ignore_ascii_case("pg_hi21") {
+            if let Some(op) = self.parse_label(label) {
+                return op.into();
+            }
+            self.src
+                .report(modifier, "`:pg_hi21:` can only be used with `ADRP`");
+            return Ops::Error;
+        }
+        self.parse_imm(expr).map_or(Ops::Error, Ops::from)
     }
 
-    fn parse_ident_int(&self, expr: &expr::IdentInt) -> Ops {
-        if let Some(op) = self.parse_shift(*expr) {
+    fn parse_ident_int(&mut self, expr: &IdentInt) -> Ops {
+        if let Some(op) = self.parse_shift(expr) {
             return op.into();
         }
-        if let Some(op) = self.parse_extend(expr.span, Some(expr.int)) {
+        if let Some(op) = self.parse_extend(expr.span, Some(expr)) {
             return op.into();
         }
-        todo!()
+        self.src.report(expr.span, "unexpected operand");
+        Ops::Error
     }
 
     fn allow(&self, kind: Kind) -> bool {
@@ -147,9 +644,37 @@ pub fn parse_args(&mut self, args: &'ast [ast::Expr<'ast>], vec: &mut Vec<Ops>)
         use ast::Expr;
         for arg in args {
             let op = match arg {
-                Expr::Ident { span } => self.parse_ident(*span),
+                Expr::Ident { span } => self.parse_ident(arg, *span),
                 Expr::IdentInt(expr) => self.parse_ident_int(expr),
-                _ => todo!(),
+                Expr::IntLiteral(..) | Expr::Unary { .. } | Expr::Binary { .. }
+                    if self.allow(Kind::FpImm) && !self.allow(Kind::Imm) =>
+                {
+                    self.parse_fp_imm(arg).map_or(Ops::Error, Ops::from)
+                }
+                Expr::IntLiteral(..) | Expr::Unary { .. } | Expr::Binary { .. } => {
+                    self.parse_imm(arg).map_or(Ops::Error, Ops::from)
+                }
+                Expr::FloatLiteral { span, .. } => {
+                    if self.allow(Kind::FpImm) {
+                        self.parse_fp_imm(arg).map_or(Ops::Error, Ops::from)
+                    } else {
+                        self.src.report(*span, "unexpected float");
+                        Ops::Error
+                    }
+                }
+                Expr::Address { args, group, index } => self.parse_address(args, *index, *group),
+                Expr::Reloc { modifier, label } => self.parse_reloc(arg, *modifier, *label),
+                Expr::Vector {
+                    reg,
+                    arrangement,
+                    index,
+                } => self.parse_vector(*reg, *arrangement, *index),
+                Expr::RegList { regs, group, range } => self.parse_reg_list(regs, *group, *range),
+                Expr::String { span } => {
+                    self.src.report(*span, "unexpected string operand");
+                    Ops::Error
+                }
+                Expr::Error => Ops::Error,
             };
             self.narrow.check_next(op.kind());
             vec.push(op);
@@ -161,6 +686,24 @@ pub fn finish(self) -> Result<usize, NarrowError> {
     }
 }
 
+fn expr_span(expr: &ast::Expr) -> Option<Span> {
+    use ast::Expr;
+    match expr {
+        Expr::Ident { span }
+        | Expr::String { span }
+        | Expr::FloatLiteral { span, .. }
+        | Expr::Unary { span, .. }
+        | Expr::Binary { span, .. } => Some(*span),
+        Expr::IntLiteral(int) => Some(int.span),
+        Expr::IdentInt(int) => Some(int.span),
+        Expr::Reloc { modifier, .. } => Some(*modifier),
+        Expr::Address { group, .. } | Expr::RegList { group, .. } => Some(*group),
+        Expr::Vector { reg, .. } => Some(*reg),
+        // already reported
+        Expr::Error => None,
+    }
+}
+
 fn byte_str_to_u8(s: &[u8]) -> Option<u8> {
     let mut acc: u8 = 0;
     for b in s.iter().copied() {
@@ -174,3 +717,149 @@ fn byte_str_to_u8(s: &[u8]) -> Option<u8> {
     }
     Some(acc)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        assembler::parse::{ast::Top, Parser},
+        code::{Source, SourceManager},
+        inst::Param,
+    };
+    use bumpalo::Bump;
+    use std::path::PathBuf;
+
+    const ADDRESS: &[&[Param]] = &[
+        &[Param::Req(Kind::AddrImm)],
+        &[Param::Req(Kind::AddrReg)],
+        &[Param::Req(Kind::AddrLabel)],
+    ];
+
+    const VECTOR: &[&[Param]] = &[
+        &[Param::Req(Kind::Vr)],
+        &[Param::Req(Kind::IdxVr)],
+        &[Param::Req(Kind::VrList)],
+    ];
+
+    /// operand of `address`, `None` if an error was reported
+    fn parse_address(address: &str) -> Option<Ops> {
+        parse_operand(ADDRESS, address)
+    }
+
+    /// single operand of one of the `variants`, `None` if an error was reported
+    fn parse_operand(variants: &'static [&'static [Param]], operand: &str) -> Option<Ops> {
+        let src = SourceManager::new();
+        let file = src.add(Source::new(PathBuf::new(), format!("op {operand}\n")));
+        let bump = Bump::new();
+        let mut parser = Parser::new_in(&src, file, &bump);
+        let Some(Top::Instruction {
+            args: Some(args), ..
+        }) = parser.next()
+        else {
+            panic!("expected an instruction");
+        };
+        let mut labels = LabelResolver::new(&bump);
+        let mut arg_parser = ArgParser::new(&src, &mut labels, NarrowVariant::new(variants));
+        let mut ops = Vec::new();
+        arg_parser.parse_args(args, &mut ops);
+        match src.error_count() {
+            0 => ops.pop(),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn it_parses_addresses() {
+        use IndexMode::*;
+        let imm = |address| match parse_address(address) {
+            Some(Ops::AddrImm { base, offset, mode }) => Some((base, offset.value, mode)),
+            _ => None,
+        };
+        assert!(matches!(imm("[x1]"), Some((GprKind::R(..), 0, Offset))));
+        assert!(matches!(imm("[sp, #-16]!"), Some((GprKind::SP, -16, Pre))));
+        assert!(matches!(imm("[x1], #8 * 2"), Some((GprKind::R(..), 16, Post))));
+        assert!(matches!(imm("[x1, 4]"), Some((_, 4, Offset))));
+
+        let reg = |address| match parse_address(address) {
+            Some(Ops::AddrReg { index, extend, .. }) => {
+                Some((index.size, extend.map(|e| (e.kind, e.left_shift_amount))))
+            }
+            _ => None,
+        };
+        assert!(matches!(reg("[x1, x2]"), Some((GprSize::B8, None))));
+        assert!(matches!(
+            reg("[x1, x2, lsl #3]"),
+            Some((GprSize::B8, Some((ExtendKind::UXTX, Some(3)))))
+        ));
+        assert!(matches!(
+            reg("[x1, w2, SXTW #2]"),
+            Some((GprSize::B4, Some((ExtendKind::SXTW, Some(2)))))
+        ));
+        assert!(matches!(
+            reg("[x1, w2, uxtw]"),
+            Some((GprSize::B4, Some((ExtendKind::UXTW, None))))
+        ));
+
+        assert!(matches!(parse_address("label"), Some(Ops::AddrLabel(..))));
+    }
+
+    #[test]
+    fn it_reports_invalid_addresses() {
+        for address in [
+            "[w1]",
+            "[x1, w2]",
+            "[x1, x2, sxtw]",
+            "[x1, x2]!",
+            "[x1]!",
+            "[x1, #8], #8",
+            "[x1, x2, lsl #1, #4]",
+            "[x1, #4",
+        ] {
+            assert!(parse_address(address).is_none(), "{address}");
+        }
+    }
+
+    #[test]
+    fn it_parses_vectors() {
+        let vector = |operand| match parse_operand(VECTOR, operand) {
+            Some(Ops::Vr { reg, size, lanes }) => Some((reg, size, lanes)),
+            _ => None,
+        };
+        assert_eq!(vector("v0.4s"), Some((0, VecSize::B4, VecLanes::B16)));
+        assert_eq!(vector("V31.8B"), Some((31, VecSize::B1, VecLanes::B8)));
+        assert_eq!(vector("v2.1d"), Some((2, VecSize::B8, VecLanes::B8)));
+
+        let lane = |operand| match parse_operand(VECTOR, operand) {
+            Some(Ops::IdxVr { reg, size, index }) => Some((reg, size, index)),
+            _ => None,
+        };
+        assert_eq!(lane("v2.s[1]"), Some((2, VecSize::B4, 1)));
+        assert_eq!(lane("v3.b[7 + 8]"), Some((3, VecSize::B1, 15)));
+
+        let list = |operand| match parse_operand(VECTOR, operand) {
+            Some(Ops::VrList {
+                reg, count, size, ..
+            }) => Some((reg, count, size)),
+            _ => None,
+        };
+        assert_eq!(list("{v0.4s-v3.4s}"), Some((0, 4, VecSize::B4)));
+        assert_eq!(list("{v1.16b}"), Some((1, 1, VecSize::B1)));
+        assert_eq!(list("{v31.2d, v0.2d}"), Some((31, 2, VecSize::B8)));
+        assert_eq!(list("{v30.8h-v1.8h}"), Some((30, 4, VecSize::B2)));
+
+        for operand in [
+            "v32.4s",
+            "v0.3s",
+            "v0.4s[1]",
+            "v0.d[2]",
+            "v0.q[0]",
+            "{v0.4s-v4.4s}",
+            "{v0.4s, v2.4s}",
+            "{v0.4s, v1.2s}",
+            "{v0.s[1]}",
+            "{v0.4s",
+        ] {
+            assert!(parse_operand(VECTOR, operand).is_none(), "{operand}");
+        }
+    }
+}
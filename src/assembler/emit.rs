@@ -1,46 +1,240 @@
 use super::{
-    arg,
+    arg, eval,
     parse::ast::{self, Top},
 };
 use crate::{
     bitstack::{push_bits_offset_u32, BitStackU32},
-    code,
+    code::{self, Span},
     enum_str::EnumStr,
     inst::{
-        self, apply_label_fixup, label, operand::Ops, Emitter, EncInstr, EncInstrSet, Error,
-        ErrorMacro, Fixup, Mnemonic,
+        self, apply_expr_fixup, apply_label_fixup, dir, label, operand::Ops, Emitter, EncInstr,
+        EncInstrSet, Error, ErrorMacro, ExprKey, Feature, Fixup, Mnemonic, NarrowError,
+        TargetFeatures,
     },
+    obj,
     sparsebin::{Aligned, SparseBin},
 };
 use bit::{BitCt, Int, IntN};
 use bumpalo::Bump;
-use rustc_hash::FxHashMap as HashMap;
+use rustc_hash::{FxHashMap as HashMap, FxHashSet as HashSet};
 use std::{cell::Cell, str::FromStr};
 
-struct LabelResolver<E: Emitter> {
-    intern: label::Intern,
+/// value of a symbol assigned with `.equ`, `.set` or `=`
+#[derive(Clone, Copy)]
+struct Const {
+    value: i64,
+    /// assigned with `.set` or `=`, so it can be assigned again
+    reassignable: bool,
+}
+
+pub struct LabelResolver<'bump> {
+    pub intern: label::Intern,
     addr_map: HashMap<label::Key, u64>,
-    fixups: Vec<Fixup<E, label::Key, u64>>,
+    consts: HashMap<label::Key, Const>,
+    /// labels and constants in the order they were first defined
+    order: Vec<label::Key>,
+    /// named by `.global`
+    globals: HashSet<label::Key>,
+    /// never defined, but relocations refer to them, in the order they were used
+    externs: Vec<label::Key>,
+    /// how many times each numeric local label was defined so far
+    locals: HashMap<u32, u32>,
+    /// expressions that referenced a label before it was defined
+    exprs: Vec<&'bump [eval::Node]>,
+    bump: &'bump Bump,
 }
 
-impl LabelResolver<Emit<'_, '_>> {
-    pub fn new() -> Self {
+impl<'bump> LabelResolver<'bump> {
+    pub fn new(bump: &'bump Bump) -> Self {
         Self {
             intern: label::Intern::new(),
             addr_map: HashMap::default(),
-            fixups: Vec::new(),
+            consts: HashMap::default(),
+            order: Vec::new(),
+            globals: HashSet::default(),
+            externs: Vec::new(),
+            locals: HashMap::default(),
+            exprs: Vec::new(),
+            bump,
+        }
+    }
+
+    /// key of a label reference, `1b` and `1f` are keyed by the definition of `1:` they refer to
+    pub fn key(&mut self, name: &str) -> label::Key {
+        self.local_ref(name)
+            .unwrap_or_else(|| self.intern.get_or_intern(name))
+    }
+
+    fn local_ref(&mut self, name: &str) -> Option<label::Key> {
+        let (num, dir) = name.split_at(name.len().checked_sub(1)?);
+        let num: u32 = num.parse().ok()?;
+        let defined = self.locals.get(&num).copied().unwrap_or(0);
+        let index = match dir {
+            "b" => defined.checked_sub(1)?,
+            "f" => defined,
+            _ => return None,
+        };
+        Some(self.intern.get_or_intern(format!("{num}:{index}")))
+    }
+
+    /// `None` if the label is already defined, numeric local labels can be defined again
+    pub fn define(&mut self, name: &str, addr: u64) -> Option<label::Key> {
+        let key = match name.parse::<u32>() {
+            Ok(num) => {
+                let defined = self.locals.entry(num).or_default();
+                let key = self.intern.get_or_intern(format!("{num}:{defined}"));
+                *defined += 1;
+                key
+            }
+            Err(..) => self.intern.get_or_intern(name),
+        };
+        if self.addr_map.contains_key(&key) || self.consts.contains_key(&key) {
+            return None;
+        }
+        self.addr_map.insert(key, addr);
+        self.order.push(key);
+        Some(key)
+    }
+
+    /// `false` if the name is a label, or a constant that can't be assigned again
+    pub fn define_const(&mut self, key: label::Key, value: i64, reassignable: bool) -> bool {
+        if self.addr_map.contains_key(&key) {
+            return false;
+        }
+        match self.consts.get_mut(&key) {
+            Some(c) if c.reassignable && reassignable => c.value = value,
+            Some(..) => return false,
+            None => {
+                self.consts.insert(
+                    key,
+                    Const {
+                        value,
+                        reassignable,
+                    },
+                );
+                self.order.push(key);
+            }
+        }
+        true
+    }
+
+    pub fn is_const(&mut self, name: &str) -> bool {
+        let key = self.key(name);
+        self.consts.contains_key(&key)
+    }
+
+    pub fn set_global(&mut self, key: label::Key) {
+        self.globals.insert(key);
+    }
+
+    pub fn is_global(&self, key: label::Key) -> bool {
+        self.globals.contains(&key)
+    }
+
+    /// address of a label, `None` for a constant
+    pub fn addr(&self, key: label::Key) -> Option<u64> {
+        self.addr_map.get(&key).copied()
+    }
+
+    /// address of a label or value of a constant
+    pub fn value(&self, key: label::Key) -> Option<i64> {
+        match self.addr_map.get(&key) {
+            Some(&addr) => Some(addr as i64),
+            None => self.consts.get(&key).map(|c| c.value),
         }
     }
+
+    /// symbol a relocation refers to, an undefined symbol is added as an external,
+    /// `None` for a local label which is never visible to the linker
+    pub fn reloc_symbol(&mut self, key: label::Key) -> Option<String> {
+        let name = self.intern.resolve(key);
+        // `1:0`, or `1b` if no `1:` was defined before it
+        if name.contains(':') || name.starts_with(|c: char| c.is_ascii_digit()) {
+            return None;
+        }
+        if self.value(key).is_none() && !self.externs.contains(&key) {
+            self.externs.push(key);
+        }
+        Some(name.to_string())
+    }
+
+    /// name as written, `1f` for a local label that is never defined
+    pub fn name(&self, key: label::Key) -> String {
+        let name = self.intern.resolve(key);
+        match name.split_once(':') {
+            Some((num, _)) => format!("{num}f"),
+            None => name.to_string(),
+        }
+    }
+
+    pub fn eval(&self, nodes: &[eval::Node]) -> Result<i64, eval::Error> {
+        eval::eval(nodes, |key| self.value(key))
+    }
+
+    pub fn defer(&mut self, nodes: &[eval::Node]) -> ExprKey {
+        let key = ExprKey(self.exprs.len().try_into().unwrap());
+        // `.set` can assign a constant again before the expression is evaluated
+        let nodes = self
+            .bump
+            .alloc_slice_fill_iter(nodes.iter().map(|&node| match node {
+                eval::Node::Label(key, _) => match self.consts.get(&key) {
+                    Some(c) => eval::Node::Value(c.value),
+                    None => node,
+                },
+                node => node,
+            }));
+        self.exprs.push(nodes);
+        key
+    }
+
+    pub fn eval_deferred(&self, key: ExprKey) -> Result<i64, eval::Error> {
+        self.eval(self.exprs[key.0 as usize])
+    }
+
+    /// labels and constants, numeric local labels are left out, then the external symbols
+    pub fn symbols(&self) -> impl Iterator<Item = obj::Symbol> + '_ {
+        let externs = self.externs.iter().map(|&key| obj::Symbol {
+            name: self.intern.resolve(key).to_string(),
+            value: obj::SymbolValue::Undefined,
+            global: true,
+        });
+        let defined = self.order.iter().filter_map(|&key| {
+            let name = self.intern.resolve(key);
+            if name.contains(':') {
+                return None;
+            }
+            let value = match self.addr_map.get(&key) {
+                Some(&addr) => obj::SymbolValue::Text(addr),
+                None => obj::SymbolValue::Absolute(self.consts[&key].value),
+            };
+            Some(obj::Symbol {
+                name: name.to_string(),
+                value,
+                global: self.globals.contains(&key),
+            })
+        });
+        defined.chain(externs)
+    }
 }
 
 pub struct Emit<'bump, 'src> {
     bin: SparseBin,
     pc: u64,
     bit_stack: BitStackU32,
-    labels: LabelResolver<Self>,
+    labels: LabelResolver<'bump>,
+    /// span of the instruction being emitted, for errors when the fixup is applied
+    span: Span,
+    label_fixups: Vec<(Span, Fixup<Self, label::Key, u64>)>,
+    expr_fixups: Vec<(Span, Fixup<Self, ExprKey, i64>)>,
+    relocations: Vec<obj::Relocation>,
     bump: &'bump Bump,
     ops_vec: Cell<Vec<Ops>>,
-    src: &'src code::Source,
+    conds: dir::CondStack,
+    /// macro expansions being assembled with the depth of `conds` where each started
+    expansions: Vec<(u32, usize)>,
+    /// extensions that instructions can use, set by `.arch`, `.cpu` and `.arch_extension`
+    features: TargetFeatures,
+    src: &'src code::SourceManager,
 }
 
 impl Emitter for Emit<'_, '_> {
@@ -84,11 +278,39 @@ fn end_instr(&mut self) {
     }
 
     fn resolve_label(&mut self, key: label::Key) -> Option<u64> {
-        self.labels.addr_map.get(&key).copied()
+        self.labels.value(key).map(|value| value as u64)
+    }
+
+    fn define_symbol(&mut self, key: label::Key, value: i64, reassignable: bool) -> bool {
+        self.labels.define_const(key, value, reassignable)
+    }
+
+    fn set_global(&mut self, key: label::Key) {
+        self.labels.set_global(key);
     }
 
     fn push_label_fixup(&mut self, fixup: Fixup<Self, label::Key, u64>) {
-        self.labels.fixups.push(fixup);
+        self.label_fixups.push((self.span, fixup));
+    }
+
+    fn span(&self) -> Span {
+        self.span
+    }
+
+    fn conds(&mut self) -> &mut dir::CondStack {
+        &mut self.conds
+    }
+
+    fn features(&mut self) -> &mut TargetFeatures {
+        &mut self.features
+    }
+
+    fn resolve_expr(&mut self, key: ExprKey) -> Option<i64> {
+        self.labels.eval_deferred(key).ok()
+    }
+
+    fn push_expr_fixup(&mut self, fixup: Fixup<Self, ExprKey, i64>) {
+        self.expr_fixups.push((self.span, fixup));
     }
 }
 
@@ -111,13 +333,20 @@ fn deref_mut(&mut self) -> &mut Self::Target {
 }
 
 impl<'bump, 'src> Emit<'bump, 'src> {
-    pub fn new_in(src: &'src code::Source, bump: &'bump Bump) -> Self {
+    pub fn new_in(src: &'src code::SourceManager, bump: &'bump Bump) -> Self {
         Emit {
             bin: SparseBin::new(),
             pc: 0,
             bit_stack: BitStackU32::new(),
-            labels: LabelResolver::new(),
+            labels: LabelResolver::new(bump),
+            span: Span::default(),
+            label_fixups: Vec::new(),
+            expr_fixups: Vec::new(),
+            relocations: Vec::new(),
             ops_vec: Cell::new(Vec::new()),
+            conds: dir::CondStack::default(),
+            expansions: Vec::new(),
+            features: TargetFeatures::default(),
             bump,
             src,
         }
@@ -143,6 +372,7 @@ pub fn process<'ast>(&mut self, top: &'ast ast::Top<'ast>) {
                     return;
                 };
                 let narrow = inst::narrow_variant(mnem);
+                self.span = *mnem_span;
 
                 let arg_len = args.map_or(0, |a| a.len());
                 let mut ops_vec = self.take_ops_vec();
@@ -150,19 +380,22 @@ pub fn process<'ast>(&mut self, top: &'ast ast::Top<'ast>) {
                 ops_vec.clear();
                 ops_vec.reserve_exact(arg_len);
 
-                let mut arg_parser = arg::ArgParser::new(self.src, &mut self.labels.intern, narrow);
+                let mut arg_parser = arg::ArgParser::new(self.src, &mut self.labels, narrow);
                 if let Some(args) = args {
                     arg_parser.parse_args(args, &mut ops_vec);
                 }
 
-                if let Ok(variant) = arg_parser.finish().map_err(|e| {
-                    self.src
-                        .report(*mnem_span, format_args!("TODO: NarrowError {:?}", e))
-                }) {
-                    if let Err(e) = inst::get_variant_and_emit(mnem, variant, ops_vec.iter(), self)
-                    {
-                        self.handle_error(e, *mnem_span);
+                // operand errors are already reported
+                let has_error = ops_vec.iter().any(|op| matches!(op, Ops::Error));
+                match arg_parser.finish() {
+                    Ok(variant) => self.emit_instr(mnem, 1 << variant, &ops_vec, *mnem_span),
+                    Err(NarrowError::Multiple(variants)) => {
+                        self.emit_instr(mnem, variants, &ops_vec, *mnem_span)
                     }
+                    Err(e) if !has_error => self
+                        .src
+                        .report(*mnem_span, format_args!("TODO: NarrowError {:?}", e)),
+                    Err(..) => (),
                 }
 
                 self.set_ops_vec(ops_vec);
@@ -177,28 +410,14 @@ pub fn process<'ast>(&mut self, top: &'ast ast::Top<'ast>) {
                     self.src.report(*name_span, "unknown directive");
                     return;
                 };
-
-                let arg_len = args.map(|a| a.len()).unwrap_or(0);
-                let mut ops_vec = self.take_ops_vec();
-
-                ops_vec.clear();
-                ops_vec.reserve_exact(arg_len);
-
-                let narrow = inst::dir::narrow_variant(name);
-                let mut arg_parser = arg::ArgParser::new(self.src, &mut self.labels.intern, narrow);
-                args.map(|args| arg_parser.parse_args(args, &mut ops_vec));
-                inst::dir::select_and_run(self, ops_vec.iter()).unwrap_or_else(|e| {
-                    self.src
-                        .report(*name_span, format_args!("TODO: DirectiveError {:?}", e));
-                });
-                self.set_ops_vec(ops_vec);
+                self.directive(name, *name_span, *args);
             }
+            Top::Assign { eq, args } => self.directive(dir::Name::set, *eq, Some(args)),
             Top::Label(span) => {
                 let str = self.src.span(*span);
-                let key = self.labels.intern.get_or_intern(str);
-                if self.labels.addr_map.insert(key, self.pc()).is_some() {
-                    //self.src.report(*span, "label already defined");
-                    todo!("error if label already exists");
+                if self.labels.define(str, self.pc).is_none() {
+                    self.src
+                        .report(*span, format_args!("label `{str}` is already defined"));
                 }
             }
             Top::Error => {
@@ -207,21 +426,219 @@ pub fn process<'ast>(&mut self, top: &'ast ast::Top<'ast>) {
         }
     }
 
-    fn handle_error(&self, ErrorMacro(e, s): ErrorMacro, span: code::Span) {
-        todo!("src.report error here, with span from process")
+    fn directive<'ast>(
+        &mut self,
+        name: dir::Name,
+        span: Span,
+        args: Option<&'ast [ast::Expr<'ast>]>,
+    ) {
+        let arg_len = args.map(|a| a.len()).unwrap_or(0);
+        let mut ops_vec = self.take_ops_vec();
+
+        ops_vec.clear();
+        ops_vec.reserve_exact(arg_len);
+
+        let narrow = inst::dir::narrow_variant(name);
+        self.span = span;
+        let mut arg_parser = arg::ArgParser::new(self.src, &mut self.labels, narrow);
+        if let Some(args) = args {
+            arg_parser.parse_args(args, &mut ops_vec);
+        }
+
+        let has_error = ops_vec.iter().any(|op| matches!(op, Ops::Error));
+        match arg_parser.finish() {
+            Ok(..) => {
+                if let Err(e) = inst::dir::select_and_run(self, name, ops_vec.iter()) {
+                    self.report_dir_error(e, span);
+                }
+            }
+            Err(..) if has_error => (),
+            Err(..) => self.src.report(
+                span,
+                format_args!("`.{}` expects {}", name.to_str_lower(), name.expects()),
+            ),
+        }
+        self.set_ops_vec(ops_vec);
+    }
+
+    /// inside a conditional block that is not assembled, the parser has to skip it
+    pub fn is_skipping(&self) -> bool {
+        !self.conds.is_active()
+    }
+
+    /// follow the macro expansions of the preprocessor, a conditional block opened in an
+    /// expansion is closed when it ends, by `.exitm` or its last line
+    pub fn sync_expansions(&mut self, active: &[u32]) {
+        let common = self
+            .expansions
+            .iter()
+            .zip(active)
+            .take_while(|((id, _), active)| id == *active)
+            .count();
+        if let Some(&(_, depth)) = self.expansions.get(common) {
+            self.conds.unwind(depth);
+            self.expansions.truncate(common);
+        }
+        let depth = self.conds.depth();
+        self.expansions
+            .extend(active[common..].iter().map(|&id| (id, depth)));
+    }
+
+    fn report_dir_error(&self, e: dir::Error, span: Span) {
+        match e {
+            dir::Error::NotConstant => self
+                .src
+                .report(span, "value must not depend on a label defined later"),
+            dir::Error::NoIf => self.src.report(span, "no `.if` to match"),
+            dir::Error::AfterElse => self
+                .src
+                .report(span, "`.else` was already used in this `.if`"),
+            dir::Error::Redefined(key) => self.src.report(
+                span,
+                format_args!("symbol `{}` is already defined", self.labels.name(key)),
+            ),
+        }
+    }
+
+    /// apply the fixups of labels and expressions that were used before they were defined, a
+    /// global label is left to the linker like an undefined one
+    pub fn finish(mut self) -> obj::Object {
+        for span in self.conds.open() {
+            self.src.report(span, "`.if` without `.endif`");
+        }
+        let pc = self.pc;
+        for (span, fixup) in std::mem::take(&mut self.label_fixups) {
+            let key = fixup.key();
+            if let Some(kind) = fixup.reloc() {
+                if !kind.is_pc_relative()
+                    || self.labels.value(key).is_none()
+                    || self.labels.is_global(key)
+                {
+                    self.relocate(span, fixup.pc(), kind, key);
+                    continue;
+                }
+            }
+            match apply_label_fixup(&mut self, fixup) {
+                Ok(()) => (),
+                Err(Error::Resolve) => self.src.report(
+                    span,
+                    format_args!("undefined label `{}`", self.labels.name(key)),
+                ),
+                Err(e) => self.src.report(
+                    span,
+                    format_args!("offset to label `{}`: {}", self.labels.name(key), e),
+                ),
+            }
+        }
+        for (span, fixup) in std::mem::take(&mut self.expr_fixups) {
+            if let Err(e) = self.labels.eval_deferred(fixup.key()) {
+                e.report(self.src);
+                continue;
+            }
+            if let Err(e) = apply_expr_fixup(&mut self, fixup) {
+                self.src.report(span, e);
+            }
+        }
+        self.pc = pc;
+        let symbols = self.labels.symbols().collect();
+        obj::Object {
+            text: self.bin,
+            text_size: self.pc,
+            symbols,
+            relocations: self.relocations,
+            gnu_property: obj::GnuProperty::default(),
+        }
+    }
+
+    /// leave the value to the linker, a label defined here is relative to the start of the code,
+    /// unless it is global and another object could replace it
+    fn relocate(&mut self, span: Span, offset: u64, kind: obj::RelocKind, key: label::Key) {
+        let (symbol, addend) = match self.labels.addr(key) {
+            Some(addr) if !self.labels.is_global(key) => (None, addr as i64),
+            _ => match self.labels.reloc_symbol(key) {
+                Some(name) => (Some(name), 0),
+                None => {
+                    self.src.report(
+                        span,
+                        format_args!("undefined label `{}`", self.labels.name(key)),
+                    );
+                    return;
+                }
+            },
+        };
+        self.relocations.push(obj::Relocation {
+            offset,
+            kind,
+            symbol,
+            addend,
+        });
+    }
+
+    /// the first of `variants` that can encode the operands, like the shifted register form of
+    /// `ADD` unless an operand is `sp`, which only the extended register form takes
+    fn emit_instr(&mut self, mnem: Mnemonic, variants: u32, ops: &[Ops], span: code::Span) {
+        let mut first_error = None;
+        for variant in (0..u32::BITS as usize).filter(|i| variants & (1 << i) != 0) {
+            let required = inst::required_features(mnem, variant);
+            if let Some(feature) = required.missing(self.features) {
+                first_error.get_or_insert(ErrorMacro(Error::MissingFeature(feature), ""));
+                continue;
+            }
+            let fixups = (self.label_fixups.len(), self.expr_fixups.len());
+            match inst::get_variant_and_emit(mnem, variant, ops.iter(), self) {
+                Ok(()) => return,
+                Err(e) => {
+                    self.label_fixups.truncate(fixups.0);
+                    self.expr_fixups.truncate(fixups.1);
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+        if let Some(e) = first_error {
+            self.handle_error(e, span);
+        }
+    }
+
+    fn handle_error(&self, e: ErrorMacro, span: code::Span) {
+        let operand = e.operand();
+        match (e.0, operand) {
+            (Error::MissingFeature(feature), _) => self.missing_feature(feature, span),
+            (e, Some(idx)) => self.src.report(
+                span,
+                format_args!("operand {} of `{}`: {}", idx + 1, self.src.span(span), e),
+            ),
+            (e, None) => self
+                .src
+                .report(span, format_args!("`{}`: {}", self.src.span(span), e)),
+        }
+    }
+
+    fn missing_feature(&self, feature: Feature, mnem: code::Span) {
+        self.src.report(
+            mnem,
+            format_args!(
+                "`{}` requires the `{}` extension",
+                self.src.span(mnem),
+                feature.to_str_lower()
+            ),
+        );
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{assembler::parse::Parser, code::Source};
+    use crate::{
+        assembler::parse::Parser,
+        code::{Source, SourceManager},
+    };
     use lasso::Rodeo;
     use std::{hash::BuildHasherDefault, path::PathBuf};
 
     #[test]
     fn try_it() {
-        let src = Source::new(PathBuf::new(), String::new());
+        let src = SourceManager::new();
+        src.add(Source::new(PathBuf::new(), String::new()));
         let emit_alloc = Bump::new();
         let mut rodeo = label::Intern::new();
         let label = rodeo.get_or_intern_static("my_label");
@@ -243,10 +660,10 @@ fn try_it() {
             "0b{:032b}",
             e.bin.get_u32(Aligned::new(addr as usize).unwrap())
         );
-        println!("{:?}", e.labels.fixups[0]);
-        let fixup = e.labels.fixups[0].clone();
+        println!("{:?}", e.label_fixups[0].1);
+        let fixup = e.label_fixups[0].1.clone();
         apply_label_fixup(&mut e, fixup).unwrap();
-        let fixup2 = e.labels.fixups[1].clone();
+        let fixup2 = e.label_fixups[1].1.clone();
         apply_label_fixup(&mut e, fixup2).unwrap();
         println!(
             "0b{:032b}",
@@ -0,0 +1,193 @@
+use super::{
+    emit::LabelResolver,
+    parse::ast::{self, BinaryOp, UnaryOp},
+};
+use crate::{
+    code::{self, Span},
+    inst::label,
+};
+use smallvec::SmallVec;
+
+/// operand expression flattened to postfix order, with identifiers interned as labels,
+/// so it can be stored and evaluated again once every label is defined
+#[derive(Debug, Clone, Copy)]
+pub enum Node {
+    Value(i64),
+    Label(label::Key, Span),
+    Unary(UnaryOp),
+    Binary(BinaryOp, Span),
+}
+
+pub type Nodes = SmallVec<[Node; 8]>;
+
+#[derive(Debug, Clone, Copy)]
+pub enum Error {
+    /// label is not defined, or not defined yet
+    Undefined(label::Key, Span),
+    DivideByZero(Span),
+    ShiftOutOfRange(Span),
+    /// can't be part of an expression, like a string or float
+    Invalid(Span),
+    /// parser already reported the error
+    Reported,
+}
+
+impl Error {
+    pub fn report(self, src: &code::SourceManager) {
+        match self {
+            Error::Undefined(_, span) => {
+                src.report(span, format_args!("undefined label `{}`", src.span(span)))
+            }
+            Error::DivideByZero(span) => src.report(span, "division by zero"),
+            Error::ShiftOutOfRange(span) => src.report(span, "shift amount must be 0 to 63"),
+            Error::Invalid(span) => src.report(span, "not allowed in an expression"),
+            Error::Reported => (),
+        }
+    }
+}
+
+pub fn lower(
+    src: &code::SourceManager,
+    labels: &mut LabelResolver,
+    expr: &ast::Expr<'_>,
+    out: &mut Nodes,
+) -> Result<(), Error> {
+    use ast::Expr;
+    match expr {
+        Expr::IntLiteral(int) => out.push(Node::Value(int.value as i64)),
+        Expr::Ident { span } => {
+            let key = labels.key(src.span(*span));
+            out.push(Node::Label(key, *span));
+        }
+        Expr::Unary { op, expr, .. } => {
+            lower(src, labels, expr, out)?;
+            out.push(Node::Unary(*op));
+        }
+        Expr::Binary { op, lhs, rhs, span } => {
+            lower(src, labels, lhs, out)?;
+            lower(src, labels, rhs, out)?;
+            out.push(Node::Binary(*op, *span));
+        }
+        Expr::FloatLiteral { span, .. } | Expr::String { span } => {
+            return Err(Error::Invalid(*span));
+        }
+        Expr::IdentInt(ident_int) => return Err(Error::Invalid(ident_int.span)),
+        Expr::Reloc { modifier, .. } => return Err(Error::Invalid(*modifier)),
+        Expr::Address { group, .. } | Expr::RegList { group, .. } => {
+            return Err(Error::Invalid(*group))
+        }
+        Expr::Vector { reg, .. } => return Err(Error::Invalid(*reg)),
+        Expr::Error => return Err(Error::Reported),
+    }
+    Ok(())
+}
+
+/// `value` resolves a label to its value, `None` if it is not defined
+pub fn eval<F>(nodes: &[Node], mut value: F) -> Result<i64, Error>
+where
+    F: FnMut(label::Key) -> Option<i64>,
+{
+    let mut stack = SmallVec::<[i64; 8]>::new();
+    for node in nodes.iter().copied() {
+        let result = match node {
+            Node::Value(v) => v,
+            Node::Label(key, span) => value(key).ok_or(Error::Undefined(key, span))?,
+            Node::Unary(op) => {
+                let v = stack.pop().expect("postfix order");
+                match op {
+                    UnaryOp::Neg => v.wrapping_neg(),
+                    UnaryOp::Not => !v,
+                    UnaryOp::LogicalNot => i64::from(v == 0),
+                }
+            }
+            Node::Binary(op, span) => {
+                let rhs = stack.pop().expect("postfix order");
+                let lhs = stack.pop().expect("postfix order");
+                binary(op, lhs, rhs, span)?
+            }
+        };
+        stack.push(result);
+    }
+    debug_assert_eq!(stack.len(), 1);
+    Ok(stack.pop().expect("empty expression"))
+}
+
+fn binary(op: BinaryOp, lhs: i64, rhs: i64, span: Span) -> Result<i64, Error> {
+    let shift = || {
+        u32::try_from(rhs)
+            .ok()
+            .filter(|&amount| amount < i64::BITS)
+            .ok_or(Error::ShiftOutOfRange(span))
+    };
+    Ok(match op {
+        BinaryOp::Mul => lhs.wrapping_mul(rhs),
+        BinaryOp::Div | BinaryOp::Rem if rhs == 0 => return Err(Error::DivideByZero(span)),
+        BinaryOp::Div => lhs.wrapping_div(rhs),
+        BinaryOp::Rem => lhs.wrapping_rem(rhs),
+        BinaryOp::Add => lhs.wrapping_add(rhs),
+        BinaryOp::Sub => lhs.wrapping_sub(rhs),
+        BinaryOp::Shl => lhs << shift()?,
+        BinaryOp::Shr => lhs >> shift()?,
+        BinaryOp::Lt => -i64::from(lhs < rhs),
+        BinaryOp::Le => -i64::from(lhs <= rhs),
+        BinaryOp::Gt => -i64::from(lhs > rhs),
+        BinaryOp::Ge => -i64::from(lhs >= rhs),
+        BinaryOp::Eq => -i64::from(lhs == rhs),
+        BinaryOp::Ne => -i64::from(lhs != rhs),
+        BinaryOp::And => lhs & rhs,
+        BinaryOp::Xor => lhs ^ rhs,
+        BinaryOp::Or => lhs | rhs,
+        BinaryOp::LogicalAnd => i64::from(lhs != 0 && rhs != 0),
+        BinaryOp::LogicalOr => i64::from(lhs != 0 || rhs != 0),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assembler::parse::Parser;
+    use bumpalo::Bump;
+    use std::path::PathBuf;
+
+    fn fold(text: &str) -> Result<i64, Error> {
+        let src = code::SourceManager::new();
+        let file = src.add(code::Source::new(PathBuf::new(), format!("MOV {text}\n")));
+        let bump = Bump::new();
+        let mut parser = Parser::new_in(&src, file, &bump);
+        let Some(ast::Top::Instruction {
+            args: Some([expr]), ..
+        }) = parser.next()
+        else {
+            panic!("expected one operand");
+        };
+        let mut labels = LabelResolver::new(&bump);
+        let mut nodes = Nodes::new();
+        lower(&src, &mut labels, expr, &mut nodes)?;
+        eval(&nodes, |key| {
+            (labels.intern.resolve(key) == "start").then_some(0x40)
+        })
+    }
+
+    #[test]
+    fn it_folds_precedence() {
+        assert_eq!(fold("#(16 + 16) * 2").unwrap(), 64);
+        assert_eq!(fold("#1 << 12").unwrap(), 4096);
+        assert_eq!(fold("#1 + 2 * 3 << 1").unwrap(), 13);
+        assert_eq!(fold("#1 << 2 + 1").unwrap(), 5);
+        assert_eq!(fold("#16 >> 2 - 1").unwrap(), 3);
+        assert_eq!(fold("#1 | 2 + 4 & 6").unwrap(), 7);
+        assert_eq!(fold("#2 > 1").unwrap(), -1);
+        assert_eq!(fold("#0xff00 | 0x0f & ~0").unwrap(), 0xff0f);
+        assert_eq!(fold("#-16 / 4 % 3").unwrap(), -1);
+        assert_eq!(fold("#-(start - 0x10)").unwrap(), -0x30);
+        assert_eq!(fold("#1 + 1 == 2 && start >= 0x40").unwrap(), 1);
+        assert_eq!(fold("#!(3 < 2 || 4 != 4) << 1").unwrap(), 2);
+    }
+
+    #[test]
+    fn it_reports_errors() {
+        assert!(matches!(fold("#1 / (2 - 2)"), Err(Error::DivideByZero(..))));
+        assert!(matches!(fold("#1 << 64"), Err(Error::ShiftOutOfRange(..))));
+        assert!(matches!(fold("#end - start"), Err(Error::Undefined(..))));
+    }
+}
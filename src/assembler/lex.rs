@@ -1,4 +1,9 @@
-use crate::{code, code::Span, code_stream::SourceStream, stream::Stream};
+use crate::{
+    code,
+    code::Span,
+    code_stream::SourceStream,
+    stream::{MultiPeek, Stream},
+};
 use std::{borrow::BorrowMut, fmt};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -22,17 +27,51 @@ pub enum TokenKind {
     Error(ErrorKind),
     Int(IntRadix),
     Float,
+    /// `1b` or `1f`, the closest definition of `1:` before or after
+    LocalLabel,
     Colon,
     Dot,
     Comma,
+    Hash,
     LeftSquareBracket,
     RightSquareBracket,
+    LeftCurlyBracket,
+    RightCurlyBracket,
+    LeftParen,
+    RightParen,
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Percent,
+    ShiftLeft,
+    ShiftRight,
+    Ampersand,
+    Pipe,
+    Caret,
+    Tilde,
+    Equals,
+    EqualsEquals,
+    Bang,
+    BangEquals,
+    Less,
+    LessEquals,
+    Greater,
+    GreaterEquals,
+    AmpersandAmpersand,
+    PipePipe,
+    Backslash,
+    At,
 }
 
 impl TokenKind {
     pub const fn is_int(&self) -> bool {
         matches!(self, Self::Int(..))
     }
+    /// tokens that can be pasted together into one identifier
+    pub const fn is_word(&self) -> bool {
+        matches!(self, Self::Identifier | Self::Int(..))
+    }
 }
 
 pub fn variant_eq<Enum>(lhs: &Enum, rhs: &Enum) -> bool {
@@ -59,33 +98,60 @@ pub struct Scanner<'a> {
 impl<'a> Scanner<'a> {
     #[must_use]
     pub fn new(source: &'a code::Source) -> Self {
-        Scanner {
-            source,
-            it: SourceStream::new(source.str()),
-        }
+        let mut it = SourceStream::new(source.str());
+        it.location = code::Loc::start_of(source.file());
+        Scanner { source, it }
     }
-    // called after consuming '#'
-    fn parse_number(&mut self) -> TokenKind {
-        if self.it.next_if(|&c| c.is_ascii_digit()).is_none() {
-            return TokenKind::Error(ErrorKind::ExpectedDigit);
-        }
+    // called after consuming the first digit
+    fn parse_number(&mut self, first: char) -> TokenKind {
         let base = match self.it.peek() {
-            Some('x') => {
+            Some('x') if first == '0' => {
                 self.it.next();
                 IntRadix::Hex
             }
-            Some('b') => {
+            // otherwise `0b` refers to the local label `0:`
+            Some('b') if first == '0' && matches!(self.it.peek_by(1), Some('0' | '1')) => {
                 self.it.next();
                 IntRadix::Bin
             }
-            Some('.') => IntRadix::Dec,
-            Some('0'..='9' | '_') => IntRadix::Dec,
-            _ => return TokenKind::Int(IntRadix::Dec),
+            _ => IntRadix::Dec,
         };
+        if base != IntRadix::Dec && self.it.next_while(|&c| c.is_digit(base as u32)) == 0 {
+            return TokenKind::Error(ErrorKind::ExpectedDigit);
+        }
         self.it.next_while(|&c| c.is_digit(base as u32) || c == '_');
 
-        if self.it.next_if_eq('.').is_some() {
+        if base == IntRadix::Dec
+            && matches!(self.it.peek(), Some('b' | 'f'))
+            && !self
+                .it
+                .peek_by(1)
+                .is_some_and(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            self.it.next();
+            return TokenKind::LocalLabel;
+        }
+
+        let mut float = self.it.next_if_eq('.').is_some();
+        if float {
             self.it.next_while(|&c| c.is_ascii_digit() || c == '_');
+        }
+        // an exponent needs its digits, `1e` is still `1` followed by `e`
+        if base == IntRadix::Dec && matches!(self.it.peek_by(0), Some('e' | 'E')) {
+            let sign = usize::from(matches!(self.it.peek_by(1), Some('+' | '-')));
+            if self
+                .it
+                .peek_by(1 + sign)
+                .is_some_and(|c| c.is_ascii_digit())
+            {
+                for _ in 0..=sign {
+                    self.it.next();
+                }
+                self.it.next_while(|&c| c.is_ascii_digit() || c == '_');
+                float = true;
+            }
+        }
+        if float {
             TokenKind::Float
         } else {
             TokenKind::Int(base)
@@ -119,7 +185,7 @@ fn skip_space(&mut self) {
                 ' ' | '\t' => {
                     self.it.next();
                 }
-                '/' if self.it.next_if_eq('/').is_some() => {
+                '/' if self.it.peek_by(1) == Some('/') => {
                     self.it.next_till_newline();
                     break;
                 }
@@ -134,9 +200,37 @@ fn parse_token(&mut self) -> TokenKind {
             '.' => TokenKind::Dot,
             '[' => TokenKind::LeftSquareBracket,
             ']' => TokenKind::RightSquareBracket,
+            '{' => TokenKind::LeftCurlyBracket,
+            '}' => TokenKind::RightCurlyBracket,
             ',' => TokenKind::Comma,
+            '#' => TokenKind::Hash,
+            '(' => TokenKind::LeftParen,
+            ')' => TokenKind::RightParen,
+            '+' => TokenKind::Plus,
+            '-' => TokenKind::Minus,
+            '*' => TokenKind::Star,
+            '/' => TokenKind::Slash,
+            '%' => TokenKind::Percent,
+            '&' if self.it.next_if_eq('&').is_some() => TokenKind::AmpersandAmpersand,
+            '&' => TokenKind::Ampersand,
+            '|' if self.it.next_if_eq('|').is_some() => TokenKind::PipePipe,
+            '|' => TokenKind::Pipe,
+            '^' => TokenKind::Caret,
+            '~' => TokenKind::Tilde,
+            '=' if self.it.next_if_eq('=').is_some() => TokenKind::EqualsEquals,
+            '=' => TokenKind::Equals,
+            '!' if self.it.next_if_eq('=').is_some() => TokenKind::BangEquals,
+            '!' => TokenKind::Bang,
+            '\\' => TokenKind::Backslash,
+            '@' => TokenKind::At,
+            '<' if self.it.next_if_eq('<').is_some() => TokenKind::ShiftLeft,
+            '<' if self.it.next_if_eq('=').is_some() => TokenKind::LessEquals,
+            '<' => TokenKind::Less,
+            '>' if self.it.next_if_eq('>').is_some() => TokenKind::ShiftRight,
+            '>' if self.it.next_if_eq('=').is_some() => TokenKind::GreaterEquals,
+            '>' => TokenKind::Greater,
             '\r' if self.it.next_if_eq('\n').is_some() => TokenKind::Newline,
-            '#' => self.parse_number(),
+            c if c.is_ascii_digit() => self.parse_number(c),
             c @ ('"' | '\'') => self.parse_string(c),
             c if c.is_ascii_alphabetic() || c == '_' => self.parse_ident(),
             _ => todo!("error unexpected token"),
@@ -1,32 +1,946 @@
 mod arg;
 mod emit;
+mod eval;
 mod lex;
 mod parse;
+mod preproc;
 
-use crate::code::Source;
+pub use crate::code::{FileId, SourceManager};
+use crate::{
+    inst::{Emitter, TargetFeatures},
+    obj::Object,
+};
 use bumpalo::Bump;
 
-pub fn assemble(source: Source) {
+/// assemble `file` for `armv8-a`, files it includes are loaded into `sources`
+pub fn assemble(sources: &SourceManager, file: FileId) -> Object {
+    assemble_for(sources, file, TargetFeatures::default())
+}
+
+/// assemble `file` for a target with `features`, until `.arch`, `.cpu` or `.arch_extension`
+/// changes them
+pub fn assemble_for(sources: &SourceManager, file: FileId, features: TargetFeatures) -> Object {
     let ast_alloc = Bump::new();
-    let mut parser = parse::Parser::new_in(&source, &ast_alloc);
+    let mut parser = parse::Parser::new_in(sources, file, &ast_alloc);
     let emit_alloc = Bump::new();
-    let mut emitter = emit::Emit::new_in(&source, &emit_alloc);
+    let mut emitter = emit::Emit::new_in(sources, &emit_alloc);
+    *emitter.features() = features;
     while let Some(top) = parser.next() {
+        emitter.sync_expansions(&parser.expansions());
         emitter.process(&top);
+        if emitter.is_skipping() {
+            parser.skip_conditional();
+        }
     }
+    emitter.sync_expansions(&[]);
+    emitter.finish()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{
+        code::Source,
+        obj::{RelocKind, SymbolValue},
+        sparsebin::Aligned,
+    };
     use std::{path::PathBuf, str::FromStr};
 
+    fn assemble_words(text: &str, count: usize) -> Vec<u32> {
+        let sources = SourceManager::new();
+        let file = sources.add(Source::new(PathBuf::new(), text.to_string()));
+        let mut obj = assemble(&sources, file);
+        assert_eq!(sources.error_count(), 0);
+        (0..count)
+            .map(|i| obj.text.get_u32(Aligned::new(i * 4).unwrap()))
+            .collect()
+    }
+
+    fn assemble_errors(text: &str) -> usize {
+        let sources = SourceManager::new();
+        let file = sources.add(Source::new(PathBuf::new(), text.to_string()));
+        assemble(&sources, file);
+        sources.error_count()
+    }
+
+    fn assemble_messages(text: &str) -> Vec<String> {
+        let sources = SourceManager::new();
+        let file = sources.add(Source::new(PathBuf::new(), text.to_string()));
+        assemble(&sources, file);
+        sources.errors()
+    }
+
     #[test]
     fn it_assembles() {
         let text = "my_label: ADD x1, x2, x3\nB my_label\nB.EQ my_label\n";
-        assemble(Source::new(
+        let sources = SourceManager::new();
+        let file = sources.add(Source::new(
             PathBuf::from_str("some/path").unwrap(),
             String::from_str(text).unwrap(),
         ));
+        assemble(&sources, file);
+    }
+
+    #[test]
+    fn it_folds_immediates() {
+        let text = "ADD x0, x1, #(2 + 3) * 4\nADD x0, x1, #1 << 12 >> 12\n";
+        assert_eq!(assemble_words(text, 2), [0x91005020, 0x91000420]);
+    }
+
+    #[test]
+    fn it_defers_forward_labels() {
+        let text = "start: ADD x0, x1, #end - start\nB end\nend:\n";
+        assert_eq!(assemble_words(text, 2), [0x91002020, 0x14000001]);
+    }
+
+    #[test]
+    fn it_expands_macros() {
+        let text = ".macro addi rd, rn, imm=1\nADD \\rd, \\rn, #\\imm\n.endm\naddi x0, x1\naddi x0, x1, 8\n";
+        assert_eq!(assemble_words(text, 2), [0x91000420, 0x91002020]);
+    }
+
+    #[test]
+    fn it_skips_inactive_blocks() {
+        let text = "\
+start:
+.ifdef start
+  .if 1 > 2
+    B bad
+  .elseif (2 + 2) == 4
+    ADD x0, x1, #1
+    .if 0
+      not valid ]] #
+      undefined_macro x
+    .else
+      ADD x0, x1, #8
+    .endif
+  .else
+    B bad
+  .endif
+.endif
+.ifndef start
+  B bad
+.endif
+ADD x0, x1, #(2 + 3) * 4
+";
+        assert_eq!(
+            assemble_words(text, 3),
+            [0x91000420, 0x91002020, 0x91005020]
+        );
+    }
+
+    #[test]
+    fn it_closes_conditionals_left_by_exitm() {
+        let text = "\
+.macro m x
+  .if \\x
+    ADD x0, x1, #1
+    .exitm
+  .endif
+  ADD x0, x1, #2
+  .if 1
+.endm
+m 1
+m 0
+ADD x0, x1, #3
+";
+        assert_eq!(
+            assemble_words(text, 3),
+            [0x91000420, 0x91000820, 0x91000c20]
+        );
+    }
+
+    #[test]
+    fn it_reports_unbalanced_conditionals() {
+        assert_eq!(assemble_errors(".endif\n.if 1\n.else\n.else\n"), 3);
+        assert_eq!(assemble_errors(".if later\n.endif\nlater:\n"), 1);
+    }
+
+    #[test]
+    fn it_explains_invalid_operands() {
+        let text = "\
+ADD x0, x1, #4096
+AND x0, x1, #0
+MOVZ x0, #1, LSL #8
+LDR x0, [x1, #-257]!
+ldi w0, #0x100000000
+start: ADD x0, x1, #(end - start) * 4096
+end:
+";
+        assert_eq!(
+            assemble_messages(text),
+            [
+                "operand 3 of `ADD`: value must be -4095 to 4095",
+                "operand 3 of `AND`: value is not a valid bitmask immediate",
+                "operand 3 of `MOVZ`: value must be a multiple of 16",
+                "operand 2 of `LDR`: value must be -256 to 255",
+                "`ldi`: value must be -2147483648 to 4294967295",
+                "value must be 0 to 4095",
+            ]
+        );
+    }
+
+    #[test]
+    fn it_rejects_registers_as_values() {
+        let text = "MOV x0, x1\nMOV x29, sp\nROR w0, w1, w2\nLSL x0, x1, d2\nB x0\nADR x0, v1\n";
+        assert_eq!(
+            assemble_messages(text),
+            [
+                "expected an immediate, the register form is not supported",
+                "expected an immediate, the register form is not supported",
+                "expected an immediate, the register form is not supported",
+                "expected an immediate, the register form is not supported",
+                "expected a label, not a register",
+                "expected a label, not a register",
+            ]
+        );
+    }
+
+    #[test]
+    fn it_reports_malformed_operands() {
+        assert_eq!(assemble_errors("ADD x0, x1, #1 x\n"), 1);
+        assert_eq!(assemble_errors("LDR x0, x1]\nB 1c\nADD x0, x1, #1\n"), 3);
+        assert_eq!(assemble_errors("ADD x0, x1, \"str\"\n"), 1);
+    }
+
+    #[test]
+    fn it_reports_directive_arguments() {
+        let text = ".if\n.arch\n.cpu x0, x1\n.arch_extension\n.equ x\n.endif 1\n";
+        assert_eq!(assemble_errors(text), 6);
+    }
+
+    #[test]
+    fn it_resolves_local_labels() {
+        let text = "1: ADD x0, x1, #0b101\nB 1f\nB 1b\n1: B 1b\n";
+        assert_eq!(
+            assemble_words(text, 4),
+            [0x91001420, 0x14000002, 0x17fffffe, 0x14000000]
+        );
+        assert_eq!(assemble_errors("x:\nx:\nB 3b\nB 4f\n"), 3);
+    }
+
+    #[test]
+    fn it_assigns_constants() {
+        let text = "\
+.equ SIZE, 16
+SHIFT = 2
+.set N, 1
+ADD x0, x1, #SIZE + N
+.set N, 2
+ADD x0, x1, #N << SHIFT
+ADD x0, x1, x2, LSL #SHIFT
+ADD x0, x1, #later - N
+later:
+";
+        assert_eq!(
+            assemble_words(text, 4),
+            [0x91004420, 0x91002020, 0x8b020820, 0x91003820]
+        );
+        assert_eq!(assemble_errors(".equ A, 1\n.equ A, 2\n.set A, 3\n"), 2);
+        assert_eq!(assemble_errors("x:\n.set x, 1\nN = 1\nN:\n"), 2);
+        assert_eq!(assemble_errors(".equ F, later\nlater:\n"), 1);
+    }
+
+    #[test]
+    fn it_exports_symbols() {
+        let text = ".global start\nstart: ADD x0, x1, #1\n1: B 1b\nSIZE = 8\nend:\n";
+        let sources = SourceManager::new();
+        let file = sources.add(Source::new(PathBuf::new(), text.to_string()));
+        let obj = assemble(&sources, file);
+        assert_eq!(obj.text_size, 8);
+        let symbols: Vec<_> = obj
+            .symbols
+            .iter()
+            .map(|sym| (sym.name.as_str(), sym.value, sym.global))
+            .collect();
+        assert_eq!(
+            symbols,
+            [
+                ("start", SymbolValue::Text(0), true),
+                ("SIZE", SymbolValue::Absolute(8), false),
+                ("end", SymbolValue::Text(8), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_assembles_loads_and_stores() {
+        let text = "\
+start: LDR x0, [x1, #8]
+LDR w0, [x1, #-8]
+LDR x0, [sp, #16]!
+STR wzr, [x1], #255
+LDR x3, [x4, x5, LSL #3]
+LDRB w0, [x1, x2, LSL #0]
+LDRSH w0, [x1, w2, SXTW #1]
+LDRSW x2, data
+STURH w0, [x1, #2]
+data:
+";
+        assert_eq!(
+            assemble_words(text, 9),
+            [
+                0xf9400420, 0xb85f8020, 0xf8410fe0, 0xb80ff43f, 0xf8657883, 0x38627820,
+                0x78e2d820, 0x98000042, 0x78002020
+            ]
+        );
+        assert_eq!(
+            assemble_errors("LDRB x0, [x1]\nLDR x0, [x1, x2, LSL #2]\nLDUR x0, [x1, #8]!\n"),
+            3
+        );
+    }
+
+    #[test]
+    fn it_assembles_pairs() {
+        let text = "\
+STP x29, x30, [sp, #-16]!
+LDP x29, x30, [sp], #16
+STP w0, w1, [x2, #8]
+LDPSW x0, x1, [x2, #-8]!
+LDNP x0, x1, [sp, #16]
+";
+        assert_eq!(
+            assemble_words(text, 5),
+            [0xa9bf7bfd, 0xa8c17bfd, 0x29010440, 0x69ff0440, 0xa84107e0]
+        );
+        assert_eq!(
+            assemble_errors("STP x0, w1, [sp]\nLDP x0, x1, [x2, #4]\nSTNP x0, x1, [sp, #16]!\n"),
+            3
+        );
+    }
+
+    #[test]
+    fn it_assembles_branches() {
+        let text = "\
+cbz x0, 1f
+tbz w0, #3, 1f
+1:
+bl 1b
+blr x17
+ret
+tbnz x3, #33, 1b
+";
+        assert_eq!(
+            assemble_words(text, 6),
+            [0xb4000040, 0x36180020, 0x94000000, 0xd63f0220, 0xd65f03c0, 0xb70fffa3]
+        );
+        assert_eq!(
+            assemble_errors("a:\ntbz w0, #32, a\ntbz x0, #64, a\nbr w0\n"),
+            3
+        );
+    }
+
+    #[test]
+    fn it_assembles_add_and_sub() {
+        let text = "\
+sub sp, sp, #16
+subs xzr, x1, x2, lsr #63
+add x0, x1, w2, uxtw #2
+adds x0, sp, w2, sxth #1
+cmp sp, #4, lsl #12
+cmp x0, w1, sxtw #2
+cmn w0, w1, asr #3
+negs x0, x1, asr #2
+";
+        assert_eq!(
+            assemble_words(text, 8),
+            [
+                0xd10043ff, 0xeb42fc3f, 0x8b224820, 0xab22a7e0, 0xf14013ff, 0xeb21c81f,
+                0x2b810c1f, 0xeb810be0
+            ]
+        );
+        assert_eq!(
+            assemble_errors("add x0, x1, w2\nsub x0, w1, #4\nsubs sp, x1, #4\nneg x0, sp\n"),
+            4
+        );
+    }
+
+    #[test]
+    fn it_flips_add_and_sub_of_negative_immediates() {
+        let text = "\
+add x0, x1, #-1
+sub x0, x1, #-1
+adds x0, x1, #-1, lsl #12
+subs w0, w1, #-4095
+cmp x0, #-1
+cmn w0, #-4095
+sub sp, sp, #-16
+";
+        assert_eq!(
+            assemble_words(text, 7),
+            [0xd1000420, 0x91000420, 0xf1400420, 0x313ffc20, 0xb100041f, 0x713ffc1f, 0x910043ff]
+        );
+        assert_eq!(
+            assemble_messages("add x0, x1, #-4096\nsub x0, x1, #:lo12:sym\n"),
+            [
+                "operand 3 of `add`: value must be -4095 to 4095",
+                "operand 3 of `sub`: `:lo12:` can't be used here",
+            ]
+        );
+    }
+
+    #[test]
+    fn it_prefers_shifted_registers_without_sp() {
+        let text = "\
+add x0, x1, x2
+cmp x0, x1
+sub w0, w1, w2
+adds x0, x1, x2
+cmn w0, w1
+subs x0, x1, x2
+add sp, sp, x1
+add x0, sp, x1
+cmp sp, x1
+";
+        assert_eq!(
+            assemble_words(text, 9),
+            [
+                0x8b020020, 0xeb01001f, 0x4b020020, 0xab020020, 0x2b01001f, 0xeb020020, 0x8b2163ff,
+                0x8b2163e0, 0xeb2163ff
+            ]
+        );
+        assert_eq!(assemble_errors("add x0, x1, sp\n"), 1);
+    }
+
+    #[test]
+    fn it_assembles_logical() {
+        let text = "\
+and x0, x1, #0xff00ff00ff00ff00
+eor w0, w1, #-2
+tst w1, #0xc
+bic x0, x1, #0xff
+bic x0, x1, x2, asr #4
+orn w0, wzr, w1
+tst w0, w1, lsl #2
+";
+        assert_eq!(
+            assemble_words(text, 7),
+            [0x92089c20, 0x521f7820, 0x721e043f, 0x9278dc20, 0x8aa21020, 0x2a2103e0, 0x6a01081f]
+        );
+        assert_eq!(
+            assemble_errors("and x0, x1, #0\nand w0, w1, #0x100000000\nand x0, x1, #0x1234\n"),
+            3
+        );
+    }
+
+    #[test]
+    fn it_checks_shifts() {
+        let text = "orr w0, w1, w2, ror #7\nadd x0, x1, x2, lsl #63\nadd w0, w1, w2, asr #31\n";
+        assert_eq!(
+            assemble_words(text, 3),
+            [0x2ac21c20, 0x8b02fc20, 0x0b827c20]
+        );
+        let text = "add x0, x1, x2, ror #3\nadd w0, w1, w2, lsl #32\ncmp x0, x1, ror #1\n";
+        assert_eq!(assemble_errors(text), 3);
+    }
+
+    #[test]
+    fn it_moves_immediates() {
+        let text = "\
+movz w1, #0xffff, lsl #16
+movk x3, #0xbeef, lsl #32
+mov x0, #-0x10001
+mov x0, #0x5555555555555555
+ldi x0, #0x12340000abcd
+ldi x2, #-0x123456789
+";
+        assert_eq!(
+            assemble_words(text, 9),
+            [
+                0x52bfffe1, 0xf2d7dde3, 0x92a00020, 0xb200f3e0, 0xd29579a0, 0xf2c24680,
+                0x928cf102, 0xf2bb9742, 0xf2dfffc2
+            ]
+        );
+        let text = "movz x0, #1, lsl #8\nmov x0, #0x1234567\nldi x0, #later\nlater:\n";
+        assert_eq!(assemble_errors(text), 3);
+    }
+
+    #[test]
+    fn it_selects_conditionally() {
+        let text = "\
+csinc x3, x4, xzr, lt
+csneg x3, x4, x5, al
+cset w0, eq
+cinc x0, x1, mi
+cneg x5, x6, ge
+ccmp w0, #31, #0xf, ls
+ccmn w3, w4, #2, nv
+";
+        assert_eq!(
+            assemble_words(text, 7),
+            [0x9a9fb483, 0xda85e483, 0x1a9f17e0, 0x9a815420, 0xda86b4c5, 0x7a5f980f, 0x3a44f062]
+        );
+        let text = "cset x0, al\ncinc x0, x1, nv\ncsel x0, w1, x2, eq\nccmp x0, #32, #0, eq\n";
+        assert_eq!(assemble_errors(text), 4);
+    }
+
+    #[test]
+    fn it_moves_bitfields() {
+        let text = "\
+lsl w0, w1, #31
+lsr x0, x1, #63
+asr x2, x3, #7
+ubfx w0, w1, #31, #1
+sbfiz x0, x1, #60, #4
+bfi w0, w1, #0, #32
+uxtb x0, w1
+sxtw x0, w1
+ror x0, x1, #7
+";
+        assert_eq!(
+            assemble_words(text, 9),
+            [
+                0x53010020, 0xd37ffc20, 0x9347fc62, 0x531f7c20, 0x93440c20, 0x33007c20,
+                0x53001c20, 0x93407c20, 0x93c11c20
+            ]
+        );
+        let text = "\
+lsl w0, w1, #32
+ubfx w0, w1, #31, #2
+bfi x0, x1, #0, #0
+sxtw w0, w1
+extr w0, w1, w2, #32
+sbfm w0, w1, #32, #0
+";
+        assert_eq!(assemble_errors(text), 6);
+    }
+
+    #[test]
+    fn it_assembles_system_instructions() {
+        let text = "\
+svc #0
+brk #0x3e8
+hint #34
+nop
+wfe
+dmb ish
+dsb sy
+isb
+mrs x0, cntvct_el0
+msr nzcv, x1
+mrs x2, S3_3_C13_C0_2
+msr fpcr, xzr
+";
+        assert_eq!(
+            assemble_words(text, 12),
+            [
+                0xd4000001, 0xd4207d00, 0xd503245f, 0xd503201f, 0xd503205f, 0xd5033bbf,
+                0xd5033f9f, 0xd5033fdf, 0xd53be040, 0xd51b4201, 0xd53bd042, 0xd51b441f
+            ]
+        );
+        let text = "\
+svc #0x10000
+dmb #16
+dmb bogus
+isb ish
+mrs x0, S1_0_C0_C0_0
+mrs w0, tpidr_el0
+msr foo_el1, x0
+";
+        // an unknown register name leaves neither operand matched
+        assert_eq!(assemble_errors(text), 8);
+    }
+
+    #[test]
+    fn it_assembles_floating_point() {
+        let text = "\
+.arch_extension fp16
+fadd d0, d1, d2
+fmadd s0, s1, s2, s3
+fcmp d0, #0.0
+fcvt d0, s1
+fcvtzs w0, d1
+ucvtf d0, x1
+fmov x0, d1
+fmov d0, #1.5
+fmov s0, #-2.5
+fmov h0, #1
+";
+        assert_eq!(
+            assemble_words(text, 10),
+            [
+                0x1e622820, 0x1f020c20, 0x1e602008, 0x1e22c020, 0x1e780020, 0x9e630020,
+                0x9e660020, 0x1e6f1000, 0x1e309000, 0x1eee1000
+            ]
+        );
+        // `#0.0` has no `imm8` and is moved from ZR instead
+        let text = "\
+.arch_extension fp16
+fmov d0, #0.0
+fmov s3, #0.0
+fmov h5, #0.0
+fmov d1, #1e0
+fmov s2, #2.5e-1
+fmov d4, #-1.25E+1
+";
+        assert_eq!(
+            assemble_words(text, 6),
+            [0x9e6703e0, 0x1e2703e3, 0x1ee703e5, 0x1e6e1001, 0x1e2a1002, 0x1e753004]
+        );
+        let text = "\
+fadd d0, s1, d2
+fadd q0, q1, q2
+fmov d0, #0.1
+fmov d0, #-0.0
+fmov d0, #32.0
+fcmp d0, #1.0
+fcvt d0, d1
+fmov w0, d1
+add x0, x1, #1.5
+";
+        assert_eq!(assemble_errors(text), 9);
+    }
+
+    #[test]
+    fn it_assembles_vectors() {
+        let text = "\
+add v0.16b, v1.16b, v2.16b
+sub v0.4s, v1.4s, v31.4s
+mul v3.8h, v4.8h, v5.8h
+fmla v0.2d, v1.2d, v2.2d
+zip1 v0.4s, v1.4s, v2.4s
+trn2 v0.8b, v1.8b, v2.8b
+tbl v0.16b, {v30.16b-v1.16b}, v4.16b
+dup v0.4s, v1.s[1]
+dup v0.2d, x1
+ins v0.s[1], w1
+ins v0.b[3], v1.b[15]
+umov x0, v1.d[1]
+ld1 {v0.4s, v1.4s}, [x1]
+st1 {v0.8b}, [x0], #8
+ld1 {v0.2d-v3.2d}, [sp], #64
+";
+        assert_eq!(
+            assemble_words(text, 15),
+            [
+                0x4e228420, 0x6ebf8420, 0x4e659c83, 0x4e62cc20, 0x4e823820, 0x0e026820,
+                0x4e0463c0, 0x4e0c0420, 0x4e080c20, 0x4e0c1c20, 0x6e077c20, 0x4e183c20,
+                0x4c40a820, 0x0c9f7000, 0x4cdf2fe0
+            ]
+        );
+        let text = "\
+mov v0.s[1], v1.s[3]
+mov v6.h[2], w7
+mov w0, v1.s[1]
+mov x2, v3.d[1]
+fmla v0.4s, v1.4s, v2.s[3]
+fmla v4.2d, v5.2d, v6.d[1]
+fmul v0.4s, v1.4s, v2.s[2]
+fmul v0.4s, v1.4s, v2.4s
+ld1 {v0.16b}, [x0], x1
+st1 {v0.2d}, [x3], x4
+";
+        assert_eq!(
+            assemble_words(text, 10),
+            [
+                0x6e0c6420, 0x4e0a1ce6, 0x0e0c3c20, 0x4e183c62, 0x4fa21820, 0x4fc618a4,
+                0x4f829820, 0x6e22dc20, 0x4cc17000, 0x4c847c60
+            ]
+        );
+        let text = "\
+fadd v0.4s, v1.4s, v2.4s
+fadd v3.2s, v4.2s, v5.2s
+mov v0.16b, v1.16b
+mov v2.8b, v3.8b
+orr v0.16b, v1.16b, v2.16b
+.arch_extension fp16
+fmla v0.8h, v1.8h, v2.h[7]
+fmla v0.4h, v1.4h, v15.h[2]
+fmul v0.8h, v1.8h, v3.h[5]
+";
+        assert_eq!(
+            assemble_words(text, 8),
+            [
+                0x4e22d420, 0x0e25d483, 0x4ea11c20, 0x0ea31c62, 0x4ea21c20, 0x4f321820,
+                0x0f2f1020, 0x4f139820
+            ]
+        );
+        let text = "\
+add v0.1d, v1.1d, v2.1d
+mul v0.2d, v1.2d, v2.2d
+add v0.4s, v1.4s, v2.2s
+fmla v0.8b, v1.8b, v2.8b
+dup v0.4s, v1.s[4]
+dup v0.8b, x1
+ins v0.s[1], v1.d[0]
+umov x0, v1.s[0]
+tbl v0.16b, {v1.8b}, v2.16b
+ld1 {v0.4s}, [x0], #8
+mov w0, v1.b[0]
+fmla v0.4s, v1.4s, v2.d[0]
+ld1 {v0.16b}, [x0], xzr
+ldr x0, [x1], x2
+mov v0.4s, v1.4s
+fadd v0.8b, v1.8b, v2.8b
+fmla v0.8h, v1.8h, v2.h[0]
+.arch_extension fp16
+fmla v0.8h, v1.8h, v16.h[0]
+";
+        assert_eq!(assemble_errors(text), 18);
+    }
+
+    #[test]
+    fn it_assembles_atomics() {
+        let text = "\
+.arch armv8.1-a
+ldaxr x0, [x1]
+stlxr w2, x3, [x1]
+ldxrb w0, [sp, #0]
+ldxp x0, x1, [x2]
+stxp w3, x0, x1, [x2]
+ldar w0, [x1]
+stlrh w0, [x1]
+casal x0, x1, [x2]
+caspa w4, w5, w6, w7, [sp]
+ldaddal w0, w1, [x2]
+ldsminlb w0, w1, [x2]
+swpa x0, x1, [x2]
+";
+        assert_eq!(
+            assemble_words(text, 12),
+            [
+                0xc85ffc20, 0xc802fc23, 0x085f7fe0, 0xc87f0440, 0xc8230440, 0x88dffc20,
+                0x489ffc20, 0xc8e0fc41, 0x08647fe6, 0xb8e00041, 0x38605041, 0xf8a08041
+            ]
+        );
+        // the status register of a store exclusive can't be its data or base
+        let text = "\
+.arch armv8.1-a
+stxr w0, x0, [x1]
+stxr w1, x0, [x1]
+stxp w0, x1, x0, [x2]
+ldxp x0, x0, [x1]
+ldxr x0, [x1, #8]
+ldxr x0, [x1], #8
+stxr x0, x1, [x2]
+casp x1, x2, x3, x4, [x5]
+casp x0, x2, x4, x5, [x6]
+cas w0, x1, [x2]
+";
+        assert_eq!(assemble_errors(text), 10);
+        assert_eq!(assemble_words("stxr w0, x1, [sp]\n", 1), [0xc8007fe1]);
+    }
+
+    #[test]
+    fn it_assembles_pointer_authentication() {
+        let text = "\
+.arch armv8.5-a
+paciasp
+autiasp
+xpaclri
+bti
+bti c
+bti jc
+pacib x0, sp
+autia x1, x2
+xpaci x3
+retab
+braa x0, sp
+blraa x1, x2
+";
+        assert_eq!(
+            assemble_words(text, 12),
+            [
+                0xd503233f, 0xd50323bf, 0xd50320ff, 0xd503241f, 0xd503245f, 0xd50324df,
+                0xdac107e0, 0xdac11041, 0xdac143e3, 0xd65f0fff, 0xd71f081f, 0xd73f0822
+            ]
+        );
+        let text = "\
+.arch armv8.5-a
+bti x
+pacia w0, x1
+pacia x0, xzr
+braa sp, x1
+";
+        assert_eq!(assemble_errors(text), 4);
+    }
+
+    #[test]
+    fn it_assembles_crypto_and_crc() {
+        let text = "\
+.arch_extension crc
+.arch_extension crypto
+crc32b w0, w1, w2
+crc32cx w1, w2, xzr
+aese v0.16b, v1.16b
+aesimc v31.16b, v30.16b
+sha1c q0, s1, v2.4s
+sha256h q12, q13, v14.4s
+sha1h s21, s22
+sha256su0 v25.4s, v26.4s
+pmull v0.8h, v1.8b, v2.8b
+pmull2 v9.1q, v10.2d, v11.2d
+";
+        assert_eq!(
+            assemble_words(text, 10),
+            [
+                0x1ac24020, 0x9adf5c41, 0x4e284820, 0x4e287bdf, 0x5e020020, 0x5e0e41ac,
+                0x5e280ad5, 0x5e282b59, 0x0e22e020, 0x4eebe149
+            ]
+        );
+        let text = "\
+nop
+crc32b w0, w1, w2
+sha1h s0, s1
+.arch_extension crc
+.arch_extension nocrc
+crc32w w0, w1, w2
+.arch_extension sve9
+pmull v0.1q, v1.1d, v2.1d
+.arch_extension aes
+aese v0.4s, v1.4s
+pmull v0.8h, v1.16b, v2.16b
+";
+        assert_eq!(assemble_errors(text), 7);
+
+        // only the 1q form of PMULL is part of AES
+        let text = "pmull v0.8h, v1.8b, v2.8b\npmull2 v0.8h, v1.16b, v2.16b\n";
+        assert_eq!(assemble_words(text, 2), [0x0e22e020, 0x4e22e020]);
+    }
+
+    #[test]
+    fn it_selects_target_features() {
+        let text = "\
+.arch armv8.1-a+fp16
+ldadd w0, w1, [x2]
+fadd h0, h1, h2
+.cpu cortex-a53+crypto
+crc32b w0, w1, w2
+aese v0.16b, v1.16b
+fcvt h0, s1
+.ARCH ARMv9-A
+bti c
+";
+        assert_eq!(
+            assemble_words(text, 6),
+            [0xb8200041, 0x1ee22820, 0x1ac24020, 0x4e284820, 0x1e23c020, 0xd503245f]
+        );
+        let text = "\
+.arch armv8.2-a
+fadd h0, h1, h2
+fmov w0, h1
+.cpu cortex-a57
+ldadd w0, w1, [x2]
+.arch armv7-a
+.arch armv8-a+bogus
+.cpu cortex-z9
+.arch_extension nofp
+fadd d0, d1, d2
+add v0.4s, v1.4s, v2.4s
+";
+        assert_eq!(assemble_errors(text), 8);
+
+        let sources = SourceManager::new();
+        let text = "ldadd w0, w1, [x2]\npacia x0, x1\n";
+        let file = sources.add(Source::new(PathBuf::new(), text.to_string()));
+        let features = TargetFeatures::arch("armv8.3-a").unwrap();
+        assemble_for(&sources, file, features);
+        assert_eq!(sources.error_count(), 0);
+        let file = sources.add(Source::new(PathBuf::new(), text.to_string()));
+        assemble(&sources, file);
+        assert_eq!(sources.error_count(), 2);
+
+        // the hint space forms are NOPs on older cores
+        let text = "paciasp\nautiasp\nbti c\n";
+        assert_eq!(
+            assemble_words(text, 3),
+            [0xd503233f, 0xd50323bf, 0xd503245f]
+        );
+    }
+
+    #[test]
+    fn it_multiplies_and_divides() {
+        let text = "\
+msub x4, x5, x6, x7
+mul w0, w1, w2
+smaddl x0, w1, w2, x3
+umull x4, w5, w6
+umulh x0, x1, x2
+sdiv w0, w1, w2
+";
+        assert_eq!(
+            assemble_words(text, 6),
+            [0x9b069ca4, 0x1b027c20, 0x9b220c20, 0x9ba67ca4, 0x9bc27c20, 0x1ac20c20]
+        );
+        let text = "\
+smaddl w0, w1, w2, x3
+smull x0, x1, x2
+smulh w0, w1, w2
+madd x0, w1, x2, x3
+sdiv x0, x1, w2
+";
+        assert_eq!(assemble_errors(text), 5);
+    }
+
+    #[test]
+    fn it_relocates_addresses() {
+        let text = "\
+start: adr x0, start
+adr x1, data
+adrp x2, data
+add x2, x2, :lo12:data
+ldr w3, [x2, #:lo12:data]
+adrp x4, :pg_hi21:ext
+bl ext
+data:
+";
+        let sources = SourceManager::new();
+        let file = sources.add(Source::new(PathBuf::new(), text.to_string()));
+        let mut obj = assemble(&sources, file);
+        assert_eq!(sources.error_count(), 0);
+        let words: Vec<_> = (0..7)
+            .map(|i| obj.text.get_u32(Aligned::new(i * 4).unwrap()))
+            .collect();
+        assert_eq!(
+            words,
+            [0x10000000, 0x100000c1, 0x90000002, 0x91000042, 0xb9400043, 0x90000004, 0x94000000]
+        );
+        let relocations: Vec<_> = obj
+            .relocations
+            .iter()
+            .map(|r| (r.offset, r.kind, r.symbol.as_deref(), r.addend))
+            .collect();
+        assert_eq!(
+            relocations,
+            [
+                (8, RelocKind::AdrPrelPgHi21, None, 28),
+                (12, RelocKind::AddAbsLo12Nc, None, 28),
+                (16, RelocKind::Ldst32AbsLo12Nc, None, 28),
+                (20, RelocKind::AdrPrelPgHi21, Some("ext"), 0),
+                (24, RelocKind::Call26, Some("ext"), 0),
+            ]
+        );
+        assert_eq!(obj.symbols.last().unwrap().value, SymbolValue::Undefined);
+
+        // another object could replace a global label, so even a defined one is relocated
+        let text = "\
+.global gl
+gl: b gl
+bl gl
+bl local
+b.eq gl
+local:
+";
+        let sources = SourceManager::new();
+        let file = sources.add(Source::new(PathBuf::new(), text.to_string()));
+        let mut obj = assemble(&sources, file);
+        assert_eq!(sources.error_count(), 0);
+        let relocations: Vec<_> = obj
+            .relocations
+            .iter()
+            .map(|r| (r.offset, r.kind, r.symbol.as_deref(), r.addend))
+            .collect();
+        assert_eq!(
+            relocations,
+            [
+                (0, RelocKind::Jump26, Some("gl"), 0),
+                (4, RelocKind::Call26, Some("gl"), 0),
+                (12, RelocKind::CondBr19, Some("gl"), 0),
+            ]
+        );
+        assert_eq!(obj.text.get_u32(Aligned::new(8).unwrap()), 0x94000002);
+
+        let text = "sub x0, x1, :lo12:a\nldr x0, [x1, :lo12:a]!\nadrp x0, 1f\nadr x0, :lo12:a\nadd x0, x1, :foo:a\na:\n";
+        assert_eq!(assemble_errors(text), 5);
     }
 }
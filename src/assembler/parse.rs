@@ -1,9 +1,13 @@
 use crate::{
-    assembler::lex::{IntRadix, Scanner, Token, TokenKind},
+    assembler::{
+        lex::{IntRadix, Token, TokenKind},
+        preproc::Preprocessor,
+    },
     code::{self, Loc, Span},
     stream::Stream,
 };
 use bumpalo::{collections::Vec as BumpVec, Bump};
+use std::cell::{OnceCell, RefCell};
 
 pub mod ast {
     use enum_variant_type::EnumVariantType;
@@ -19,10 +23,22 @@ pub struct IntLiteral {
             pub base: IntRadix,
             pub span: Span,
         }
+        /// identifier followed by an immediate, `LSL #2`
         #[derive(Debug, Clone, Copy)]
-        pub struct IdentInt {
+        pub struct IdentInt<'bump> {
             pub span: Span,
-            pub int: IntLiteral,
+            pub expr: &'bump Expr<'bump>,
+        }
+
+        /// how an address updates its base register
+        #[derive(Debug, Clone, Copy)]
+        pub enum Index<'bump> {
+            /// `[x1, #8]`, not updated
+            Offset,
+            /// `[x1, #8]!`, updated before the access
+            Pre,
+            /// `[x1], #8`, updated by the expression after the access
+            Post(&'bump Expr<'bump>),
         }
 
         impl From<IntLiteral> for Expr<'_> {
@@ -30,32 +46,117 @@ fn from(val: IntLiteral) -> Self {
                 Expr::IntLiteral(val)
             }
         }
-        impl From<IdentInt> for Expr<'_> {
-            fn from(val: IdentInt) -> Self {
+        impl<'bump> From<IdentInt<'bump>> for Expr<'bump> {
+            fn from(val: IdentInt<'bump>) -> Self {
                 Expr::IdentInt(val)
             }
         }
     }
 
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum UnaryOp {
+        /// `-`
+        Neg,
+        /// `~`
+        Not,
+        /// `!`, 1 if zero, otherwise 0
+        LogicalNot,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum BinaryOp {
+        Mul,
+        Div,
+        Rem,
+        Add,
+        Sub,
+        Shl,
+        /// arithmetic shift right
+        Shr,
+        Lt,
+        Le,
+        Gt,
+        Ge,
+        Eq,
+        Ne,
+        And,
+        Xor,
+        Or,
+        /// `&&`, 1 if true, otherwise 0, while comparisons are -1 if true
+        LogicalAnd,
+        LogicalOr,
+    }
+
+    impl BinaryOp {
+        /// higher binds tighter, the order of GNU as, which puts shifts with `*` and the
+        /// comparisons with `+`, unlike C
+        pub const fn precedence(self) -> u8 {
+            match self {
+                Self::Mul | Self::Div | Self::Rem | Self::Shl | Self::Shr => 4,
+                Self::And | Self::Xor | Self::Or => 3,
+                Self::Add
+                | Self::Sub
+                | Self::Lt
+                | Self::Le
+                | Self::Gt
+                | Self::Ge
+                | Self::Eq
+                | Self::Ne => 2,
+                Self::LogicalAnd | Self::LogicalOr => 1,
+            }
+        }
+    }
+
     #[derive(Debug)]
     pub enum Expr<'bump> {
         Address {
+            /// base register, then the optional offset and extend
             args: &'bump [Expr<'bump>],
             group: Span,
+            index: expr::Index<'bump>,
         },
         IntLiteral(expr::IntLiteral),
         FloatLiteral {
-            int: u64,
-            frac: u64,
+            value: f64,
             span: Span,
         },
         Ident {
             span: Span,
         },
-        IdentInt(expr::IdentInt),
+        IdentInt(expr::IdentInt<'bump>),
         String {
             span: Span,
         },
+        /// `:lo12:label`, part of the address of a label that the linker fills in
+        Reloc {
+            modifier: Span,
+            label: Span,
+        },
+        /// `v0.4s`, or the lane `v0.s[1]` if there is an index
+        Vector {
+            reg: Span,
+            arrangement: Span,
+            index: Option<&'bump Expr<'bump>>,
+        },
+        /// `{v0.4s, v1.4s}`, or `{v0.4s-v3.4s}` where `regs` are the first and last register
+        RegList {
+            regs: &'bump [Expr<'bump>],
+            group: Span,
+            range: bool,
+        },
+        Unary {
+            op: UnaryOp,
+            expr: &'bump Expr<'bump>,
+            /// span of the operator
+            span: Span,
+        },
+        Binary {
+            op: BinaryOp,
+            lhs: &'bump Expr<'bump>,
+            rhs: &'bump Expr<'bump>,
+            /// span of the operator
+            span: Span,
+        },
         Error,
     }
 
@@ -70,33 +171,46 @@ pub enum Top<'bump> {
             args: Option<&'bump [Expr<'bump>]>,
         },
         Label(Span),
+        /// `NAME = expr`, args are the name followed by the value
+        Assign {
+            eq: Span,
+            args: &'bump [Expr<'bump>],
+        },
         Error,
     }
 }
 
-use ast::{expr, Expr, Top};
+use ast::{expr, BinaryOp, Expr, Top, UnaryOp};
 
+/// only reads ahead when peeked, so the lines after a statement are not preprocessed
+/// before the statement is emitted
 struct TokenStream<'src> {
-    scanner: Scanner<'src>,
-    current: Option<Token>,
+    pre: RefCell<Preprocessor<'src>>,
+    current: OnceCell<Option<Token>>,
 }
 
 impl<'src> Stream for TokenStream<'src> {
     type Item = Token;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.current?;
-        std::mem::replace(&mut self.current, self.scanner.next())
+        match self.current.take() {
+            Some(current) => current,
+            None => self.pre.get_mut().next(),
+        }
     }
     fn peek(&self) -> Option<&Token> {
-        self.current.as_ref()
+        self.current
+            .get_or_init(|| self.pre.borrow_mut().next())
+            .as_ref()
     }
 }
 
 impl<'src> TokenStream<'src> {
-    fn new(mut scanner: Scanner<'src>) -> Self {
-        let current = scanner.next();
-        Self { scanner, current }
+    fn new(pre: Preprocessor<'src>) -> Self {
+        Self {
+            pre: RefCell::new(pre),
+            current: OnceCell::new(),
+        }
     }
 
     fn peek_kind(&self) -> Option<TokenKind> {
@@ -109,132 +223,349 @@ fn next_span(&mut self) -> Span {
 
 pub struct Parser<'bump, 'src> {
     it: TokenStream<'src>,
-    src: &'src code::Source,
+    src: &'src code::SourceManager,
     bump: &'bump Bump,
 }
 
 impl<'bump, 'src> Parser<'bump, 'src> {
     #[must_use]
-    pub fn new_in(src: &'src code::Source, bump: &'bump Bump) -> Self {
-        let scanner = Scanner::new(src);
+    pub fn new_in(src: &'src code::SourceManager, file: code::FileId, bump: &'bump Bump) -> Self {
         Self {
-            it: TokenStream::new(scanner),
+            it: TokenStream::new(Preprocessor::new(src, file)),
             src,
             bump,
         }
     }
 
     fn parse_int_value(src: &'src str, base: IntRadix) -> Option<u64> {
-        use std::num::IntErrorKind::{NegOverflow, PosOverflow};
-        if src.is_empty() {
+        let digits = match base {
+            IntRadix::Dec => src,
+            // skip 0x or 0b
+            IntRadix::Hex | IntRadix::Bin => &src[2..],
+        };
+        if digits.is_empty() {
             return None;
         }
-        match u64::from_str_radix(src, base as u32).map_err(|e| e.kind().clone()) {
-            Ok(val) => Some(val),
-            Err(NegOverflow | PosOverflow) => None,
-            _ => panic!("scanner mistyped token as {base:?}: {src:?}"),
+        let mut value: u64 = 0;
+        for c in digits.chars().filter(|&c| c != '_') {
+            let Some(digit) = c.to_digit(base as u32) else {
+                panic!("scanner mistyped token as {base:?}: {src:?}");
+            };
+            value = value
+                .checked_mul(base as u64)?
+                .checked_add(u64::from(digit))?;
         }
+        Some(value)
     }
 
     // called after consuming int
     fn parse_int(&mut self, span: Span, base: IntRadix) -> Option<expr::IntLiteral> {
-        let int_str = self.src.span(span);
-        if int_str.is_empty() || int_str.as_bytes()[0] != b'#' {
-            return None;
-        }
-        match Self::parse_int_value(&int_str[1..], base) {
+        match Self::parse_int_value(self.src.span(span), base) {
             Some(value) => ast::expr::IntLiteral { value, base, span }.into(),
-            None => None,
+            None => {
+                self.src.report(span, "integer literal is too large");
+                None
+            }
         }
     }
 
     // called after consuming float
     fn parse_float(&mut self, span: Span) -> ast::Expr<'bump> {
-        let mut iter = self.src.span(span).splitn(2, '.');
-        let Some(int_str) = iter.next() else {
-            return ast::Expr::Error;
-        };
-        if int_str.is_empty() || int_str.as_bytes()[0] != b'#' {
-            return ast::Expr::Error;
+        let digits = self.src.span(span).replace('_', "");
+        match digits.parse() {
+            Ok(value) => ast::Expr::FloatLiteral { value, span },
+            Err(..) => {
+                self.src.report(span, "invalid float literal");
+                ast::Expr::Error
+            }
         }
-        let Some(int) = Self::parse_int_value(&int_str[1..], IntRadix::Dec) else {
-            return ast::Expr::Error;
-        };
-        let Some(frac_str) = iter.next() else {
-            return ast::Expr::Error;
-        };
-        let Some(frac) = Self::parse_int_value(frac_str, IntRadix::Dec) else {
-            return ast::Expr::Error;
-        };
-        ast::Expr::FloatLiteral { int, frac, span }
     }
 
     // called after consuming [
-    fn parse_address_arg(&mut self) -> &'bump [ast::Expr<'bump>] {
+    // [x1]
+    // [x1, #8]!
+    // [x1], #8
+    // [x1, w2, SXTW #2]
+    fn parse_address(&mut self, span: Span) -> ast::Expr<'bump> {
         use TokenKind as T;
-        let first = match self.it.peek_kind() {
-            Some(T::Identifier) => ast::Expr::Ident {
-                span: self.it.next_span(),
-            },
-            _ => todo!("invalid addr mode arg"),
-        };
-        if self.it.peek_kind() == Some(T::RightSquareBracket) {
-            return self.bump.alloc([first]);
+        let mut args = BumpVec::<ast::Expr<'bump>>::with_capacity_in(3, self.bump);
+        loop {
+            let Some(expr) = self.parse_one_arg() else {
+                return ast::Expr::Error;
+            };
+            args.push(expr);
+            match self.it.peek_kind() {
+                Some(T::Comma) => {
+                    self.it.next();
+                }
+                Some(T::RightSquareBracket) => break,
+                _ => {
+                    self.src.report(span, "expected `]` to close the address");
+                    return ast::Expr::Error;
+                }
+            }
         }
-        if self.it.next_if_eq(T::Comma).is_none() {
-            todo!("error if arg is not followed by comma");
+        let end = self.it.next_span();
+        let index = if self.it.next_if_eq(T::Bang).is_some() {
+            expr::Index::Pre
+        } else if self.it.next_if_eq(T::Comma).is_some() {
+            let Some(expr) = self.parse_one_arg() else {
+                return ast::Expr::Error;
+            };
+            expr::Index::Post(self.bump.alloc(expr))
+        } else {
+            expr::Index::Offset
+        };
+        ast::Expr::Address {
+            args: args.into_bump_slice(),
+            group: Span::group(span, end),
+            index,
         }
-        let second = match self.it.peek_kind() {
-            Some(T::Identifier) => ast::Expr::Ident {
-                span: self.it.next_span(),
-            },
-            Some(T::Int(base)) => {
-                let span = self.it.next_span();
-                self.parse_int(span, base).unwrap().into()
+    }
+
+    // called after an identifier that is followed by `.`
+    // v0.4s
+    // v1.16b
+    // v2.s[1]
+    fn parse_vector(&mut self, reg: Span) -> Option<ast::Expr<'bump>> {
+        use TokenKind as T;
+        let dot = self.it.next_span();
+        let Some(first) = self.it.next_if(|t| {
+            dot.is_adjacent(t.span) && matches!(t.kind, T::Int(..) | T::LocalLabel | T::Identifier)
+        }) else {
+            self.src.report(dot, "expected an arrangement after `.`");
+            return None;
+        };
+        // `4s` is an integer followed by the element size, `16b` is lexed as a local label
+        let mut arrangement = first.span;
+        if first.kind.is_int() {
+            if let Some(size) = self
+                .it
+                .next_if(|t| t.kind == T::Identifier && first.span.is_adjacent(t.span))
+            {
+                arrangement = Span::group(first.span, size.span);
             }
-            Some(T::Float) => {
-                let span = self.it.next_span();
-                self.parse_float(span)
+        }
+        let index = match self.it.next_if_eq(T::LeftSquareBracket) {
+            Some(open) => {
+                let expr = self.parse_expr()?;
+                if self.it.next_if_eq(T::RightSquareBracket).is_none() {
+                    self.src
+                        .report(open.span, "expected `]` after the lane index");
+                    return None;
+                }
+                Some(&*self.bump.alloc(expr))
             }
-            _ => todo!("invalid addr mode arg"),
+            None => None,
         };
-        self.bump.alloc([first, second])
+        Some(ast::Expr::Vector {
+            reg,
+            arrangement,
+            index,
+        })
     }
 
-    fn parse_address(&mut self, span: Span) -> ast::Expr<'bump> {
-        let args = self.parse_address_arg();
-        if self.it.peek_kind() != Some(TokenKind::RightSquareBracket) {
-            todo!("error if no right bracket");
+    // called after consuming {
+    // {v0.16b}
+    // {v0.4s, v1.4s}
+    // {v0.4s-v3.4s}
+    fn parse_reg_list(&mut self, span: Span) -> ast::Expr<'bump> {
+        use TokenKind as T;
+        let mut regs = BumpVec::<ast::Expr<'bump>>::with_capacity_in(4, self.bump);
+        let mut range = false;
+        loop {
+            let Some(reg) = self.parse_primary() else {
+                return ast::Expr::Error;
+            };
+            regs.push(reg);
+            match self.it.peek_kind() {
+                Some(T::Comma) if !range => {
+                    self.it.next();
+                }
+                Some(T::Minus) if !range && regs.len() == 1 => {
+                    self.it.next();
+                    range = true;
+                }
+                Some(T::RightCurlyBracket) => break,
+                _ => {
+                    self.src
+                        .report(span, "expected `}` to close the register list");
+                    return ast::Expr::Error;
+                }
+            }
         }
         let end = self.it.next_span();
-        ast::Expr::Address {
-            args,
+        ast::Expr::RegList {
+            regs: regs.into_bump_slice(),
             group: Span::group(span, end),
+            range,
         }
     }
 
+    fn binary_op(kind: TokenKind) -> Option<BinaryOp> {
+        use TokenKind as T;
+        Some(match kind {
+            T::Star => BinaryOp::Mul,
+            T::Slash => BinaryOp::Div,
+            T::Percent => BinaryOp::Rem,
+            T::Plus => BinaryOp::Add,
+            T::Minus => BinaryOp::Sub,
+            T::ShiftLeft => BinaryOp::Shl,
+            T::ShiftRight => BinaryOp::Shr,
+            T::Less => BinaryOp::Lt,
+            T::LessEquals => BinaryOp::Le,
+            T::Greater => BinaryOp::Gt,
+            T::GreaterEquals => BinaryOp::Ge,
+            T::EqualsEquals => BinaryOp::Eq,
+            T::BangEquals => BinaryOp::Ne,
+            T::Ampersand => BinaryOp::And,
+            T::Caret => BinaryOp::Xor,
+            T::Pipe => BinaryOp::Or,
+            T::AmpersandAmpersand => BinaryOp::LogicalAnd,
+            T::PipePipe => BinaryOp::LogicalOr,
+            _ => return None,
+        })
+    }
+
     // x1
-    // #123
-    // "abc",
-    // [x1, 10]
+    // 123
+    // "abc"
     // LSL #1
-    fn parse_one_arg(&mut self) -> Option<ast::Expr<'bump>> {
+    // (end - start) * 2
+    fn parse_primary(&mut self) -> Option<ast::Expr<'bump>> {
         use TokenKind as T;
         let Token { kind, span } = self.it.next()?;
         match kind {
             T::Identifier => {
-                if let Some(T::Int(base)) = self.it.peek_kind() {
-                    let int_span = self.it.next_span();
-                    let int = self.parse_int(int_span, base)?;
-                    Some(expr::IdentInt { span, int }.into())
+                if self.it.next_if_eq(T::Hash).is_some() {
+                    let expr = self.parse_expr()?;
+                    Some(
+                        expr::IdentInt {
+                            span,
+                            expr: self.bump.alloc(expr),
+                        }
+                        .into(),
+                    )
+                } else if self
+                    .it
+                    .peek()
+                    .is_some_and(|t| t.kind == T::Dot && span.is_adjacent(t.span))
+                {
+                    self.parse_vector(span)
                 } else {
                     Some(ast::Expr::Ident { span })
                 }
             }
             T::Int(base) => Some(self.parse_int(span, base)?.into()),
+            T::LocalLabel => Some(ast::Expr::Ident { span }),
             T::Float => Some(self.parse_float(span)),
-            T::LeftSquareBracket => Some(self.parse_address(span)),
-            _ => todo!("error invalid arg"),
+            T::String => Some(ast::Expr::String { span }),
+            T::Colon => {
+                let Some(modifier) = self.it.next_if_eq(T::Identifier) else {
+                    self.src
+                        .report(span, "expected a relocation modifier after `:`");
+                    return None;
+                };
+                if self.it.next_if_eq(T::Colon).is_none() {
+                    self.src
+                        .report(modifier.span, "expected `:` after the modifier");
+                    return None;
+                }
+                let Some(label) = self
+                    .it
+                    .next_if(|t| matches!(t.kind, T::Identifier | T::LocalLabel))
+                else {
+                    self.src
+                        .report(modifier.span, "expected a label after the modifier");
+                    return None;
+                };
+                Some(ast::Expr::Reloc {
+                    modifier: modifier.span,
+                    label: label.span,
+                })
+            }
+            T::LeftParen => {
+                let expr = self.parse_expr()?;
+                if self.it.next_if_eq(T::RightParen).is_none() {
+                    self.src.report(span, "unclosed `(`");
+                    return None;
+                }
+                Some(expr)
+            }
+            _ => {
+                self.src.report(span, "expected an expression");
+                None
+            }
+        }
+    }
+
+    fn parse_unary(&mut self) -> Option<ast::Expr<'bump>> {
+        use TokenKind as T;
+        let op = match self.it.peek_kind()? {
+            T::Minus => UnaryOp::Neg,
+            T::Tilde => UnaryOp::Not,
+            T::Bang => UnaryOp::LogicalNot,
+            T::Plus => {
+                self.it.next();
+                return self.parse_unary();
+            }
+            _ => return self.parse_primary(),
+        };
+        let span = self.it.next_span();
+        let expr = self.parse_unary()?;
+        Some(ast::Expr::Unary {
+            op,
+            expr: self.bump.alloc(expr),
+            span,
+        })
+    }
+
+    // precedence climbing, operators that bind looser than `min_prec` are left for the caller
+    fn parse_binary(&mut self, min_prec: u8) -> Option<ast::Expr<'bump>> {
+        let mut lhs = self.parse_unary()?;
+        while let Some(op) = self.it.peek_kind().and_then(Self::binary_op) {
+            if op.precedence() < min_prec {
+                break;
+            }
+            let span = self.it.next_span();
+            let rhs = self.parse_binary(op.precedence() + 1)?;
+            lhs = ast::Expr::Binary {
+                op,
+                lhs: self.bump.alloc(lhs),
+                rhs: self.bump.alloc(rhs),
+                span,
+            };
+        }
+        Some(lhs)
+    }
+
+    fn parse_expr(&mut self) -> Option<ast::Expr<'bump>> {
+        self.parse_binary(0)
+    }
+
+    // x1
+    // #123
+    // "abc",
+    // [x1, 10]
+    // {v0.4s-v3.4s}
+    // LSL #1
+    // #(FRAME_SIZE + 16) * 2
+    fn parse_one_arg(&mut self) -> Option<ast::Expr<'bump>> {
+        use TokenKind as T;
+        match self.it.peek_kind()? {
+            T::LeftSquareBracket => {
+                let span = self.it.next_span();
+                Some(self.parse_address(span))
+            }
+            T::LeftCurlyBracket => {
+                let span = self.it.next_span();
+                Some(self.parse_reg_list(span))
+            }
+            T::Hash => {
+                self.it.next();
+                self.parse_expr()
+            }
+            _ => self.parse_expr(),
         }
     }
 
@@ -255,22 +586,61 @@ fn parse_args(&mut self, is_instr: bool) -> Option<&'bump [ast::Expr<'bump>]> {
                 args.push(ast::Expr::Ident { span: modif })
             }
         }
+        Some(self.parse_arg_list(args))
+    }
 
+    // rest of the line as one name, `armv8.2-a+crc` of `.arch` is not an expression
+    fn parse_name_arg(&mut self) -> Option<&'bump [ast::Expr<'bump>]> {
+        use TokenKind as T;
+        if let None | Some(T::Newline) = self.it.peek_kind() {
+            return None;
+        }
+        let first = self.it.next_span();
+        let mut last = first;
         loop {
-            match self.parse_one_arg() {
-                Some(expr) => args.push(expr),
-                None => args.push(ast::Expr::Error),
-            }
             match self.it.peek_kind() {
-                Some(T::Comma) => {
+                Some(T::Newline) => {
+                    self.it.next();
+                    break;
+                }
+                Some(_) => last = self.it.next_span(),
+                None => break,
+            }
+        }
+        let mut args = BumpVec::with_capacity_in(1, self.bump);
+        args.push(ast::Expr::Ident {
+            span: Span::group(first, last),
+        });
+        Some(args.into_bump_slice())
+    }
+
+    // comma separated args up to the end of the line, appended to `args`
+    fn parse_arg_list(
+        &mut self,
+        mut args: BumpVec<'bump, ast::Expr<'bump>>,
+    ) -> &'bump [ast::Expr<'bump>] {
+        use TokenKind as T;
+        loop {
+            let parsed = self.parse_one_arg();
+            let reported = parsed.is_none();
+            args.push(parsed.unwrap_or(ast::Expr::Error));
+            match self.it.peek() {
+                Some(Token { kind: T::Comma, .. }) => {
                     self.it.next(); // consume comma
                 }
-                Some(T::Newline) => {
+                Some(Token {
+                    kind: T::Newline, ..
+                }) => {
                     self.it.next();
                     break;
                 }
-                Some(_) => {
+                Some(&Token { span, .. }) => {
+                    if !reported {
+                        self.src.report(span, "expected `,` or end of line");
+                    }
                     args.push(ast::Expr::Error);
+                    // resync at the next line
+                    self.it.next_while(|t| t.kind != T::Newline);
                     break;
                 }
                 None => {
@@ -278,8 +648,7 @@ fn parse_args(&mut self, is_instr: bool) -> Option<&'bump [ast::Expr<'bump>]> {
                 }
             }
         }
-
-        Some(args.into_bump_slice())
+        args.into_bump_slice()
     }
 
     fn parse_root(&mut self) -> ast::Top<'bump> {
@@ -293,6 +662,13 @@ fn parse_root(&mut self) -> ast::Top<'bump> {
                     self.it.next();
                     ast::Top::Label(span)
                 }
+                Some(T::Equals) => {
+                    let eq = self.it.next_span();
+                    let mut args = BumpVec::with_capacity_in(2, self.bump);
+                    args.push(ast::Expr::Ident { span });
+                    let args = self.parse_arg_list(args);
+                    ast::Top::Assign { eq, args }
+                }
                 Some(_) => {
                     let args = self.parse_args(true);
                     ast::Top::Instruction { mnem: span, args }
@@ -302,11 +678,15 @@ fn parse_root(&mut self) -> ast::Top<'bump> {
                     args: None,
                 },
             },
+            T::Int(..) if self.it.next_if_eq(T::Colon).is_some() => ast::Top::Label(span),
             T::Dot => match self.it.peek_kind() {
                 Some(T::Identifier) => {
-                    self.it.next();
-                    let args = self.parse_args(false);
-                    ast::Top::Directive { name: span, args }
+                    let name = self.it.next_span();
+                    let args = match self.src.span(name).to_ascii_lowercase().as_str() {
+                        "arch" | "cpu" => self.parse_name_arg(),
+                        _ => self.parse_args(false),
+                    };
+                    ast::Top::Directive { name, args }
                 }
                 _ => ast::Top::Error,
             },
@@ -314,6 +694,28 @@ fn parse_root(&mut self) -> ast::Top<'bump> {
         }
     }
 
+    /// skip the lines of a conditional block that is not assembled,
+    /// up to the `.else`, `.elseif` or `.endif` that ends it
+    pub fn skip_conditional(&mut self) {
+        // at most the newline that ended the last statement was read
+        let current = self.it.current.take();
+        debug_assert!(matches!(
+            current,
+            None | Some(
+                None | Some(Token {
+                    kind: TokenKind::Newline,
+                    ..
+                })
+            )
+        ));
+        self.it.pre.get_mut().skip_conditional();
+    }
+
+    /// ids of the macro expansions the last statement was read from, outermost first
+    pub fn expansions(&self) -> std::cell::Ref<'_, [u32]> {
+        std::cell::Ref::map(self.it.pre.borrow(), |pre| pre.expansions())
+    }
+
     pub fn next<'a>(&'a mut self) -> Option<ast::Top<'bump>> {
         self.it.next_while(|t| t.kind == TokenKind::Newline);
         self.it.peek_kind()?;
@@ -0,0 +1,594 @@
+use super::lex::{IntRadix, Scanner, Token, TokenKind};
+use crate::code::{self, FileId, Span};
+use rustc_hash::FxHashMap as HashMap;
+use std::rc::Rc;
+
+/// expansions inside expansions, before giving up on a macro that calls itself
+const MAX_DEPTH: usize = 100;
+
+struct Param<'src> {
+    name: &'src str,
+    default: Vec<Token>,
+}
+
+struct Macro<'src> {
+    params: Vec<Param<'src>>,
+    /// every line between `.macro` and `.endm`, each ending with a newline
+    body: Vec<Token>,
+}
+
+struct Expansion {
+    tokens: Vec<Token>,
+    index: usize,
+}
+
+enum Input<'src> {
+    /// the main file, or a file loaded by the `.include` at `from`
+    File {
+        scanner: Scanner<'src>,
+        from: Option<Span>,
+    },
+    Expansion(Expansion),
+}
+
+/// sits between the scanner and the parser, handles the lines that the parser never sees:
+/// `.include`, `.macro` definitions, macro invocations and `.exitm`
+///
+/// expanded tokens keep the span they were written at, either in the macro body or in the
+/// arguments of the invocation, text pasted together with `\()` or `\@` is generated
+pub struct Preprocessor<'src> {
+    src: &'src code::SourceManager,
+    /// read ahead, the last one is read next
+    peeked: Vec<Token>,
+    macros: HashMap<String, Rc<Macro<'src>>>,
+    /// innermost last, an included file or expansion is popped once it runs out
+    inputs: Vec<Input<'src>>,
+    /// value of `\@`, counts every expansion
+    counter: u32,
+    /// `\@` of each expansion in `inputs`, innermost last
+    expanding: Vec<u32>,
+    /// nothing but labels since the last newline
+    line_start: bool,
+    /// last token read from an input was a newline
+    newline: bool,
+}
+
+impl<'src> Preprocessor<'src> {
+    pub fn new(src: &'src code::SourceManager, file: FileId) -> Self {
+        let scanner = Scanner::new(src.get(file));
+        Self {
+            src,
+            peeked: Vec::new(),
+            macros: HashMap::default(),
+            inputs: vec![Input::File {
+                scanner,
+                from: None,
+            }],
+            counter: 0,
+            expanding: Vec::new(),
+            line_start: true,
+            newline: true,
+        }
+    }
+
+    fn next_raw(&mut self) -> Option<Token> {
+        if let Some(tok) = self.peeked.pop() {
+            return Some(tok);
+        }
+        while let Some(input) = self.inputs.last_mut() {
+            let tok = match input {
+                Input::File { scanner, .. } => scanner.next(),
+                Input::Expansion(expansion) => {
+                    let tok = expansion.tokens.get(expansion.index).copied();
+                    expansion.index += 1;
+                    tok
+                }
+            };
+            if let Some(tok) = tok {
+                self.newline = tok == TokenKind::Newline;
+                return Some(tok);
+            }
+            if let Some(Input::File {
+                from: Some(from), ..
+            }) = self.pop_input()
+            {
+                if self.newline {
+                    continue;
+                }
+                // the last line of the included file did not end with one
+                self.newline = true;
+                return Some(Token {
+                    kind: TokenKind::Newline,
+                    span: from,
+                });
+            }
+        }
+        None
+    }
+
+    fn pop_input(&mut self) -> Option<Input<'src>> {
+        let input = self.inputs.pop();
+        if let Some(Input::Expansion(..)) = input {
+            self.expanding.pop();
+        }
+        input
+    }
+
+    fn depth(&self) -> usize {
+        self.expanding.len()
+    }
+
+    /// macro expansions that are being read, outermost first, each has a different id
+    pub fn expansions(&self) -> &[u32] {
+        &self.expanding
+    }
+
+    fn peek_raw(&mut self) -> Option<Token> {
+        if self.peeked.is_empty() {
+            let tok = self.next_raw()?;
+            self.peeked.push(tok);
+        }
+        self.peeked.last().copied()
+    }
+
+    fn peek_raw_kind(&mut self) -> Option<TokenKind> {
+        self.peek_raw().map(|t| t.kind)
+    }
+
+    /// consume through the next newline
+    fn skip_line(&mut self) {
+        while let Some(tok) = self.next_raw() {
+            if tok == TokenKind::Newline {
+                break;
+            }
+        }
+    }
+
+    /// the identifier after a `.`, if it is one of `names`
+    fn peek_directive(&mut self, names: &[&'static str]) -> Option<&'static str> {
+        let tok = self.peek_raw().filter(|t| *t == TokenKind::Identifier)?;
+        let s = self.src.span(tok.span);
+        names
+            .iter()
+            .copied()
+            .find(|name| s.eq_ignore_ascii_case(name))
+    }
+
+    pub fn next(&mut self) -> Option<Token> {
+        loop {
+            let tok = self.next_raw()?;
+            if !self.line_start {
+                self.line_start = tok == TokenKind::Newline;
+                return Some(tok);
+            }
+            match tok.kind {
+                TokenKind::Newline | TokenKind::Colon => return Some(tok),
+                TokenKind::Dot => match self.peek_directive(&["include", "macro", "endm", "exitm"])
+                {
+                    Some("include") => {
+                        self.peeked.pop();
+                        self.include(tok.span);
+                    }
+                    Some("macro") => {
+                        self.peeked.pop();
+                        self.define(tok.span);
+                    }
+                    Some("endm") => {
+                        self.src.report(tok.span, "`.endm` without `.macro`");
+                        self.skip_line();
+                    }
+                    Some(_) if !matches!(self.inputs.last(), Some(Input::Expansion(..))) => {
+                        self.src.report(tok.span, "`.exitm` outside of a macro");
+                        self.skip_line();
+                    }
+                    Some(_) => {
+                        self.peeked.pop();
+                        self.pop_input();
+                    }
+                    None => {
+                        self.line_start = false;
+                        return Some(tok);
+                    }
+                },
+                TokenKind::Identifier if self.peek_raw_kind() != Some(TokenKind::Colon) => {
+                    let name = self.src.span(tok.span).to_ascii_lowercase();
+                    match self.macros.get(&name) {
+                        Some(mac) => self.invoke(tok.span, Rc::clone(mac)),
+                        None => {
+                            self.line_start = false;
+                            return Some(tok);
+                        }
+                    }
+                }
+                // label, still at the start of the line
+                _ if self.peek_raw_kind() == Some(TokenKind::Colon) => return Some(tok),
+                _ => {
+                    self.line_start = false;
+                    return Some(tok);
+                }
+            }
+        }
+    }
+
+    /// skip whole lines up to the `.else`, `.elseif` or `.endif` that ends the conditional
+    /// block, nested blocks are skipped with it and nothing is expanded or included
+    pub fn skip_conditional(&mut self) {
+        use TokenKind as T;
+        let mut depth = 0usize;
+        let mut line_start = true;
+        while let Some(tok) = self.next_raw() {
+            if line_start && tok == T::Dot {
+                match self.peek_directive(&["if", "ifdef", "ifndef", "else", "elseif", "endif"]) {
+                    Some("if" | "ifdef" | "ifndef") => depth += 1,
+                    Some("endif") if depth > 0 => depth -= 1,
+                    Some(_) if depth == 0 => {
+                        self.peeked.push(tok);
+                        break;
+                    }
+                    _ => (),
+                }
+            }
+            line_start = tok == T::Newline;
+        }
+        self.line_start = true;
+    }
+
+    /// tokens up to a comma or newline that is not inside brackets, leaves the comma or newline
+    fn collect_arg(&mut self) -> Vec<Token> {
+        use TokenKind as T;
+        let mut depth = 0usize;
+        let mut arg = Vec::new();
+        while let Some(tok) = self.peek_raw() {
+            match tok.kind {
+                T::Newline => break,
+                T::Comma if depth == 0 => break,
+                T::LeftSquareBracket | T::LeftCurlyBracket | T::LeftParen => depth += 1,
+                T::RightSquareBracket | T::RightCurlyBracket | T::RightParen => {
+                    depth = depth.saturating_sub(1)
+                }
+                _ => (),
+            }
+            arg.push(tok);
+            self.peeked.pop();
+        }
+        arg
+    }
+
+    // called after consuming .include
+    fn include(&mut self, dot: Span) {
+        let Some(path) = self.next_raw().filter(|t| *t == TokenKind::String) else {
+            self.src.report(dot, "expected a file name in quotes");
+            self.skip_line();
+            return;
+        };
+        if self.next_raw().is_some_and(|t| t != TokenKind::Newline) {
+            self.src
+                .report(dot, "expected a newline after the file name");
+            self.skip_line();
+        }
+        let s = self.src.span(path.span);
+        let Some(file) = self.src.include(path.span, &s[1..s.len() - 1]) else {
+            return;
+        };
+        self.inputs.push(Input::File {
+            scanner: Scanner::new(self.src.get(file)),
+            from: Some(path.span),
+        });
+    }
+
+    // called after consuming .macro
+    fn define(&mut self, dot: Span) {
+        use TokenKind as T;
+        let Some(name) = self.next_raw().filter(|t| *t == T::Identifier) else {
+            self.src.report(dot, "expected a macro name");
+            self.skip_line();
+            return;
+        };
+        let mut params = Vec::new();
+        while let Some(tok) = self.next_raw() {
+            match tok.kind {
+                T::Newline => break,
+                T::Comma => (),
+                T::Identifier => {
+                    let mut default = Vec::new();
+                    if self.peek_raw_kind() == Some(T::Equals) {
+                        self.peeked.pop();
+                        default = self.collect_arg();
+                    }
+                    params.push(Param {
+                        name: self.src.span(tok.span),
+                        default,
+                    });
+                }
+                _ => {
+                    self.src.report(tok.span, "expected a macro parameter");
+                    self.skip_line();
+                    break;
+                }
+            }
+        }
+
+        let mut body = Vec::new();
+        let mut depth = 0usize;
+        let mut line_start = true;
+        loop {
+            let Some(tok) = self.next_raw() else {
+                self.src.report(name.span, "`.macro` without `.endm`");
+                return;
+            };
+            if line_start && tok == T::Dot {
+                match self.peek_directive(&["macro", "endm"]) {
+                    Some("endm") if depth == 0 => {
+                        self.peeked.pop();
+                        self.skip_line();
+                        break;
+                    }
+                    Some("endm") => depth -= 1,
+                    Some(_) => depth += 1,
+                    None => (),
+                }
+            }
+            line_start = tok == T::Newline;
+            body.push(tok);
+        }
+
+        let key = self.src.span(name.span).to_ascii_lowercase();
+        if self.macros.contains_key(&key) {
+            self.src.report(
+                name.span,
+                format_args!("macro `{}` is already defined", self.src.span(name.span)),
+            );
+            return;
+        }
+        self.macros.insert(key, Rc::new(Macro { params, body }));
+    }
+
+    // called after consuming the macro name
+    fn invoke(&mut self, name: Span, mac: Rc<Macro<'src>>) {
+        use TokenKind as T;
+        if self.depth() >= MAX_DEPTH {
+            self.src
+                .report(name, "macro expansion is nested too deeply");
+            self.skip_line();
+            return;
+        }
+        let mut args: Vec<Option<Vec<Token>>> = vec![None; mac.params.len()];
+        let mut position = 0;
+        loop {
+            let arg = self.collect_arg();
+            let keyword = match arg.as_slice() {
+                [key, eq, value @ ..] if *key == T::Identifier && *eq == T::Equals => {
+                    let s = self.src.span(key.span);
+                    match mac.params.iter().position(|p| p.name == s) {
+                        Some(index) => Some((index, value.to_vec())),
+                        None => {
+                            self.src
+                                .report(key.span, format_args!("unknown macro parameter `{s}`"));
+                            None
+                        }
+                    }
+                }
+                _ => None,
+            };
+            if let Some((index, value)) = keyword {
+                args[index] = Some(value);
+            } else if let Some(first) = arg.first() {
+                if position < args.len() {
+                    args[position] = Some(arg);
+                } else {
+                    self.src.report(first.span, "too many macro arguments");
+                }
+                position += 1;
+            } else {
+                // empty argument keeps the default
+                position += 1;
+            }
+            if self.next_raw().map_or(true, |t| t == T::Newline) {
+                break;
+            }
+        }
+
+        let args: Vec<Vec<Token>> = args
+            .into_iter()
+            .zip(&mac.params)
+            .map(|(arg, param)| arg.unwrap_or_else(|| param.default.clone()))
+            .collect();
+        let tokens = self.substitute(&mac, &args);
+        self.expanding.push(self.counter);
+        self.counter += 1;
+        self.inputs
+            .push(Input::Expansion(Expansion { tokens, index: 0 }));
+    }
+
+    /// body with every `\param` and `\@` replaced, a substitution is pasted together with
+    /// the words written right next to it, `\()` only separates
+    fn substitute(&mut self, mac: &Macro<'src>, args: &[Vec<Token>]) -> Vec<Token> {
+        use TokenKind as T;
+        let mut out = Vec::with_capacity(mac.body.len());
+        let mut prev: Option<Span> = None;
+        let mut prev_subst = false;
+        let mut i = 0;
+        while let Some(&tok) = mac.body.get(i) {
+            let adjacent = prev.is_some_and(|p| p.is_adjacent(tok.span));
+            if tok != T::Backslash {
+                self.push_pasted(&mut out, tok, adjacent && prev_subst);
+                prev = Some(tok.span);
+                prev_subst = false;
+                i += 1;
+                continue;
+            }
+            let next = mac.body.get(i + 1).copied();
+            let last = match next.map(|t| t.kind) {
+                Some(T::At) => {
+                    let text = self.counter.to_string();
+                    let span = self.src.generate_span(tok.span.loc(), &text);
+                    let int = Token {
+                        kind: T::Int(IntRadix::Dec),
+                        span,
+                    };
+                    self.push_pasted(&mut out, int, adjacent);
+                    i += 2;
+                    next
+                }
+                Some(T::LeftParen) if mac.body.get(i + 2).is_some_and(|t| *t == T::RightParen) => {
+                    i += 3;
+                    mac.body.get(i - 1).copied()
+                }
+                Some(T::Identifier) => {
+                    let ident = next.unwrap();
+                    let s = self.src.span(ident.span);
+                    match mac.params.iter().position(|p| p.name == s) {
+                        Some(index) => {
+                            if let Some((first, rest)) = args[index].split_first() {
+                                self.push_pasted(&mut out, *first, adjacent);
+                                out.extend_from_slice(rest);
+                            }
+                        }
+                        None => self
+                            .src
+                            .report(ident.span, format_args!("unknown macro parameter `{s}`")),
+                    }
+                    i += 2;
+                    next
+                }
+                _ => {
+                    self.src
+                        .report(tok.span, "expected a macro parameter after `\\`");
+                    i += 1;
+                    Some(tok)
+                }
+            };
+            prev = last.map(|t| t.span);
+            prev_subst = true;
+        }
+        out
+    }
+
+    fn push_pasted(&mut self, out: &mut Vec<Token>, tok: Token, paste: bool) {
+        if let Some(last) = out.last_mut().filter(|_| paste) {
+            if last.kind.is_word() && tok.kind.is_word() {
+                let text = format!("{}{}", self.src.span(last.span), self.src.span(tok.span));
+                let kind = if text.bytes().all(|b| b.is_ascii_digit()) {
+                    TokenKind::Int(IntRadix::Dec)
+                } else {
+                    TokenKind::Identifier
+                };
+                let span = self.src.generate_span(last.span.loc(), &text);
+                *last = Token { kind, span };
+                return;
+            }
+        }
+        out.push(tok);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn expand_file(src: &code::SourceManager, file: FileId) -> String {
+        let mut pre = Preprocessor::new(src, file);
+        let mut out = String::new();
+        while let Some(tok) = pre.next() {
+            match tok.kind {
+                TokenKind::Newline => out.push('\n'),
+                _ => {
+                    if !out.is_empty() && !out.ends_with('\n') {
+                        out.push(' ');
+                    }
+                    out.push_str(src.span(tok.span));
+                }
+            }
+        }
+        out
+    }
+
+    fn expand(text: &str) -> (String, usize) {
+        let src = code::SourceManager::new();
+        let file = src.add(code::Source::new(PathBuf::new(), text.to_string()));
+        (expand_file(&src, file), src.error_count())
+    }
+
+    /// writes `files` to a new directory, returns the expansion of the first one
+    fn expand_files(name: &str, files: &[(&str, &str)]) -> (String, usize) {
+        let dir = std::env::temp_dir().join(format!("armventure-{name}-{}", std::process::id()));
+        for (path, text) in files {
+            let path = dir.join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, text).unwrap();
+        }
+        let mut src = code::SourceManager::new();
+        src.add_search_path(dir.join("inc"));
+        let file = src.load(dir.join(files[0].0)).unwrap();
+        let out = expand_file(&src, file);
+        std::fs::remove_dir_all(dir).unwrap();
+        (out, src.error_count())
+    }
+
+    #[test]
+    fn it_substitutes_args() {
+        let text = ".macro inc reg, by=1\nADD \\reg, \\reg, #\\by\n.endm\ninc x0\ninc x1, 4\n";
+        assert_eq!(
+            expand(text),
+            ("ADD x0 , x0 , # 1\nADD x1 , x1 , # 4\n".to_string(), 0)
+        );
+        let text = ".macro m a, b=2, c=3\n\\a \\b \\c\n.endm\nm 1, c=[x0, #4]\nm 1,,5\n";
+        assert_eq!(expand(text), ("1 2 [ x0 , # 4 ]\n1 2 5\n".to_string(), 0));
+    }
+
+    #[test]
+    fn it_pastes_unique_labels() {
+        let text = ".macro spin r\nloop\\@: SUB \\r\\()_x, \\r, #\\@\n.endm\nspin w\nspin x\n";
+        assert_eq!(
+            expand(text),
+            (
+                "loop0 : SUB w_x , w , # 0\nloop1 : SUB x_x , x , # 1\n".to_string(),
+                0
+            )
+        );
+    }
+
+    #[test]
+    fn it_nests_and_exits() {
+        let text =
+            ".macro outer\n.macro inner\nB x\n.endm\ninner\n.exitm\nB y\n.endm\nouter\nB z\n";
+        assert_eq!(expand(text), ("B x\nB z\n".to_string(), 0));
+    }
+
+    #[test]
+    fn it_reports_errors() {
+        assert_eq!(expand(".macro m\n").1, 1);
+        assert_eq!(expand(".endm\n.exitm\n").1, 2);
+        assert_eq!(expand(".macro m a\n\\b\n.endm\nm 1, 2\n").1, 2);
+        assert_eq!(expand(".macro m\nm\n.endm\nm\n").1, 1);
+    }
+
+    #[test]
+    fn it_includes_files() {
+        let (out, errors) = expand_files(
+            "include",
+            &[
+                (
+                    "main.s",
+                    ".include \"lib/spin.s\"\n.include \"defs.s\"\nspin\n",
+                ),
+                ("lib/spin.s", ".macro spin\nB .\n.endm\nB lib"),
+                ("inc/defs.s", "B defs\n"),
+            ],
+        );
+        assert_eq!((out.as_str(), errors), ("B lib\nB defs\nB .\n", 0));
+    }
+
+    #[test]
+    fn it_reports_include_errors() {
+        let (_, errors) = expand_files(
+            "cycle",
+            &[
+                ("a.s", ".include \"b.s\"\n"),
+                ("b.s", ".include \"a.s\"\n.include \"missing.s\"\n"),
+            ],
+        );
+        assert_eq!(errors, 2);
+    }
+}
@@ -1,10 +1,10 @@
 use std::{
-    cell::Cell,
+    cell::{Cell, RefCell},
     fmt,
     marker::PhantomData,
     num::{NonZeroU32, NonZeroU8},
     ops::Range,
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
 use thiserror::Error;
 use typed_arena::Arena;
@@ -28,28 +28,41 @@ pub fn new(path: PathBuf) -> Self {
 
     pub fn load(self) -> Result<Source> {
         let content = std::fs::read_to_string(self.path.as_path())?;
-        Ok(Source {
-            path: self.path,
-            content,
-            diags: Cell::new(Vec::new()),
-        })
+        Ok(Source::new(self.path, content))
     }
 }
 
+/// index of a `Source` in the `SourceManager`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FileId(u32);
+
 pub struct Source {
+    file: FileId,
     path: PathBuf,
     content: String,
-    diags: Cell<Vec<String>>,
+    /// text that is not in the file, like identifiers pasted together by a macro,
+    /// each with the offset its spans start at, counting on from the end of `content`
+    generated: RefCell<Vec<(usize, Box<str>)>>,
+    /// the `.include` that loaded this file
+    included_from: Option<Span>,
 }
 
 impl Source {
     pub fn new(path: PathBuf, content: String) -> Self {
         Source {
+            file: FileId::default(),
             path,
             content,
-            diags: Cell::new(Vec::new()),
+            generated: RefCell::new(Vec::new()),
+            included_from: None,
         }
     }
+    pub fn file(&self) -> FileId {
+        self.file
+    }
+    pub fn path(&self) -> &Path {
+        self.path.as_path()
+    }
     pub fn str(&self) -> &str {
         self.content.as_str()
     }
@@ -57,20 +70,149 @@ pub fn create_span(&self, loc: Loc, start: usize, end: usize) -> Span {
         let _ = self.content[start..end];
         Span { loc, start, end }
     }
+    /// span for text that is not in the file, reported at `loc`
+    pub fn generate_span(&self, loc: Loc, text: &str) -> Span {
+        debug_assert!(!text.is_empty());
+        let mut generated = self.generated.borrow_mut();
+        let start = generated
+            .last()
+            .map_or(self.content.len(), |(start, s)| start + s.len());
+        generated.push((start, text.into()));
+        Span {
+            loc,
+            start,
+            end: start + text.len(),
+        }
+    }
+    pub fn span(&self, span: Span) -> &str {
+        if span.end <= self.content.len() {
+            return &self.content[span.start..span.end];
+        }
+        let generated = self.generated.borrow();
+        let index = generated.partition_point(|(start, _)| *start <= span.start) - 1;
+        let (start, text) = &generated[index];
+        let text: *const str = &text[span.start - start..span.end - start];
+        // SAFETY: generated text is never removed or changed,
+        // and the boxed str does not move when the vec grows
+        unsafe { &*text }
+    }
+}
+
+/// every file of one assembly, the main file and everything it includes
+#[derive(Default)]
+pub struct SourceManager {
+    #[allow(
+        clippy::vec_box,
+        reason = "get hands out references that outlive the borrow"
+    )]
+    files: RefCell<Vec<Box<Source>>>,
+    /// directories searched by `.include` after the directory of the including file
+    search_paths: Vec<PathBuf>,
+    diags: Cell<Vec<String>>,
+}
+
+impl SourceManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn add_search_path(&mut self, path: PathBuf) {
+        self.search_paths.push(path);
+    }
+    pub fn add(&self, mut source: Source) -> FileId {
+        let mut files = self.files.borrow_mut();
+        source.file = FileId(u32::try_from(files.len()).expect("too many files"));
+        files.push(Box::new(source));
+        FileId(u32::try_from(files.len() - 1).unwrap())
+    }
+    pub fn load(&self, path: PathBuf) -> Result<FileId> {
+        Ok(self.add(File::new(path).load()?))
+    }
+    pub fn get(&self, file: FileId) -> &Source {
+        let files = self.files.borrow();
+        let source: *const Source = &*files[file.0 as usize];
+        // SAFETY: sources are never removed, and the box does not move when the vec grows
+        unsafe { &*source }
+    }
     pub fn span(&self, span: Span) -> &str {
-        &self.content[span.start..span.end]
+        self.get(span.loc.file).span(span)
+    }
+    /// span for text that is not in any file, reported at `loc`
+    pub fn generate_span(&self, loc: Loc, text: &str) -> Span {
+        self.get(loc.file).generate_span(loc, text)
+    }
+
+    /// load the file named by the `.include` at `from`, looked up next to the including file
+    /// and then in each search path
+    pub fn include(&self, from: Span, name: &str) -> Option<FileId> {
+        let including = self.get(from.loc.file);
+        let dir = including.path.parent().unwrap_or(Path::new(""));
+        let Some(path) = std::iter::once(dir)
+            .chain(self.search_paths.iter().map(PathBuf::as_path))
+            .map(|dir| dir.join(name))
+            .find(|path| path.is_file())
+        else {
+            self.report(from, format_args!("can't find `{name}` to include"));
+            return None;
+        };
+
+        let canonical = std::fs::canonicalize(&path).ok();
+        let mut source = Some(including);
+        while let Some(s) = source {
+            if canonical.is_some() && std::fs::canonicalize(&s.path).ok() == canonical {
+                self.report(from, format_args!("`{name}` is already being included"));
+                return None;
+            }
+            source = s.included_from.map(|span| self.get(span.loc.file));
+        }
+
+        match File::new(path).load() {
+            Ok(mut source) => {
+                source.included_from = Some(from);
+                Some(self.add(source))
+            }
+            Err(e) => {
+                self.report(from, format_args!("{e}"));
+                None
+            }
+        }
+    }
+
+    pub fn error_count(&self) -> usize {
+        let vec = self.diags.take();
+        let count = vec.len();
+        self.diags.set(vec);
+        count
+    }
+    /// messages of every error reported so far
+    pub fn errors(&self) -> Vec<String> {
+        let vec = self.diags.take();
+        let errors = vec.clone();
+        self.diags.set(vec);
+        errors
     }
     pub fn report<S: ToString>(&self, span: Span, msg: S) {
         let mut vec = self.diags.take();
         vec.push(msg.to_string());
 
+        let source = self.get(span.loc.file);
         eprintln!(
             "{}:{}:{}: {}",
-            self.path.display(),
+            source.path.display(),
             span.loc.line(),
             span.loc.column(),
             vec.last().unwrap(),
         );
+        let mut from = source.included_from;
+        while let Some(span) = from {
+            let source = self.get(span.loc.file);
+            eprintln!(
+                "    included from {}:{}:{}",
+                source.path.display(),
+                span.loc.line(),
+                span.loc.column(),
+            );
+            from = source.included_from;
+        }
 
         self.diags.set(vec);
     }
@@ -78,6 +220,7 @@ pub fn report<S: ToString>(&self, span: Span, msg: S) {
 
 #[derive(Clone, Copy, PartialEq, Eq)]
 pub struct Loc {
+    file: FileId,
     line: NonZeroU32,
     column: NonZeroU32,
 }
@@ -86,7 +229,13 @@ impl Loc {
     #[must_use]
     #[allow(clippy::missing_panics_doc, reason = "const")]
     pub fn new() -> Self {
+        Self::start_of(FileId::default())
+    }
+    #[must_use]
+    #[allow(clippy::missing_panics_doc, reason = "const")]
+    pub fn start_of(file: FileId) -> Self {
         Self {
+            file,
             line: NonZeroU32::new(1).unwrap(),
             column: NonZeroU32::new(1).unwrap(),
         }
@@ -103,6 +252,9 @@ pub fn inc_column(&mut self) {
         self.column = self.column.checked_add(1).unwrap();
     }
 
+    pub fn file(&self) -> FileId {
+        self.file
+    }
     pub fn line(&self) -> u32 {
         self.line.get()
     }
@@ -122,7 +274,7 @@ fn default() -> Self {
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Default)]
 pub struct Span {
     loc: Loc,
     //pub src: &'a str,
@@ -140,6 +292,10 @@ pub fn is_empty(&self) -> bool {
     pub fn loc(&self) -> Loc {
         self.loc
     }
+    /// nothing between the two spans, not even whitespace
+    pub fn is_adjacent(&self, next: Span) -> bool {
+        self.loc.file == next.loc.file && self.end == next.start
+    }
     pub fn group(start: Span, end: Span) -> Span {
         Span {
             loc: start.loc,
@@ -1,6 +1,9 @@
 use std::io::{Error as IoError, IoSlice, Write};
 
-use crate::sparsebin::SparseBin;
+use crate::{
+    obj::{GnuProperty, Object, Relocation, SymbolValue},
+    sparsebin::SparseBin,
+};
 
 const MAGIC: [u8; 4] = [0x7f, b'E', b'L', b'F'];
 #[derive(PartialEq, Eq)]
@@ -184,6 +187,309 @@ pub fn copy_data_64le(&self, d: &mut [u8]) {
     }
 }
 
+// symbol table entries
+mod sym {
+    pub mod bind {
+        pub const LOCAL: u8 = 0;
+        pub const GLOBAL: u8 = 1;
+    }
+    pub mod ty {
+        pub const NO_TYPE: u8 = 0;
+        pub const SECTION: u8 = 3;
+    }
+    /// section index of a symbol that is defined in another object
+    pub const UNDEF_IDX: u16 = 0;
+    /// section index of a symbol that is not relative to any section
+    pub const ABS_IDX: u16 = 0xFFF1;
+    pub struct Entry {
+        pub name_offset: u32,
+        pub bind: u8,
+        pub ty: u8,
+        pub sect_idx: u16,
+        pub value: u64,
+        pub size: u64,
+    }
+    impl Entry {
+        pub const SIZE_64: usize = 0x18;
+        pub fn copy_data_64le(&self, d: &mut [u8]) {
+            assert!(d.len() == Self::SIZE_64);
+            copy_bytes_le!(d[0x0..0x4], self.name_offset);
+            d[0x4] = (self.bind << 4) | self.ty;
+            d[0x5] = 0; // default visibility
+            copy_bytes_le!(d[0x6..0x8], self.sect_idx);
+            copy_bytes_le!(d[0x8..0x10], self.value);
+            copy_bytes_le!(d[0x10..0x18], self.size);
+        }
+    }
+}
+
+// relocation entries with addends
+mod rela {
+    pub struct Entry {
+        pub offset: u64,
+        pub sym_idx: u32,
+        pub ty: u32,
+        pub addend: i64,
+    }
+    impl Entry {
+        pub const SIZE_64: usize = 0x18;
+        pub fn copy_data_64le(&self, d: &mut [u8]) {
+            assert!(d.len() == Self::SIZE_64);
+            copy_bytes_le!(d[0x0..0x8], self.offset);
+            copy_bytes_le!(d[0x8..0x10], ((self.sym_idx as u64) << 32) | self.ty as u64);
+            copy_bytes_le!(d[0x10..0x18], self.addend);
+        }
+    }
+}
+
+// `.note.gnu.property`
+mod note {
+    /// `NT_GNU_PROPERTY_TYPE_0`
+    pub const GNU_PROPERTY_TYPE_0: u32 = 5;
+    /// `GNU_PROPERTY_AARCH64_FEATURE_1_AND`
+    pub const AARCH64_FEATURE_1_AND: u32 = 0xc0000000;
+    pub const NAME: &[u8; 4] = b"GNU\0";
+    /// header, name and one property with its 4 byte value padded to 8
+    pub const SIZE_64: usize = 0x20;
+}
+
+/// note with the feature bits of the AArch64 property, empty if there are none
+fn gnu_property_note(property: GnuProperty) -> Vec<u8> {
+    let features = property.feature_1_and();
+    if features == 0 {
+        return Vec::new();
+    }
+    let mut d = vec![0u8; note::SIZE_64];
+    copy_bytes_le!(d[0x0..0x4], u32, note::NAME.len());
+    // size of the property, its type, size and value padded to 8
+    copy_bytes_le!(d[0x4..0x8], 16u32);
+    copy_bytes_le!(d[0x8..0xC], note::GNU_PROPERTY_TYPE_0);
+    d[0xC..0x10].copy_from_slice(note::NAME);
+    copy_bytes_le!(d[0x10..0x14], note::AARCH64_FEATURE_1_AND);
+    copy_bytes_le!(d[0x14..0x18], 4u32);
+    copy_bytes_le!(d[0x18..0x1C], features);
+    d
+}
+
+/// null terminated names, referenced by their offset
+struct StrTab(Vec<u8>);
+
+impl StrTab {
+    fn new() -> Self {
+        // offset 0 is the empty name
+        Self(vec![0])
+    }
+
+    fn add(&mut self, name: &str) -> u32 {
+        let offset = self.0.len().try_into().unwrap();
+        self.0.extend_from_slice(name.as_bytes());
+        self.0.push(0);
+        offset
+    }
+}
+
+/// relocatable object with the code in `.text` and its symbols in `.symtab`,
+/// and `.rela.text` after the other sections if the linker has to fill in addresses,
+/// then `.note.gnu.property` if the object declares BTI or PAC
+pub fn write_object_64le<W: Write>(obj: &mut Object, file: &mut W) -> Result<(), IoError> {
+    // index 0 is the null section
+    const TEXT_IDX: u16 = 1;
+    const SYMTAB_IDX: u32 = 2;
+    const STRTAB_IDX: u32 = 3;
+    const SHSTRTAB_IDX: u16 = 4;
+
+    let mut strtab = StrTab::new();
+    // relocations of labels defined in this object are relative to the `.text` section symbol
+    let sect_symbol = !obj.relocations.is_empty();
+    let first_symbol = 1 + sect_symbol as usize;
+    // locals have to come before globals
+    let mut symbols: Vec<_> = obj.symbols.iter().collect();
+    symbols.sort_by_key(|sym| sym.global);
+    let first_global = first_symbol + symbols.iter().take_while(|sym| !sym.global).count();
+    let mut symtab = vec![0u8; sym::Entry::SIZE_64 * (first_symbol + symbols.len())];
+    if sect_symbol {
+        let entry = sym::Entry {
+            name_offset: 0,
+            bind: sym::bind::LOCAL,
+            ty: sym::ty::SECTION,
+            sect_idx: TEXT_IDX,
+            value: 0,
+            size: 0,
+        };
+        entry.copy_data_64le(&mut symtab[sym::Entry::SIZE_64..2 * sym::Entry::SIZE_64]);
+    }
+    for (i, symbol) in symbols.iter().enumerate() {
+        let (sect_idx, value) = match symbol.value {
+            SymbolValue::Text(addr) => (TEXT_IDX, addr),
+            SymbolValue::Absolute(value) => (sym::ABS_IDX, value as u64),
+            SymbolValue::Undefined => (sym::UNDEF_IDX, 0),
+        };
+        let entry = sym::Entry {
+            name_offset: strtab.add(&symbol.name),
+            bind: if symbol.global {
+                sym::bind::GLOBAL
+            } else {
+                sym::bind::LOCAL
+            },
+            ty: sym::ty::NO_TYPE,
+            sect_idx,
+            value,
+            size: 0,
+        };
+        let start = (first_symbol + i) * sym::Entry::SIZE_64;
+        entry.copy_data_64le(&mut symtab[start..start + sym::Entry::SIZE_64]);
+    }
+
+    let sym_idx = |reloc: &Relocation| -> u32 {
+        let idx = match &reloc.symbol {
+            Some(name) => {
+                first_symbol
+                    + symbols
+                        .iter()
+                        .position(|sym| &sym.name == name)
+                        .expect("relocation refers to a symbol of the object")
+            }
+            None => 1,
+        };
+        idx.try_into().unwrap()
+    };
+    let mut rela_text = vec![0u8; rela::Entry::SIZE_64 * obj.relocations.len()];
+    for (i, reloc) in obj.relocations.iter().enumerate() {
+        let entry = rela::Entry {
+            offset: reloc.offset,
+            sym_idx: sym_idx(reloc),
+            ty: reloc.kind as u32,
+            addend: reloc.addend,
+        };
+        let start = i * rela::Entry::SIZE_64;
+        entry.copy_data_64le(&mut rela_text[start..start + rela::Entry::SIZE_64]);
+    }
+
+    let mut shstrtab = StrTab::new();
+    let names = [
+        ".text",
+        ".symtab",
+        ".strtab",
+        ".shstrtab",
+        ".rela.text",
+        ".note.gnu.property",
+    ]
+    .map(|name| shstrtab.add(name));
+    let note = gnu_property_note(obj.gnu_property);
+
+    let text = obj.text_bytes();
+    let text_offset = Header::SIZE_64;
+    let symtab_offset = (text_offset + text.len()).next_multiple_of(8);
+    let strtab_offset = symtab_offset + symtab.len();
+    let shstrtab_offset = strtab_offset + strtab.0.len();
+    let rela_text_offset = (shstrtab_offset + shstrtab.0.len()).next_multiple_of(8);
+    let note_offset = (rela_text_offset + rela_text.len()).next_multiple_of(8);
+    let sect_offset = note_offset + note.len();
+    let mut sections = vec![
+        sect::Header {
+            name_offset: names[0],
+            ty: sect::Type::ProgBits,
+            flags: sect::flag::ALLOC | sect::flag::EXEC,
+            virt_addr: 0,
+            file_addr: text_offset,
+            file_size: text.len(),
+            link_idx: 0,
+            info: 0,
+            align: 4,
+            entry_size: 0,
+        },
+        sect::Header {
+            name_offset: names[1],
+            ty: sect::Type::SymTab,
+            flags: 0,
+            virt_addr: 0,
+            file_addr: symtab_offset,
+            file_size: symtab.len(),
+            link_idx: STRTAB_IDX,
+            info: first_global.try_into().unwrap(),
+            align: 8,
+            entry_size: sym::Entry::SIZE_64,
+        },
+        sect::Header {
+            name_offset: names[2],
+            ty: sect::Type::StrTab,
+            flags: 0,
+            virt_addr: 0,
+            file_addr: strtab_offset,
+            file_size: strtab.0.len(),
+            link_idx: 0,
+            info: 0,
+            align: 1,
+            entry_size: 0,
+        },
+        sect::Header {
+            name_offset: names[3],
+            ty: sect::Type::StrTab,
+            flags: 0,
+            virt_addr: 0,
+            file_addr: shstrtab_offset,
+            file_size: shstrtab.0.len(),
+            link_idx: 0,
+            info: 0,
+            align: 1,
+            entry_size: 0,
+        },
+    ];
+    if !rela_text.is_empty() {
+        sections.push(sect::Header {
+            name_offset: names[4],
+            ty: sect::Type::RelAdd,
+            flags: sect::flag::INFO_LINK,
+            virt_addr: 0,
+            file_addr: rela_text_offset,
+            file_size: rela_text.len(),
+            link_idx: SYMTAB_IDX,
+            info: TEXT_IDX.into(),
+            align: 8,
+            entry_size: rela::Entry::SIZE_64,
+        });
+    }
+    if !note.is_empty() {
+        sections.push(sect::Header {
+            name_offset: names[5],
+            ty: sect::Type::Note,
+            flags: sect::flag::ALLOC,
+            virt_addr: 0,
+            file_addr: note_offset,
+            file_size: note.len(),
+            link_idx: 0,
+            info: 0,
+            align: 8,
+            entry_size: 0,
+        });
+    }
+    let sect_count = 1 + sections.len();
+
+    let mut data = vec![0u8; sect_offset + sect::Header::SIZE_64 * sect_count];
+    let header = Header {
+        entry: 0,
+        ty: Type::Reloc,
+        prog_count: 0,
+        sect_count: sect_count.try_into().unwrap(),
+        prog_table_addr: 0,
+        sect_table_addr: sect_offset as u64,
+        sect_names_idx: SHSTRTAB_IDX,
+    };
+    header.copy_data_64le(&mut data[0..Header::SIZE_64]);
+    data[text_offset..text_offset + text.len()].copy_from_slice(&text);
+    data[symtab_offset..strtab_offset].copy_from_slice(&symtab);
+    data[strtab_offset..shstrtab_offset].copy_from_slice(&strtab.0);
+    data[shstrtab_offset..shstrtab_offset + shstrtab.0.len()].copy_from_slice(&shstrtab.0);
+    data[rela_text_offset..rela_text_offset + rela_text.len()].copy_from_slice(&rela_text);
+    data[note_offset..sect_offset].copy_from_slice(&note);
+    for (i, sect) in sections.iter().enumerate() {
+        let start = sect_offset + (1 + i) * sect::Header::SIZE_64;
+        sect.copy_data_64le(&mut data[start..start + sect::Header::SIZE_64]);
+    }
+
+    file.write_all(&data)
+}
+
 pub struct Elf {
     entry: u64,
     ty: Type,
@@ -235,3 +541,128 @@ pub fn write_64le_to<W: Write>(self, file: &mut W) -> Result<(), IoError> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        obj::{RelocKind, Symbol},
+        sparsebin::Aligned,
+    };
+
+    #[test]
+    fn it_writes_symbols() {
+        let mut text = SparseBin::new();
+        text.write_u32(Aligned::new(0).unwrap(), 0x14000000);
+        let symbol = |name: &str, value, global| Symbol {
+            name: name.to_string(),
+            value,
+            global,
+        };
+        let mut obj = Object {
+            text,
+            text_size: 4,
+            symbols: vec![
+                symbol("start", SymbolValue::Text(0), true),
+                symbol("SIZE", SymbolValue::Absolute(-8), false),
+            ],
+            relocations: Vec::new(),
+            gnu_property: GnuProperty::default(),
+        };
+        let mut data = Vec::new();
+        write_object_64le(&mut obj, &mut data).unwrap();
+
+        assert_eq!(data[0..4], MAGIC);
+        assert_eq!(data[0x40..0x44], 0x14000000u32.to_le_bytes());
+        // the local symbol is sorted first, after the null symbol
+        let symtab = 0x48;
+        let entry = |i: usize| &data[symtab + i * sym::Entry::SIZE_64..][..sym::Entry::SIZE_64];
+        assert_eq!(entry(1)[0x4], sym::bind::LOCAL << 4);
+        assert_eq!(entry(1)[0x6..0x8], sym::ABS_IDX.to_le_bytes());
+        assert_eq!(entry(1)[0x8..0x10], (-8i64).to_le_bytes());
+        assert_eq!(entry(2)[0x4], sym::bind::GLOBAL << 4);
+        assert_eq!(entry(2)[0x6..0x8], 1u16.to_le_bytes());
+    }
+
+    #[test]
+    fn it_writes_relocations() {
+        let mut text = SparseBin::new();
+        text.write_u32(Aligned::new(0).unwrap(), 0x90000000);
+        text.write_u32(Aligned::new(4).unwrap(), 0x94000000);
+        let mut obj = Object {
+            text,
+            text_size: 8,
+            symbols: vec![Symbol {
+                name: "ext".to_string(),
+                value: SymbolValue::Undefined,
+                global: true,
+            }],
+            relocations: vec![
+                Relocation {
+                    offset: 0,
+                    kind: RelocKind::AdrPrelPgHi21,
+                    symbol: None,
+                    addend: 8,
+                },
+                Relocation {
+                    offset: 4,
+                    kind: RelocKind::Call26,
+                    symbol: Some("ext".to_string()),
+                    addend: 0,
+                },
+            ],
+            gnu_property: GnuProperty::default(),
+        };
+        let mut data = Vec::new();
+        write_object_64le(&mut obj, &mut data).unwrap();
+
+        // `.rela.text` is the last of 6 sections
+        assert_eq!(data[0x3C..0x3E], 6u16.to_le_bytes());
+        let sect_offset = u64::from_le_bytes(data[0x28..0x30].try_into().unwrap()) as usize;
+        let rela = &data[sect_offset + 5 * sect::Header::SIZE_64..][..sect::Header::SIZE_64];
+        assert_eq!(rela[0x4..0x8], (sect::Type::RelAdd as u32).to_le_bytes());
+        assert_eq!(rela[0x28..0x30], [2, 0, 0, 0, 1, 0, 0, 0]);
+        let rela_offset = u64::from_le_bytes(rela[0x18..0x20].try_into().unwrap()) as usize;
+        let entry =
+            |i: usize| &data[rela_offset + i * rela::Entry::SIZE_64..][..rela::Entry::SIZE_64];
+        // the `.text` section symbol is 1, followed by `ext`
+        assert_eq!(entry(0)[0x8..0x10], (1u64 << 32 | 275).to_le_bytes());
+        assert_eq!(entry(0)[0x10..0x18], 8i64.to_le_bytes());
+        assert_eq!(entry(1)[0x0..0x8], 4u64.to_le_bytes());
+        assert_eq!(entry(1)[0x8..0x10], (2u64 << 32 | 283).to_le_bytes());
+    }
+
+    #[test]
+    fn it_writes_the_gnu_property_note() {
+        let mut text = SparseBin::new();
+        text.write_u32(Aligned::new(0).unwrap(), 0xd503245f);
+        let mut obj = Object {
+            text,
+            text_size: 4,
+            symbols: Vec::new(),
+            relocations: Vec::new(),
+            gnu_property: GnuProperty {
+                bti: true,
+                pac: true,
+            },
+        };
+        let mut data = Vec::new();
+        write_object_64le(&mut obj, &mut data).unwrap();
+
+        // `.note.gnu.property` is the last of 6 sections, without `.rela.text`
+        assert_eq!(data[0x3C..0x3E], 6u16.to_le_bytes());
+        let sect_offset = u64::from_le_bytes(data[0x28..0x30].try_into().unwrap()) as usize;
+        let note = &data[sect_offset + 5 * sect::Header::SIZE_64..][..sect::Header::SIZE_64];
+        assert_eq!(note[0x4..0x8], (sect::Type::Note as u32).to_le_bytes());
+        assert_eq!(note[0x30..0x38], 8u64.to_le_bytes());
+        let note_offset = u64::from_le_bytes(note[0x18..0x20].try_into().unwrap()) as usize;
+        assert_eq!(note_offset % 8, 0);
+        assert_eq!(
+            data[note_offset..note_offset + note::SIZE_64],
+            [
+                4, 0, 0, 0, 16, 0, 0, 0, 5, 0, 0, 0, b'G', b'N', b'U', 0, //
+                0, 0, 0, 0xc0, 4, 0, 0, 0, 3, 0, 0, 0, 0, 0, 0, 0,
+            ]
+        );
+    }
+}
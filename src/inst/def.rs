@@ -6,32 +6,1179 @@ pub enum Variant {
     ExtendedRegister,
     Immediate,
     Condition,
+    /// register operand, or a register offset address
+    Register,
+    /// pc relative address
+    Literal,
+    /// FP register moved to a general-purpose register
+    ToGpr,
+    /// general-purpose register moved to an FP register
+    FromGpr,
+    /// SIMD vector registers
+    Vector,
+    /// one lane of a vector register
+    Element,
 }
 
 def_instrs! {
     UDF () (B(0b10));
+    // a negative immediate is the other of ADD and SUB, only ADD takes `:lo12:`, like llvm
     ADD Immediate
         (Gpr() Gpr() Imm() Opt(Shift()))
-        (Sf():0 B(0b00100010) ShiftConst(LSL, 12):3 Gpr(AllowSp):1 UImm(12):2 Gpr(AllowSp):0),
+        (Sf():0 AddSubOp():2 B(0b0100010) ShiftConst(LSL, 12):3 Lo12(AddSubImm()):2 Gpr(AllowSp, Size = Sf):1 Gpr(AllowSp):0),
+        // without a shift or extend both register forms match, the emitter picks this one
+        // unless an operand is `sp`, which only ExtendedRegister takes
         ShiftedRegister
-        // docs say Shift is optional, but that conflicts with ExtendedRegister,
-        // and need to favor that since it allows special registers in the common `ADD Gpr, Gpr, Gpr`
-        (Gpr() Gpr() Gpr() Shift())
-        (Sf():0 B(0b0001011) Shift(Kind):3 B(0b0) Gpr():2 Shift(Amount(6)):3 Gpr():1 Gpr():0),
+        (Gpr() Gpr() Gpr() Opt(Shift()))
+        (Sf():0 B(0b0001011) Shift(Kind):3 B(0b0) Gpr(AllowZr, Size = Sf):2 Shift(Amount, Size = Sf):3 Gpr(AllowZr, Size = Sf):1 Gpr(AllowZr):0),
         ExtendedRegister
         (Gpr() Gpr() Gpr() Opt(Extend()))
-        (Sf():2 B(0b0001011001) Gpr(AllowZr):2 Extend(Kind):3 Extend(Shift):3 Gpr(AllowSp):1 Gpr(AllowSp):0);
+        (Sf():0 B(0b0001011001) Gpr(AllowZr):2 Extend(Kind, Rm = 2):3 Extend(Shift):3 Gpr(AllowSp, Size = Sf):1 Gpr(AllowSp):0),
+        Vector [SIMD]
+        (Vr() Vr() Vr())
+        (B(0b0) Q():0 B(0b001110) VecSize(Max = 8):0 B(0b1) Vr(Same):2 B(0b100001) Vr(Same):1 Vr():0);
 
-    ADDS ExtendedRegister
+    ADDS Immediate
+        (Gpr() Gpr() Imm() Opt(Shift()))
+        (Sf():0 AddSubOp():2 B(0b1100010) ShiftConst(LSL, 12):3 AddSubImm():2 Gpr(AllowSp, Size = Sf):1 Gpr(AllowZr):0),
+        ShiftedRegister
+        (Gpr() Gpr() Gpr() Opt(Shift()))
+        (Sf():0 B(0b0101011) Shift(Kind):3 B(0b0) Gpr(AllowZr, Size = Sf):2 Shift(Amount, Size = Sf):3 Gpr(AllowZr, Size = Sf):1 Gpr(AllowZr):0),
+        ExtendedRegister
+        (Gpr() Gpr() Gpr() Opt(Extend()))
+        (Sf():0 B(0b0101011001) Gpr(AllowZr):2 Extend(Kind, Rm = 2):3 Extend(Shift):3 Gpr(AllowSp, Size = Sf):1 Gpr(AllowZr):0);
+
+    SUB Immediate
+        (Gpr() Gpr() Imm() Opt(Shift()))
+        (Sf():0 AddSubOp(Sub):2 B(0b0100010) ShiftConst(LSL, 12):3 AddSubImm():2 Gpr(AllowSp, Size = Sf):1 Gpr(AllowSp):0),
+        ShiftedRegister
+        (Gpr() Gpr() Gpr() Opt(Shift()))
+        (Sf():0 B(0b1001011) Shift(Kind):3 B(0b0) Gpr(AllowZr, Size = Sf):2 Shift(Amount, Size = Sf):3 Gpr(AllowZr, Size = Sf):1 Gpr(AllowZr):0),
+        ExtendedRegister
+        (Gpr() Gpr() Gpr() Opt(Extend()))
+        (Sf():0 B(0b1001011001) Gpr(AllowZr):2 Extend(Kind, Rm = 2):3 Extend(Shift):3 Gpr(AllowSp, Size = Sf):1 Gpr(AllowSp):0),
+        Vector [SIMD]
+        (Vr() Vr() Vr())
+        (B(0b0) Q():0 B(0b101110) VecSize(Max = 8):0 B(0b1) Vr(Same):2 B(0b100001) Vr(Same):1 Vr():0);
+
+    SUBS Immediate
+        (Gpr() Gpr() Imm() Opt(Shift()))
+        (Sf():0 AddSubOp(Sub):2 B(0b1100010) ShiftConst(LSL, 12):3 AddSubImm():2 Gpr(AllowSp, Size = Sf):1 Gpr(AllowZr):0),
+        ShiftedRegister
+        (Gpr() Gpr() Gpr() Opt(Shift()))
+        (Sf():0 B(0b1101011) Shift(Kind):3 B(0b0) Gpr(AllowZr, Size = Sf):2 Shift(Amount, Size = Sf):3 Gpr(AllowZr, Size = Sf):1 Gpr(AllowZr):0),
+        ExtendedRegister
         (Gpr() Gpr() Gpr() Opt(Extend()))
-        (Sf():0 B(0b0101011001) Gpr():2 Extend(Kind):3 Extend(Shift):3 Gpr():1 Gpr():0);
+        (Sf():0 B(0b1101011001) Gpr(AllowZr):2 Extend(Kind, Rm = 2):3 Extend(Shift):3 Gpr(AllowSp, Size = Sf):1 Gpr(AllowZr):0);
+
+    // aliases of SUBS and ADDS that only set the flags, the destination is ZR
+    CMP Immediate
+        (Gpr() Imm() Opt(Shift()))
+        (Sf():0 AddSubOp(Sub):1 B(0b1100010) ShiftConst(LSL, 12):2 AddSubImm():1 Gpr(AllowSp):0 B(0b11111)),
+        ShiftedRegister
+        (Gpr() Gpr() Opt(Shift()))
+        (Sf():0 B(0b1101011) Shift(Kind):2 B(0b0) Gpr(AllowZr, Size = Sf):1 Shift(Amount, Size = Sf):2 Gpr(AllowZr):0 B(0b11111)),
+        ExtendedRegister
+        (Gpr() Gpr() Opt(Extend()))
+        (Sf():0 B(0b1101011001) Gpr(AllowZr):1 Extend(Kind, Rm = 1):2 Extend(Shift):2 Gpr(AllowSp):0 B(0b11111));
+
+    CMN Immediate
+        (Gpr() Imm() Opt(Shift()))
+        (Sf():0 AddSubOp():1 B(0b1100010) ShiftConst(LSL, 12):2 AddSubImm():1 Gpr(AllowSp):0 B(0b11111)),
+        ShiftedRegister
+        (Gpr() Gpr() Opt(Shift()))
+        (Sf():0 B(0b0101011) Shift(Kind):2 B(0b0) Gpr(AllowZr, Size = Sf):1 Shift(Amount, Size = Sf):2 Gpr(AllowZr):0 B(0b11111)),
+        ExtendedRegister
+        (Gpr() Gpr() Opt(Extend()))
+        (Sf():0 B(0b0101011001) Gpr(AllowZr):1 Extend(Kind, Rm = 1):2 Extend(Shift):2 Gpr(AllowSp):0 B(0b11111));
+
+    // aliases of SUB and SUBS from ZR
+    NEG (Gpr() Gpr() Opt(Shift()))
+        (Sf():0 B(0b1001011) Shift(Kind):2 B(0b0) Gpr(AllowZr, Size = Sf):1 Shift(Amount, Size = Sf):2 B(0b11111) Gpr(AllowZr):0);
+    NEGS (Gpr() Gpr() Opt(Shift()))
+        (Sf():0 B(0b1101011) Shift(Kind):2 B(0b0) Gpr(AllowZr, Size = Sf):1 Shift(Amount, Size = Sf):2 B(0b11111) Gpr(AllowZr):0);
+
+    // N selects the inverted second operand, the immediate form inverts the mask instead
+    AND Immediate
+        (Gpr() Gpr() Imm())
+        (Sf():0 B(0b00100100) LogicalImm(Size = Sf):2 Gpr(AllowZr, Size = Sf):1 Gpr(AllowSp):0),
+        ShiftedRegister
+        (Gpr() Gpr() Gpr() Opt(Shift()))
+        (Sf():0 B(0b0001010) Shift(Kind, AllowRor):3 B(0b0) Gpr(AllowZr, Size = Sf):2 Shift(Amount, Size = Sf):3 Gpr(AllowZr, Size = Sf):1 Gpr(AllowZr):0);
+    BIC Immediate
+        (Gpr() Gpr() Imm())
+        (Sf():0 B(0b00100100) LogicalImm(Not, Size = Sf):2 Gpr(AllowZr, Size = Sf):1 Gpr(AllowSp):0),
+        ShiftedRegister
+        (Gpr() Gpr() Gpr() Opt(Shift()))
+        (Sf():0 B(0b0001010) Shift(Kind, AllowRor):3 B(0b1) Gpr(AllowZr, Size = Sf):2 Shift(Amount, Size = Sf):3 Gpr(AllowZr, Size = Sf):1 Gpr(AllowZr):0);
+    ORR Immediate
+        (Gpr() Gpr() Imm())
+        (Sf():0 B(0b01100100) LogicalImm(Size = Sf):2 Gpr(AllowZr, Size = Sf):1 Gpr(AllowSp):0),
+        ShiftedRegister
+        (Gpr() Gpr() Gpr() Opt(Shift()))
+        (Sf():0 B(0b0101010) Shift(Kind, AllowRor):3 B(0b0) Gpr(AllowZr, Size = Sf):2 Shift(Amount, Size = Sf):3 Gpr(AllowZr, Size = Sf):1 Gpr(AllowZr):0),
+        Vector [SIMD]
+        (Vr() Vr() Vr())
+        (B(0b0) Q():0 B(0b001110101) Vr(Same):2 B(0b000111) Vr(Same):1 Vr(Size = 1):0);
+    ORN Immediate
+        (Gpr() Gpr() Imm())
+        (Sf():0 B(0b01100100) LogicalImm(Not, Size = Sf):2 Gpr(AllowZr, Size = Sf):1 Gpr(AllowSp):0),
+        ShiftedRegister
+        (Gpr() Gpr() Gpr() Opt(Shift()))
+        (Sf():0 B(0b0101010) Shift(Kind, AllowRor):3 B(0b1) Gpr(AllowZr, Size = Sf):2 Shift(Amount, Size = Sf):3 Gpr(AllowZr, Size = Sf):1 Gpr(AllowZr):0);
+    EOR Immediate
+        (Gpr() Gpr() Imm())
+        (Sf():0 B(0b10100100) LogicalImm(Size = Sf):2 Gpr(AllowZr, Size = Sf):1 Gpr(AllowSp):0),
+        ShiftedRegister
+        (Gpr() Gpr() Gpr() Opt(Shift()))
+        (Sf():0 B(0b1001010) Shift(Kind, AllowRor):3 B(0b0) Gpr(AllowZr, Size = Sf):2 Shift(Amount, Size = Sf):3 Gpr(AllowZr, Size = Sf):1 Gpr(AllowZr):0);
+    EON Immediate
+        (Gpr() Gpr() Imm())
+        (Sf():0 B(0b10100100) LogicalImm(Not, Size = Sf):2 Gpr(AllowZr, Size = Sf):1 Gpr(AllowSp):0),
+        ShiftedRegister
+        (Gpr() Gpr() Gpr() Opt(Shift()))
+        (Sf():0 B(0b1001010) Shift(Kind, AllowRor):3 B(0b1) Gpr(AllowZr, Size = Sf):2 Shift(Amount, Size = Sf):3 Gpr(AllowZr, Size = Sf):1 Gpr(AllowZr):0);
+    ANDS Immediate
+        (Gpr() Gpr() Imm())
+        (Sf():0 B(0b11100100) LogicalImm(Size = Sf):2 Gpr(AllowZr, Size = Sf):1 Gpr(AllowZr):0),
+        ShiftedRegister
+        (Gpr() Gpr() Gpr() Opt(Shift()))
+        (Sf():0 B(0b1101010) Shift(Kind, AllowRor):3 B(0b0) Gpr(AllowZr, Size = Sf):2 Shift(Amount, Size = Sf):3 Gpr(AllowZr, Size = Sf):1 Gpr(AllowZr):0);
+    BICS Immediate
+        (Gpr() Gpr() Imm())
+        (Sf():0 B(0b11100100) LogicalImm(Not, Size = Sf):2 Gpr(AllowZr, Size = Sf):1 Gpr(AllowZr):0),
+        ShiftedRegister
+        (Gpr() Gpr() Gpr() Opt(Shift()))
+        (Sf():0 B(0b1101010) Shift(Kind, AllowRor):3 B(0b1) Gpr(AllowZr, Size = Sf):2 Shift(Amount, Size = Sf):3 Gpr(AllowZr, Size = Sf):1 Gpr(AllowZr):0);
+    // alias of ANDS that only sets the flags
+    TST Immediate
+        (Gpr() Imm())
+        (Sf():0 B(0b11100100) LogicalImm(Size = Sf):1 Gpr(AllowZr):0 B(0b11111)),
+        ShiftedRegister
+        (Gpr() Gpr() Opt(Shift()))
+        (Sf():0 B(0b1101010) Shift(Kind, AllowRor):2 B(0b0) Gpr(AllowZr, Size = Sf):1 Shift(Amount, Size = Sf):2 Gpr(AllowZr):0 B(0b11111));
+
+    // hw is the LSL amount divided by 16
+    MOVN (Gpr() Imm() Opt(Shift()))
+        (Sf():0 B(0b00100101) WideShift(Size = Sf):2 UImm(16):1 Gpr(AllowZr):0);
+    MOVZ (Gpr() Imm() Opt(Shift()))
+        (Sf():0 B(0b10100101) WideShift(Size = Sf):2 UImm(16):1 Gpr(AllowZr):0);
+    MOVK (Gpr() Imm() Opt(Shift()))
+        (Sf():0 B(0b11100101) WideShift(Size = Sf):2 UImm(16):1 Gpr(AllowZr):0);
+    // alias of whichever of MOVZ, MOVN or ORR with ZR can make the immediate
+    MOV Immediate
+        (Gpr() Imm())
+        (Sf():0 MovImm(Size = Sf):1 Gpr(AllowZr):0),
+        // aliases of INS, and of UMOV for an S or D lane
+        Element [SIMD]
+        (IdxVr() IdxVr())
+        (B(0b01101110000) Lane(Imm5, Same = 1):0 B(0b0) Lane(Imm4):1 B(0b1) Vr():1 Vr():0),
+        FromGpr [SIMD]
+        (IdxVr() Gpr())
+        (B(0b01001110000) Lane(Imm5, Gpr = 1):0 B(0b000111) Gpr(AllowZr):1 Vr():0),
+        ToGpr [SIMD]
+        (Gpr() IdxVr())
+        (B(0b0) Sf():0 B(0b001110000) Lane(Imm5, Mov = 0):1 B(0b001111) Vr():1 Gpr(AllowZr):0),
+        // alias of ORR with the same source twice
+        Vector [SIMD]
+        (Vr() Vr())
+        (B(0b0) Q():0 B(0b001110101) Vr(Same):1 B(0b000111) Vr(Same):1 Vr(Size = 1):0);
+    // any constant, expands into MOVZ or MOVN followed by MOVK
+    LDI (Gpr() Imm())
+        (Expand(ldi));
+
+    // exceptions to EL1, EL2 and EL3, the immediate is passed to the handler
+    SVC (Imm())
+        (B(0b11010100000) UImm(16):0 B(0b00001));
+    HVC (Imm())
+        (B(0b11010100000) UImm(16):0 B(0b00010));
+    SMC (Imm())
+        (B(0b11010100000) UImm(16):0 B(0b00011));
+    BRK (Imm())
+        (B(0b11010100001) UImm(16):0 B(0b00000));
+    HLT (Imm())
+        (B(0b11010100010) UImm(16):0 B(0b00000));
+    // hint space, executes as NOP if the hint is not implemented
+    HINT (Imm())
+        (B(0b11010101000000110010) UImm(7):0 B(0b11111));
+    NOP ()
+        (B(0b11010101000000110010) B(0b000000011111));
+    YIELD ()
+        (B(0b11010101000000110010) B(0b000000111111));
+    WFE ()
+        (B(0b11010101000000110010) B(0b000001011111));
+    WFI ()
+        (B(0b11010101000000110010) B(0b000001111111));
+    SEV ()
+        (B(0b11010101000000110010) B(0b000010011111));
+    SEVL ()
+        (B(0b11010101000000110010) B(0b000010111111));
+    // pointer authentication of the return address in LR, with SP as the modifier,
+    // these and `BTI` are in the `HINT` space, NOPs for cores without PAUTH or BTI,
+    // so they assemble for any target
+    PACIASP ()
+        (B(0b11010101000000110010) B(0b001100111111));
+    PACIBSP ()
+        (B(0b11010101000000110010) B(0b001101111111));
+    AUTIASP ()
+        (B(0b11010101000000110010) B(0b001110111111));
+    AUTIBSP ()
+        (B(0b11010101000000110010) B(0b001111111111));
+    // strips the authentication code from LR
+    XPACLRI ()
+        (B(0b11010101000000110010) B(0b000011111111));
+    // landing pad of an indirect branch, `c` for BLR, `j` for BR or `jc` for both
+    BTI (Opt(BtiTarget()))
+        (B(0b11010101000000110010) B(0b0100) BtiTarget():0 B(0b011111));
+    // barriers take an option name or its 4-bit value
+    DMB Default
+        (Barrier())
+        (B(0b11010101000000110011) Barrier():0 B(0b10111111)),
+        Immediate
+        (Imm())
+        (B(0b11010101000000110011) UImm(4):0 B(0b10111111));
+    DSB Default
+        (Barrier())
+        (B(0b11010101000000110011) Barrier():0 B(0b10011111)),
+        Immediate
+        (Imm())
+        (B(0b11010101000000110011) UImm(4):0 B(0b10011111));
+    ISB Default
+        (Opt(Barrier()))
+        (B(0b11010101000000110011) Barrier(Isb):0 B(0b11011111)),
+        Immediate
+        (Imm())
+        (B(0b11010101000000110011) UImm(4):0 B(0b11011111));
+    // read or write a system register
+    MRS (Gpr() SysReg())
+        (B(0b110101010011) SysReg():1 Gpr(AllowZr, Size = 8):0);
+    MSR (SysReg() Gpr())
+        (B(0b110101010001) SysReg():0 Gpr(AllowZr, Size = 8):1);
+
+    // bitfield moves, N is the same as sf
+    SBFM (Gpr() Gpr() Imm() Imm())
+        (Sf():0 B(0b00100110) Sf():0 BitNum(Size = Sf):2 BitNum(Size = Sf):3 Gpr(AllowZr, Size = Sf):1 Gpr(AllowZr):0);
+    BFM (Gpr() Gpr() Imm() Imm())
+        (Sf():0 B(0b01100110) Sf():0 BitNum(Size = Sf):2 BitNum(Size = Sf):3 Gpr(AllowZr, Size = Sf):1 Gpr(AllowZr):0);
+    UBFM (Gpr() Gpr() Imm() Imm())
+        (Sf():0 B(0b10100110) Sf():0 BitNum(Size = Sf):2 BitNum(Size = Sf):3 Gpr(AllowZr, Size = Sf):1 Gpr(AllowZr):0);
+    // aliases of the bitfield moves, immr and imms are computed from the shift, or lsb and width
+    LSL Immediate
+        (Gpr() Gpr() Imm())
+        (Sf():0 B(0b10100110) Sf():0 BitfieldShift(Left, Size = Sf):2 Gpr(AllowZr, Size = Sf):1 Gpr(AllowZr):0);
+    LSR Immediate
+        (Gpr() Gpr() Imm())
+        (Sf():0 B(0b10100110) Sf():0 BitfieldShift(Right, Size = Sf):2 Gpr(AllowZr, Size = Sf):1 Gpr(AllowZr):0);
+    ASR Immediate
+        (Gpr() Gpr() Imm())
+        (Sf():0 B(0b00100110) Sf():0 BitfieldShift(Right, Size = Sf):2 Gpr(AllowZr, Size = Sf):1 Gpr(AllowZr):0);
+    SBFX (Gpr() Gpr() Imm() Imm())
+        (Sf():0 B(0b00100110) Sf():0 Bitfield(Extract, Width = 3, Size = Sf):2 Gpr(AllowZr, Size = Sf):1 Gpr(AllowZr):0);
+    BFXIL (Gpr() Gpr() Imm() Imm())
+        (Sf():0 B(0b01100110) Sf():0 Bitfield(Extract, Width = 3, Size = Sf):2 Gpr(AllowZr, Size = Sf):1 Gpr(AllowZr):0);
+    UBFX (Gpr() Gpr() Imm() Imm())
+        (Sf():0 B(0b10100110) Sf():0 Bitfield(Extract, Width = 3, Size = Sf):2 Gpr(AllowZr, Size = Sf):1 Gpr(AllowZr):0);
+    SBFIZ (Gpr() Gpr() Imm() Imm())
+        (Sf():0 B(0b00100110) Sf():0 Bitfield(Insert, Width = 3, Size = Sf):2 Gpr(AllowZr, Size = Sf):1 Gpr(AllowZr):0);
+    BFI (Gpr() Gpr() Imm() Imm())
+        (Sf():0 B(0b01100110) Sf():0 Bitfield(Insert, Width = 3, Size = Sf):2 Gpr(AllowZr, Size = Sf):1 Gpr(AllowZr):0);
+    UBFIZ (Gpr() Gpr() Imm() Imm())
+        (Sf():0 B(0b10100110) Sf():0 Bitfield(Insert, Width = 3, Size = Sf):2 Gpr(AllowZr, Size = Sf):1 Gpr(AllowZr):0);
+    // zero extend a W register, writing it also clears the upper half of the X register
+    UXTB (Gpr() Gpr())
+        (B(0b0101001100) B(0b000000) B(0b000111) Gpr(AllowZr, Size = 4):1 Gpr(AllowZr):0);
+    UXTH (Gpr() Gpr())
+        (B(0b0101001100) B(0b000000) B(0b001111) Gpr(AllowZr, Size = 4):1 Gpr(AllowZr):0);
+    // sign extend part of a W register to a W or X register
+    SXTB (Gpr() Gpr())
+        (Sf():0 B(0b00100110) Sf():0 B(0b000000) B(0b000111) Gpr(AllowZr, Size = 4):1 Gpr(AllowZr):0);
+    SXTH (Gpr() Gpr())
+        (Sf():0 B(0b00100110) Sf():0 B(0b000000) B(0b001111) Gpr(AllowZr, Size = 4):1 Gpr(AllowZr):0);
+    SXTW (Gpr() Gpr())
+        (B(0b1001001101) B(0b000000) B(0b011111) Gpr(AllowZr, Size = 4):1 Gpr(AllowZr, Size = 8):0);
+    // low bits of Rn:Rm from the lsb
+    EXTR (Gpr() Gpr() Gpr() Imm())
+        (Sf():0 B(0b00100111) Sf():0 B(0b0) Gpr(AllowZr, Size = Sf):2 BitNum(Size = Sf):3 Gpr(AllowZr, Size = Sf):1 Gpr(AllowZr):0);
+    // alias of EXTR with Rn as both sources
+    ROR Immediate
+        (Gpr() Gpr() Imm())
+        (Sf():0 B(0b00100111) Sf():0 B(0b0) Gpr(AllowZr, Size = Sf):1 BitNum(Size = Sf):2 Gpr(AllowZr, Size = Sf):1 Gpr(AllowZr):0);
+
+    // Rd = Ra + Rn * Rm, or Ra - Rn * Rm
+    MADD (Gpr() Gpr() Gpr() Gpr())
+        (Sf():0 B(0b0011011000) Gpr(AllowZr, Size = Sf):2 B(0b0) Gpr(AllowZr, Size = Sf):3 Gpr(AllowZr, Size = Sf):1 Gpr(AllowZr):0);
+    MSUB (Gpr() Gpr() Gpr() Gpr())
+        (Sf():0 B(0b0011011000) Gpr(AllowZr, Size = Sf):2 B(0b1) Gpr(AllowZr, Size = Sf):3 Gpr(AllowZr, Size = Sf):1 Gpr(AllowZr):0);
+    // aliases with Ra = ZR
+    MUL Default
+        (Gpr() Gpr() Gpr())
+        (Sf():0 B(0b0011011000) Gpr(AllowZr, Size = Sf):2 B(0b0) B(0b11111) Gpr(AllowZr, Size = Sf):1 Gpr(AllowZr):0),
+        Vector [SIMD]
+        (Vr() Vr() Vr())
+        (B(0b0) Q():0 B(0b001110) VecSize(Max = 4):0 B(0b1) Vr(Same):2 B(0b100111) Vr(Same):1 Vr():0);
+    MNEG (Gpr() Gpr() Gpr())
+        (Sf():0 B(0b0011011000) Gpr(AllowZr, Size = Sf):2 B(0b1) B(0b11111) Gpr(AllowZr, Size = Sf):1 Gpr(AllowZr):0);
+    // long multiply of W registers, added to an X register
+    SMADDL (Gpr() Gpr() Gpr() Gpr())
+        (B(0b10011011001) Gpr(AllowZr, Size = 4):2 B(0b0) Gpr(AllowZr, Size = 8):3 Gpr(AllowZr, Size = 4):1 Gpr(AllowZr, Size = 8):0);
+    UMADDL (Gpr() Gpr() Gpr() Gpr())
+        (B(0b10011011101) Gpr(AllowZr, Size = 4):2 B(0b0) Gpr(AllowZr, Size = 8):3 Gpr(AllowZr, Size = 4):1 Gpr(AllowZr, Size = 8):0);
+    SMULL (Gpr() Gpr() Gpr())
+        (B(0b10011011001) Gpr(AllowZr, Size = 4):2 B(0b0) B(0b11111) Gpr(AllowZr, Size = 4):1 Gpr(AllowZr, Size = 8):0);
+    UMULL (Gpr() Gpr() Gpr())
+        (B(0b10011011101) Gpr(AllowZr, Size = 4):2 B(0b0) B(0b11111) Gpr(AllowZr, Size = 4):1 Gpr(AllowZr, Size = 8):0);
+    // high 64 bits of the 128-bit product
+    SMULH (Gpr() Gpr() Gpr())
+        (B(0b10011011010) Gpr(AllowZr, Size = 8):2 B(0b011111) Gpr(AllowZr, Size = 8):1 Gpr(AllowZr, Size = 8):0);
+    UMULH (Gpr() Gpr() Gpr())
+        (B(0b10011011110) Gpr(AllowZr, Size = 8):2 B(0b011111) Gpr(AllowZr, Size = 8):1 Gpr(AllowZr, Size = 8):0);
+    SDIV (Gpr() Gpr() Gpr())
+        (Sf():0 B(0b0011010110) Gpr(AllowZr, Size = Sf):2 B(0b000011) Gpr(AllowZr, Size = Sf):1 Gpr(AllowZr):0);
+    UDIV (Gpr() Gpr() Gpr())
+        (Sf():0 B(0b0011010110) Gpr(AllowZr, Size = Sf):2 B(0b000010) Gpr(AllowZr, Size = Sf):1 Gpr(AllowZr):0);
+
+    // Rm if the condition holds, otherwise Rn, incremented, inverted or negated
+    CSEL (Gpr() Gpr() Gpr() Cond())
+        (Sf():0 B(0b0011010100) Gpr(AllowZr, Size = Sf):2 Cond():3 B(0b00) Gpr(AllowZr, Size = Sf):1 Gpr(AllowZr):0);
+    CSINC (Gpr() Gpr() Gpr() Cond())
+        (Sf():0 B(0b0011010100) Gpr(AllowZr, Size = Sf):2 Cond():3 B(0b01) Gpr(AllowZr, Size = Sf):1 Gpr(AllowZr):0);
+    CSINV (Gpr() Gpr() Gpr() Cond())
+        (Sf():0 B(0b1011010100) Gpr(AllowZr, Size = Sf):2 Cond():3 B(0b00) Gpr(AllowZr, Size = Sf):1 Gpr(AllowZr):0);
+    CSNEG (Gpr() Gpr() Gpr() Cond())
+        (Sf():0 B(0b1011010100) Gpr(AllowZr, Size = Sf):2 Cond():3 B(0b01) Gpr(AllowZr, Size = Sf):1 Gpr(AllowZr):0);
+    // aliases of conditional select with the inverse condition, which can't be AL or NV
+    CSET (Gpr() Cond())
+        (Sf():0 B(0b0011010100) B(0b11111) Cond(Invert):1 B(0b01) B(0b11111) Gpr(AllowZr):0);
+    CSETM (Gpr() Cond())
+        (Sf():0 B(0b1011010100) B(0b11111) Cond(Invert):1 B(0b00) B(0b11111) Gpr(AllowZr):0);
+    CINC (Gpr() Gpr() Cond())
+        (Sf():0 B(0b0011010100) Gpr(AllowZr, Size = Sf):1 Cond(Invert):2 B(0b01) Gpr(AllowZr, Size = Sf):1 Gpr(AllowZr):0);
+    CINV (Gpr() Gpr() Cond())
+        (Sf():0 B(0b1011010100) Gpr(AllowZr, Size = Sf):1 Cond(Invert):2 B(0b00) Gpr(AllowZr, Size = Sf):1 Gpr(AllowZr):0);
+    CNEG (Gpr() Gpr() Cond())
+        (Sf():0 B(0b1011010100) Gpr(AllowZr, Size = Sf):1 Cond(Invert):2 B(0b01) Gpr(AllowZr, Size = Sf):1 Gpr(AllowZr):0);
+    // flags are compared if the condition holds, otherwise set to nzcv
+    CCMN Register
+        (Gpr() Gpr() Imm() Cond())
+        (Sf():0 B(0b0111010010) Gpr(AllowZr, Size = Sf):1 Cond():3 B(0b00) Gpr(AllowZr):0 B(0b0) UImm(4):2),
+        Immediate
+        (Gpr() Imm() Imm() Cond())
+        (Sf():0 B(0b0111010010) UImm(5):1 Cond():3 B(0b10) Gpr(AllowZr):0 B(0b0) UImm(4):2);
+    CCMP Register
+        (Gpr() Gpr() Imm() Cond())
+        (Sf():0 B(0b1111010010) Gpr(AllowZr, Size = Sf):1 Cond():3 B(0b00) Gpr(AllowZr):0 B(0b0) UImm(4):2),
+        Immediate
+        (Gpr() Imm() Imm() Cond())
+        (Sf():0 B(0b1111010010) UImm(5):1 Cond():3 B(0b10) Gpr(AllowZr):0 B(0b0) UImm(4):2);
+
+    // scalar floating point, ftype is the precision of Rd and the others have to match it
+    FADD Default [FP]
+        (Dpr() Dpr() Dpr())
+        (B(0b00011110) Ftype():0 B(0b1) Dpr(Size = Ftype):2 B(0b0010) B(0b10) Dpr(Size = Ftype):1 Dpr():0),
+        Vector [SIMD]
+        (Vr() Vr() Vr())
+        (B(0b0) Q():0 B(0b0011100) FpVecSize():0 B(0b1) Vr(Same):2 B(0b110101) Vr(Same):1 Vr():0);
+    FSUB [FP] (Dpr() Dpr() Dpr())
+        (B(0b00011110) Ftype():0 B(0b1) Dpr(Size = Ftype):2 B(0b0011) B(0b10) Dpr(Size = Ftype):1 Dpr():0);
+    FMUL Default [FP]
+        (Dpr() Dpr() Dpr())
+        (B(0b00011110) Ftype():0 B(0b1) Dpr(Size = Ftype):2 B(0b0000) B(0b10) Dpr(Size = Ftype):1 Dpr():0),
+        Vector [SIMD]
+        (Vr() Vr() Vr())
+        (B(0b0) Q():0 B(0b1011100) FpVecSize():0 B(0b1) Vr(Same):2 B(0b110111) Vr(Same):1 Vr():0),
+        Element [SIMD]
+        (Vr() Vr() IdxVr())
+        (B(0b0) Q():0 B(0b001111) FpElemSize():0 Lane(Low, Same = 0):2 Lane(Vr):2 B(0b1001) Lane(High):2 B(0b0) Vr(Same):1 Vr():0);
+    FDIV [FP] (Dpr() Dpr() Dpr())
+        (B(0b00011110) Ftype():0 B(0b1) Dpr(Size = Ftype):2 B(0b0001) B(0b10) Dpr(Size = Ftype):1 Dpr():0);
+    FABS [FP] (Dpr() Dpr())
+        (B(0b00011110) Ftype():0 B(0b1) B(0b000001) B(0b10000) Dpr(Size = Ftype):1 Dpr():0);
+    FNEG [FP] (Dpr() Dpr())
+        (B(0b00011110) Ftype():0 B(0b1) B(0b000010) B(0b10000) Dpr(Size = Ftype):1 Dpr():0);
+    FSQRT [FP] (Dpr() Dpr())
+        (B(0b00011110) Ftype():0 B(0b1) B(0b000011) B(0b10000) Dpr(Size = Ftype):1 Dpr():0);
+    // Rd = Ra + Rn * Rm, FNMADD and FNMSUB negate the result
+    FMADD [FP] (Dpr() Dpr() Dpr() Dpr())
+        (B(0b00011111) Ftype():0 B(0b0) Dpr(Size = Ftype):2 B(0b0) Dpr(Size = Ftype):3 Dpr(Size = Ftype):1 Dpr():0);
+    FMSUB [FP] (Dpr() Dpr() Dpr() Dpr())
+        (B(0b00011111) Ftype():0 B(0b0) Dpr(Size = Ftype):2 B(0b1) Dpr(Size = Ftype):3 Dpr(Size = Ftype):1 Dpr():0);
+    FNMADD [FP] (Dpr() Dpr() Dpr() Dpr())
+        (B(0b00011111) Ftype():0 B(0b1) Dpr(Size = Ftype):2 B(0b0) Dpr(Size = Ftype):3 Dpr(Size = Ftype):1 Dpr():0);
+    FNMSUB [FP] (Dpr() Dpr() Dpr() Dpr())
+        (B(0b00011111) Ftype():0 B(0b1) Dpr(Size = Ftype):2 B(0b1) Dpr(Size = Ftype):3 Dpr(Size = Ftype):1 Dpr():0);
+    // sets the flags, FCMPE also signals on a quiet NaN
+    FCMP Register [FP]
+        (Dpr() Dpr())
+        (B(0b00011110) Ftype():0 B(0b1) Dpr(Size = Ftype):1 B(0b001000) Dpr():0 B(0b00000)),
+        Immediate [FP]
+        (Dpr() FpImm())
+        (B(0b00011110) Ftype():0 B(0b1) FpImm(Zero):1 B(0b001000) Dpr():0 B(0b01000));
+    FCMPE Register [FP]
+        (Dpr() Dpr())
+        (B(0b00011110) Ftype():0 B(0b1) Dpr(Size = Ftype):1 B(0b001000) Dpr():0 B(0b10000)),
+        Immediate [FP]
+        (Dpr() FpImm())
+        (B(0b00011110) Ftype():0 B(0b1) FpImm(Zero):1 B(0b001000) Dpr():0 B(0b11000));
+    // opc is the precision of Rd, ftype of Rn
+    FCVT [FP] (Dpr() Dpr())
+        (B(0b00011110) Ftype(Convert):1 B(0b1) B(0b0001) Ftype(Convert):0 B(0b10000) Dpr():1 Dpr():0);
+    // to an integer, rounding to nearest with ties to even or away, towards -inf, +inf or zero
+    FCVTNS [FP] (Gpr() Dpr())
+        (Sf():0 B(0b0011110) Ftype():1 B(0b1) B(0b00) B(0b000) B(0b000000) Dpr():1 Gpr(AllowZr):0);
+    FCVTNU [FP] (Gpr() Dpr())
+        (Sf():0 B(0b0011110) Ftype():1 B(0b1) B(0b00) B(0b001) B(0b000000) Dpr():1 Gpr(AllowZr):0);
+    FCVTAS [FP] (Gpr() Dpr())
+        (Sf():0 B(0b0011110) Ftype():1 B(0b1) B(0b00) B(0b100) B(0b000000) Dpr():1 Gpr(AllowZr):0);
+    FCVTAU [FP] (Gpr() Dpr())
+        (Sf():0 B(0b0011110) Ftype():1 B(0b1) B(0b00) B(0b101) B(0b000000) Dpr():1 Gpr(AllowZr):0);
+    FCVTMS [FP] (Gpr() Dpr())
+        (Sf():0 B(0b0011110) Ftype():1 B(0b1) B(0b10) B(0b000) B(0b000000) Dpr():1 Gpr(AllowZr):0);
+    FCVTMU [FP] (Gpr() Dpr())
+        (Sf():0 B(0b0011110) Ftype():1 B(0b1) B(0b10) B(0b001) B(0b000000) Dpr():1 Gpr(AllowZr):0);
+    FCVTPS [FP] (Gpr() Dpr())
+        (Sf():0 B(0b0011110) Ftype():1 B(0b1) B(0b01) B(0b000) B(0b000000) Dpr():1 Gpr(AllowZr):0);
+    FCVTPU [FP] (Gpr() Dpr())
+        (Sf():0 B(0b0011110) Ftype():1 B(0b1) B(0b01) B(0b001) B(0b000000) Dpr():1 Gpr(AllowZr):0);
+    FCVTZS [FP] (Gpr() Dpr())
+        (Sf():0 B(0b0011110) Ftype():1 B(0b1) B(0b11) B(0b000) B(0b000000) Dpr():1 Gpr(AllowZr):0);
+    FCVTZU [FP] (Gpr() Dpr())
+        (Sf():0 B(0b0011110) Ftype():1 B(0b1) B(0b11) B(0b001) B(0b000000) Dpr():1 Gpr(AllowZr):0);
+    // from a signed or unsigned integer
+    SCVTF [FP] (Dpr() Gpr())
+        (Sf():1 B(0b0011110) Ftype():0 B(0b1) B(0b00) B(0b010) B(0b000000) Gpr(AllowZr):1 Dpr():0);
+    UCVTF [FP] (Dpr() Gpr())
+        (Sf():1 B(0b0011110) Ftype():0 B(0b1) B(0b00) B(0b011) B(0b000000) Gpr(AllowZr):1 Dpr():0);
+    // bits are copied unchanged between registers, the immediate is `imm8` except for `#0.0`,
+    // which is `fmov d0, xzr` like llvm
+    FMOV Register [FP]
+        (Dpr() Dpr())
+        (B(0b00011110) Ftype():0 B(0b1) B(0b000000) B(0b10000) Dpr(Size = Ftype):1 Dpr():0),
+        ToGpr [FP]
+        (Gpr() Dpr())
+        (Sf():0 B(0b0011110) Ftype(Gpr = 0):1 B(0b1) B(0b00) B(0b110) B(0b000000) Dpr():1 Gpr(AllowZr):0),
+        FromGpr [FP]
+        (Dpr() Gpr())
+        (Sf():1 B(0b0011110) Ftype(Gpr = 1):0 B(0b1) B(0b00) B(0b111) B(0b000000) Gpr(AllowZr):1 Dpr():0),
+        Immediate [FP]
+        (Dpr() FpImm())
+        (FpImm(Sf, Dpr = 0):1 B(0b0011110) Ftype():0 B(0b1) FpImm(Fmov):1 Dpr():0);
+
+    // SIMD, `Q` selects a 128-bit vector, the other operands have the arrangement of Rd,
+    // by element the index of the lane of Rm is `H:L` for S or `H` for D
+    FMLA Vector [SIMD]
+        (Vr() Vr() Vr())
+        (B(0b0) Q():0 B(0b0011100) FpVecSize():0 B(0b1) Vr(Same):2 B(0b110011) Vr(Same):1 Vr():0),
+        Element [SIMD]
+        (Vr() Vr() IdxVr())
+        (B(0b0) Q():0 B(0b001111) FpElemSize():0 Lane(Low, Same = 0):2 Lane(Vr):2 B(0b0001) Lane(High):2 B(0b0) Vr(Same):1 Vr():0);
+    // interleave, deinterleave or transpose the elements of two vectors
+    ZIP1 [SIMD] (Vr() Vr() Vr())
+        (B(0b0) Q():0 B(0b001110) VecSize(Max = 8):0 B(0b0) Vr(Same):2 B(0b0) B(0b011) B(0b10) Vr(Same):1 Vr():0);
+    ZIP2 [SIMD] (Vr() Vr() Vr())
+        (B(0b0) Q():0 B(0b001110) VecSize(Max = 8):0 B(0b0) Vr(Same):2 B(0b0) B(0b111) B(0b10) Vr(Same):1 Vr():0);
+    UZP1 [SIMD] (Vr() Vr() Vr())
+        (B(0b0) Q():0 B(0b001110) VecSize(Max = 8):0 B(0b0) Vr(Same):2 B(0b0) B(0b001) B(0b10) Vr(Same):1 Vr():0);
+    UZP2 [SIMD] (Vr() Vr() Vr())
+        (B(0b0) Q():0 B(0b001110) VecSize(Max = 8):0 B(0b0) Vr(Same):2 B(0b0) B(0b101) B(0b10) Vr(Same):1 Vr():0);
+    TRN1 [SIMD] (Vr() Vr() Vr())
+        (B(0b0) Q():0 B(0b001110) VecSize(Max = 8):0 B(0b0) Vr(Same):2 B(0b0) B(0b010) B(0b10) Vr(Same):1 Vr():0);
+    TRN2 [SIMD] (Vr() Vr() Vr())
+        (B(0b0) Q():0 B(0b001110) VecSize(Max = 8):0 B(0b0) Vr(Same):2 B(0b0) B(0b110) B(0b10) Vr(Same):1 Vr():0);
+    // bytes of Rd are looked up in a table of 16b registers, size is 0 so Rd has to be 8b or 16b
+    TBL [SIMD] (Vr() VrList() Vr())
+        (B(0b0) Q():0 B(0b001110) VecSize(Max = 1):0 B(0b0) Vr(Same):2 B(0b0) VrList(TableLen):1 B(0b000) Vr():1 Vr():0);
+    // imm5 is the lane size and index, lane 0 when duplicating a general register
+    DUP Element [SIMD]
+        (Vr() IdxVr())
+        (B(0b0) Q():0 B(0b001110000) Lane(Imm5, Same = 0):1 B(0b000001) Vr():1 Vr():0),
+        FromGpr [SIMD]
+        (Vr() Gpr())
+        (B(0b0) Q():0 B(0b001110000) Lane(Imm5, Gpr = 1):0 B(0b000011) Gpr(AllowZr):1 Vr():0);
+    INS Element [SIMD]
+        (IdxVr() IdxVr())
+        (B(0b01101110000) Lane(Imm5, Same = 1):0 B(0b0) Lane(Imm4):1 B(0b1) Vr():1 Vr():0),
+        FromGpr [SIMD]
+        (IdxVr() Gpr())
+        (B(0b01001110000) Lane(Imm5, Gpr = 1):0 B(0b000111) Gpr(AllowZr):1 Vr():0);
+    // Q is set for a D lane, which is moved to an X register
+    UMOV [SIMD] (Gpr() IdxVr())
+        (B(0b0) Sf():0 B(0b001110000) Lane(Imm5, Gpr = 0):1 B(0b001111) Vr():1 Gpr(AllowZr):0);
+    // multiple structures with one element, no offset or a post-index by the size of the list
+    // or by a register
+    LD1 Default [SIMD]
+        (VrList() AddrImm())
+        (B(0b0) Q():0 B(0b001100) Addr(ListMode):1 B(0b1) B(0b0) Addr(ListOffset, List = 0):1 VrList(Opcode):0 VecSize(Max = 8):0 Addr(Base):1 Vr():0),
+        Register [SIMD]
+        (VrList() AddrPostReg())
+        (B(0b0) Q():0 B(0b001100) B(0b1) B(0b1) B(0b0) Addr(Index):1 VrList(Opcode):0 VecSize(Max = 8):0 Addr(Base):1 Vr():0);
+    ST1 Default [SIMD]
+        (VrList() AddrImm())
+        (B(0b0) Q():0 B(0b001100) Addr(ListMode):1 B(0b0) B(0b0) Addr(ListOffset, List = 0):1 VrList(Opcode):0 VecSize(Max = 8):0 Addr(Base):1 Vr():0),
+        Register [SIMD]
+        (VrList() AddrPostReg())
+        (B(0b0) Q():0 B(0b001100) B(0b1) B(0b0) B(0b0) Addr(Index):1 VrList(Opcode):0 VecSize(Max = 8):0 Addr(Base):1 Vr():0);
 
     B   Default
         (Label())
-        (B(0b000101) Label(SImm(26, Align = 2)):0),
+        (B(0b000101) Label(SImm(26, Align = 2), Reloc = Jump26):0),
         Condition
         (Cond() Label())
-        (B(0b01010100) Label(SImm(19, Align = 2)):1 B(0b0) Cond():0);
+        (B(0b01010100) Label(SImm(19, Align = 2), Reloc = CondBr19):1 B(0b0) Cond():0);
+    BL  (Label())
+        (B(0b100101) Label(SImm(26, Align = 2), Reloc = Call26):0);
+    BR  (Gpr())
+        (B(0b1101011000011111000000) Gpr(AllowZr, Size = 8):0 B(0b00000));
+    BLR (Gpr())
+        (B(0b1101011000111111000000) Gpr(AllowZr, Size = 8):0 B(0b00000));
+    // returns to x30 if no register is given
+    RET (Opt(Gpr()))
+        (B(0b1101011001011111000000) Gpr(AllowZr, Size = 8, Default = 30):0 B(0b00000));
+    // pointer authentication, the code is added to or checked in Xd with the modifier in Xn or SP
+    PACIA [PAUTH] (Gpr() Gpr())
+        (B(0b1101101011000001) B(0b000000) Gpr(AllowSp, Size = 8):1 Gpr(AllowZr, Size = 8):0);
+    PACIB [PAUTH] (Gpr() Gpr())
+        (B(0b1101101011000001) B(0b000001) Gpr(AllowSp, Size = 8):1 Gpr(AllowZr, Size = 8):0);
+    AUTIA [PAUTH] (Gpr() Gpr())
+        (B(0b1101101011000001) B(0b000100) Gpr(AllowSp, Size = 8):1 Gpr(AllowZr, Size = 8):0);
+    AUTIB [PAUTH] (Gpr() Gpr())
+        (B(0b1101101011000001) B(0b000101) Gpr(AllowSp, Size = 8):1 Gpr(AllowZr, Size = 8):0);
+    XPACI [PAUTH] (Gpr())
+        (B(0b1101101011000001) B(0b010000) B(0b11111) Gpr(AllowZr, Size = 8):0);
+    // authenticated branches, LR is checked with SP as the modifier, or Xn with Xm or SP
+    RETAA [PAUTH] ()
+        (B(0b1101011001011111000010) B(0b1111111111));
+    RETAB [PAUTH] ()
+        (B(0b1101011001011111000011) B(0b1111111111));
+    BRAA [PAUTH] (Gpr() Gpr())
+        (B(0b1101011100011111000010) Gpr(AllowZr, Size = 8):0 Gpr(AllowSp, Size = 8):1);
+    BRAB [PAUTH] (Gpr() Gpr())
+        (B(0b1101011100011111000011) Gpr(AllowZr, Size = 8):0 Gpr(AllowSp, Size = 8):1);
+    BLRAA [PAUTH] (Gpr() Gpr())
+        (B(0b1101011100111111000010) Gpr(AllowZr, Size = 8):0 Gpr(AllowSp, Size = 8):1);
+    BLRAB [PAUTH] (Gpr() Gpr())
+        (B(0b1101011100111111000011) Gpr(AllowZr, Size = 8):0 Gpr(AllowSp, Size = 8):1);
+    CBZ (Gpr() Label())
+        (Sf():0 B(0b0110100) Label(SImm(19, Align = 2), Reloc = CondBr19):1 Gpr(AllowZr):0);
+    CBNZ (Gpr() Label())
+        (Sf():0 B(0b0110101) Label(SImm(19, Align = 2), Reloc = CondBr19):1 Gpr(AllowZr):0);
+    // the bit number is split into b5 and b40
+    TBZ (Gpr() Imm() Label())
+        (TestBit(High):1 B(0b0110110) TestBit(Low):1 Label(SImm(14, Align = 2), Reloc = TstBr14):2 Gpr(AllowZr):0);
+    TBNZ (Gpr() Imm() Label())
+        (TestBit(High):1 B(0b0110111) TestBit(Low):1 Label(SImm(14, Align = 2), Reloc = TstBr14):2 Gpr(AllowZr):0);
+
+    // pc relative address, the byte offset is split into immlo and immhi
+    ADR (Gpr() Label())
+        (B(0b0) Label(AdrImm(), Reloc = AdrPrelLo21):1 Gpr(AllowZr, Size = 8):0);
+    // address of the 4KB page, always left to the linker, `:pg_hi21:` is optional
+    ADRP (Gpr() Label())
+        (B(0b1) Label(AdrImm(), Reloc = AdrPrelPgHi21):1 Gpr(AllowZr, Size = 8):0);
+
+    // access size is the size field at the top, and the scale of the immediate offset
+    LDR Immediate
+        (Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b11100) Addr(Scaled, Scale = Sf):1 B(0b01) Addr(Offset, Scale = Sf):1 Addr(Base):1 Gpr(AllowZr):0),
+        Register
+        (Gpr() AddrReg())
+        (B(0b1) Sf():0 B(0b111000) B(0b01) B(0b1) Addr(Index):1 Addr(Extend):1 Addr(Shift, Scale = Sf):1 B(0b10) Addr(Base):1 Gpr(AllowZr):0),
+        Literal
+        (Gpr() AddrLabel())
+        (B(0b0) Sf():0 B(0b011000) AddrLabel(SImm(19, Align = 2), Reloc = LdPrelLo19):1 Gpr(AllowZr):0);
+    LDRB Immediate
+        (Gpr() AddrImm())
+        (B(0b0011100) Addr(Scaled, Scale = 0):1 B(0b01) Addr(Offset, Scale = 0):1 Addr(Base):1 Gpr(AllowZr, Size = 4):0),
+        Register
+        (Gpr() AddrReg())
+        (B(0b00111000) B(0b01) B(0b1) Addr(Index):1 Addr(Extend):1 Addr(Shift, Scale = 0):1 B(0b10) Addr(Base):1 Gpr(AllowZr, Size = 4):0);
+    LDRH Immediate
+        (Gpr() AddrImm())
+        (B(0b0111100) Addr(Scaled, Scale = 1):1 B(0b01) Addr(Offset, Scale = 1):1 Addr(Base):1 Gpr(AllowZr, Size = 4):0),
+        Register
+        (Gpr() AddrReg())
+        (B(0b01111000) B(0b01) B(0b1) Addr(Index):1 Addr(Extend):1 Addr(Shift, Scale = 1):1 B(0b10) Addr(Base):1 Gpr(AllowZr, Size = 4):0);
+    // sign extend to a W or X register
+    LDRSB Immediate
+        (Gpr() AddrImm())
+        (B(0b0011100) Addr(Scaled, Scale = 0):1 B(0b1) Sf(Not):0 Addr(Offset, Scale = 0):1 Addr(Base):1 Gpr(AllowZr):0),
+        Register
+        (Gpr() AddrReg())
+        (B(0b00111000) B(0b1) Sf(Not):0 B(0b1) Addr(Index):1 Addr(Extend):1 Addr(Shift, Scale = 0):1 B(0b10) Addr(Base):1 Gpr(AllowZr):0);
+    LDRSH Immediate
+        (Gpr() AddrImm())
+        (B(0b0111100) Addr(Scaled, Scale = 1):1 B(0b1) Sf(Not):0 Addr(Offset, Scale = 1):1 Addr(Base):1 Gpr(AllowZr):0),
+        Register
+        (Gpr() AddrReg())
+        (B(0b01111000) B(0b1) Sf(Not):0 B(0b1) Addr(Index):1 Addr(Extend):1 Addr(Shift, Scale = 1):1 B(0b10) Addr(Base):1 Gpr(AllowZr):0);
+    LDRSW Immediate
+        (Gpr() AddrImm())
+        (B(0b1011100) Addr(Scaled, Scale = 2):1 B(0b10) Addr(Offset, Scale = 2):1 Addr(Base):1 Gpr(AllowZr, Size = 8):0),
+        Register
+        (Gpr() AddrReg())
+        (B(0b10111000) B(0b10) B(0b1) Addr(Index):1 Addr(Extend):1 Addr(Shift, Scale = 2):1 B(0b10) Addr(Base):1 Gpr(AllowZr, Size = 8):0),
+        Literal
+        (Gpr() AddrLabel())
+        (B(0b10011000) AddrLabel(SImm(19, Align = 2), Reloc = LdPrelLo19):1 Gpr(AllowZr, Size = 8):0);
+    STR Immediate
+        (Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b11100) Addr(Scaled, Scale = Sf):1 B(0b00) Addr(Offset, Scale = Sf):1 Addr(Base):1 Gpr(AllowZr):0),
+        Register
+        (Gpr() AddrReg())
+        (B(0b1) Sf():0 B(0b111000) B(0b00) B(0b1) Addr(Index):1 Addr(Extend):1 Addr(Shift, Scale = Sf):1 B(0b10) Addr(Base):1 Gpr(AllowZr):0);
+    STRB Immediate
+        (Gpr() AddrImm())
+        (B(0b0011100) Addr(Scaled, Scale = 0):1 B(0b00) Addr(Offset, Scale = 0):1 Addr(Base):1 Gpr(AllowZr, Size = 4):0),
+        Register
+        (Gpr() AddrReg())
+        (B(0b00111000) B(0b00) B(0b1) Addr(Index):1 Addr(Extend):1 Addr(Shift, Scale = 0):1 B(0b10) Addr(Base):1 Gpr(AllowZr, Size = 4):0);
+    STRH Immediate
+        (Gpr() AddrImm())
+        (B(0b0111100) Addr(Scaled, Scale = 1):1 B(0b00) Addr(Offset, Scale = 1):1 Addr(Base):1 Gpr(AllowZr, Size = 4):0),
+        Register
+        (Gpr() AddrReg())
+        (B(0b01111000) B(0b00) B(0b1) Addr(Index):1 Addr(Extend):1 Addr(Shift, Scale = 1):1 B(0b10) Addr(Base):1 Gpr(AllowZr, Size = 4):0);
+
+    // both registers are the same size, the offset is scaled by it
+    LDP
+        (Gpr() Gpr() AddrImm())
+        (Sf():0 B(0b0) B(0b10100) Addr(PairMode):2 B(0b1) Addr(PairOffset, Scale = Sf):2 Gpr(AllowZr, Size = Sf):1 Addr(Base):2 Gpr(AllowZr):0);
+    LDPSW
+        (Gpr() Gpr() AddrImm())
+        (B(0b01) B(0b10100) Addr(PairMode):2 B(0b1) Addr(PairOffset, Scale = 2):2 Gpr(AllowZr, Size = 8):1 Addr(Base):2 Gpr(AllowZr, Size = 8):0);
+    LDNP
+        (Gpr() Gpr() AddrImm())
+        (Sf():0 B(0b0) B(0b10100) Addr(NoWriteback):2 B(0b1) Addr(PairOffset, Scale = Sf):2 Gpr(AllowZr, Size = Sf):1 Addr(Base):2 Gpr(AllowZr):0);
+    STP
+        (Gpr() Gpr() AddrImm())
+        (Sf():0 B(0b0) B(0b10100) Addr(PairMode):2 B(0b0) Addr(PairOffset, Scale = Sf):2 Gpr(AllowZr, Size = Sf):1 Addr(Base):2 Gpr(AllowZr):0);
+    STNP
+        (Gpr() Gpr() AddrImm())
+        (Sf():0 B(0b0) B(0b10100) Addr(NoWriteback):2 B(0b0) Addr(PairOffset, Scale = Sf):2 Gpr(AllowZr, Size = Sf):1 Addr(Base):2 Gpr(AllowZr):0);
+
+    // unscaled offset only, `LDR` already falls back to it
+    LDUR
+        (Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b111000) B(0b01) Addr(Unscaled):1 Addr(Base):1 Gpr(AllowZr):0);
+    LDURB
+        (Gpr() AddrImm())
+        (B(0b00111000) B(0b01) Addr(Unscaled):1 Addr(Base):1 Gpr(AllowZr, Size = 4):0);
+    LDURH
+        (Gpr() AddrImm())
+        (B(0b01111000) B(0b01) Addr(Unscaled):1 Addr(Base):1 Gpr(AllowZr, Size = 4):0);
+    LDURSB
+        (Gpr() AddrImm())
+        (B(0b00111000) B(0b1) Sf(Not):0 Addr(Unscaled):1 Addr(Base):1 Gpr(AllowZr):0);
+    LDURSH
+        (Gpr() AddrImm())
+        (B(0b01111000) B(0b1) Sf(Not):0 Addr(Unscaled):1 Addr(Base):1 Gpr(AllowZr):0);
+    LDURSW
+        (Gpr() AddrImm())
+        (B(0b10111000) B(0b10) Addr(Unscaled):1 Addr(Base):1 Gpr(AllowZr, Size = 8):0);
+    STUR
+        (Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b111000) B(0b00) Addr(Unscaled):1 Addr(Base):1 Gpr(AllowZr):0);
+    STURB
+        (Gpr() AddrImm())
+        (B(0b00111000) B(0b00) Addr(Unscaled):1 Addr(Base):1 Gpr(AllowZr, Size = 4):0);
+    STURH
+        (Gpr() AddrImm())
+        (B(0b01111000) B(0b00) Addr(Unscaled):1 Addr(Base):1 Gpr(AllowZr, Size = 4):0);
+
+    // exclusive and acquire/release accesses take `[xN]` or `[xN, #0]`, the size is at the top
+    // the status register of a store exclusive must not overlap the data or the base
+    LDXR
+        (Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b001000010) B(0b11111) B(0b0) B(0b11111) Addr(BaseOnly):1 Gpr(AllowZr):0);
+    LDXRB
+        (Gpr() AddrImm())
+        (B(0b00) B(0b001000010) B(0b11111) B(0b0) B(0b11111) Addr(BaseOnly):1 Gpr(AllowZr, Size = 4):0);
+    LDXRH
+        (Gpr() AddrImm())
+        (B(0b01) B(0b001000010) B(0b11111) B(0b0) B(0b11111) Addr(BaseOnly):1 Gpr(AllowZr, Size = 4):0);
+    LDAXR
+        (Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b001000010) B(0b11111) B(0b1) B(0b11111) Addr(BaseOnly):1 Gpr(AllowZr):0);
+    LDAXRB
+        (Gpr() AddrImm())
+        (B(0b00) B(0b001000010) B(0b11111) B(0b1) B(0b11111) Addr(BaseOnly):1 Gpr(AllowZr, Size = 4):0);
+    LDAXRH
+        (Gpr() AddrImm())
+        (B(0b01) B(0b001000010) B(0b11111) B(0b1) B(0b11111) Addr(BaseOnly):1 Gpr(AllowZr, Size = 4):0);
+    STXR
+        (Gpr() Gpr() AddrImm())
+        (B(0b1) Sf():1 B(0b001000000) Gpr(AllowZr, Size = 4, Distinct = [1 2]):0 B(0b0) B(0b11111) Addr(BaseOnly):2 Gpr(AllowZr):1);
+    STXRB
+        (Gpr() Gpr() AddrImm())
+        (B(0b00) B(0b001000000) Gpr(AllowZr, Size = 4, Distinct = [1 2]):0 B(0b0) B(0b11111) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    STXRH
+        (Gpr() Gpr() AddrImm())
+        (B(0b01) B(0b001000000) Gpr(AllowZr, Size = 4, Distinct = [1 2]):0 B(0b0) B(0b11111) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    STLXR
+        (Gpr() Gpr() AddrImm())
+        (B(0b1) Sf():1 B(0b001000000) Gpr(AllowZr, Size = 4, Distinct = [1 2]):0 B(0b1) B(0b11111) Addr(BaseOnly):2 Gpr(AllowZr):1);
+    STLXRB
+        (Gpr() Gpr() AddrImm())
+        (B(0b00) B(0b001000000) Gpr(AllowZr, Size = 4, Distinct = [1 2]):0 B(0b1) B(0b11111) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    STLXRH
+        (Gpr() Gpr() AddrImm())
+        (B(0b01) B(0b001000000) Gpr(AllowZr, Size = 4, Distinct = [1 2]):0 B(0b1) B(0b11111) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDXP
+        (Gpr() Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b001000011) B(0b11111) B(0b0) Gpr(AllowZr, Size = Sf, Distinct = [0]):1 Addr(BaseOnly):2 Gpr(AllowZr):0);
+    LDAXP
+        (Gpr() Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b001000011) B(0b11111) B(0b1) Gpr(AllowZr, Size = Sf, Distinct = [0]):1 Addr(BaseOnly):2 Gpr(AllowZr):0);
+    STXP
+        (Gpr() Gpr() Gpr() AddrImm())
+        (B(0b1) Sf():1 B(0b001000001) Gpr(AllowZr, Size = 4, Distinct = [1 2 3]):0 B(0b0) Gpr(AllowZr, SizeOf = 1):2 Addr(BaseOnly):3 Gpr(AllowZr):1);
+    STLXP
+        (Gpr() Gpr() Gpr() AddrImm())
+        (B(0b1) Sf():1 B(0b001000001) Gpr(AllowZr, Size = 4, Distinct = [1 2 3]):0 B(0b1) Gpr(AllowZr, SizeOf = 1):2 Addr(BaseOnly):3 Gpr(AllowZr):1);
+    LDAR
+        (Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b001000110) B(0b11111) B(0b1) B(0b11111) Addr(BaseOnly):1 Gpr(AllowZr):0);
+    LDARB
+        (Gpr() AddrImm())
+        (B(0b00) B(0b001000110) B(0b11111) B(0b1) B(0b11111) Addr(BaseOnly):1 Gpr(AllowZr, Size = 4):0);
+    LDARH
+        (Gpr() AddrImm())
+        (B(0b01) B(0b001000110) B(0b11111) B(0b1) B(0b11111) Addr(BaseOnly):1 Gpr(AllowZr, Size = 4):0);
+    STLR
+        (Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b001000100) B(0b11111) B(0b1) B(0b11111) Addr(BaseOnly):1 Gpr(AllowZr):0);
+    STLRB
+        (Gpr() AddrImm())
+        (B(0b00) B(0b001000100) B(0b11111) B(0b1) B(0b11111) Addr(BaseOnly):1 Gpr(AllowZr, Size = 4):0);
+    STLRH
+        (Gpr() AddrImm())
+        (B(0b01) B(0b001000100) B(0b11111) B(0b1) B(0b11111) Addr(BaseOnly):1 Gpr(AllowZr, Size = 4):0);
+
+    // LSE, `A` acquires and `L` releases, Rs is compared and Rt is stored if they match
+    CAS [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b0010001) B(0b0) B(0b1) Gpr(AllowZr):0 B(0b0) B(0b11111) Addr(BaseOnly):2 Gpr(AllowZr, Size = Sf):1);
+    CASA [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b0010001) B(0b1) B(0b1) Gpr(AllowZr):0 B(0b0) B(0b11111) Addr(BaseOnly):2 Gpr(AllowZr, Size = Sf):1);
+    CASL [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b0010001) B(0b0) B(0b1) Gpr(AllowZr):0 B(0b1) B(0b11111) Addr(BaseOnly):2 Gpr(AllowZr, Size = Sf):1);
+    CASAL [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b0010001) B(0b1) B(0b1) Gpr(AllowZr):0 B(0b1) B(0b11111) Addr(BaseOnly):2 Gpr(AllowZr, Size = Sf):1);
+    CASB [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b00) B(0b0010001) B(0b0) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b11111) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    CASAB [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b00) B(0b0010001) B(0b1) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b11111) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    CASLB [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b00) B(0b0010001) B(0b0) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b1) B(0b11111) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    CASALB [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b00) B(0b0010001) B(0b1) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b1) B(0b11111) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    CASH [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b01) B(0b0010001) B(0b0) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b11111) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    CASAH [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b01) B(0b0010001) B(0b1) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b11111) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    CASLH [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b01) B(0b0010001) B(0b0) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b1) B(0b11111) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    CASALH [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b01) B(0b0010001) B(0b1) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b1) B(0b11111) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    // both pairs start at an even register, only the first of each is encoded
+    CASP [LSE]
+        (Gpr() Gpr() Gpr() Gpr() AddrImm())
+        (B(0b0) Sf():0 B(0b0010000) B(0b0) B(0b1) Gpr(AllowZr, Size = Sf, Pair = 1):0 B(0b0) B(0b11111) Addr(BaseOnly):4 Gpr(AllowZr, Size = Sf, Pair = 3):2);
+    CASPA [LSE]
+        (Gpr() Gpr() Gpr() Gpr() AddrImm())
+        (B(0b0) Sf():0 B(0b0010000) B(0b1) B(0b1) Gpr(AllowZr, Size = Sf, Pair = 1):0 B(0b0) B(0b11111) Addr(BaseOnly):4 Gpr(AllowZr, Size = Sf, Pair = 3):2);
+    CASPL [LSE]
+        (Gpr() Gpr() Gpr() Gpr() AddrImm())
+        (B(0b0) Sf():0 B(0b0010000) B(0b0) B(0b1) Gpr(AllowZr, Size = Sf, Pair = 1):0 B(0b1) B(0b11111) Addr(BaseOnly):4 Gpr(AllowZr, Size = Sf, Pair = 3):2);
+    CASPAL [LSE]
+        (Gpr() Gpr() Gpr() Gpr() AddrImm())
+        (B(0b0) Sf():0 B(0b0010000) B(0b1) B(0b1) Gpr(AllowZr, Size = Sf, Pair = 1):0 B(0b1) B(0b11111) Addr(BaseOnly):4 Gpr(AllowZr, Size = Sf, Pair = 3):2);
+    // atomic memory operations, Rs is combined with the value at the address, which is loaded to Rt
+    LDADD [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b111000) B(0b0) B(0b0) B(0b1) Gpr(AllowZr):0 B(0b0) B(0b000) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = Sf):1);
+    LDADDA [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b111000) B(0b1) B(0b0) B(0b1) Gpr(AllowZr):0 B(0b0) B(0b000) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = Sf):1);
+    LDADDL [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b111000) B(0b0) B(0b1) B(0b1) Gpr(AllowZr):0 B(0b0) B(0b000) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = Sf):1);
+    LDADDAL [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b111000) B(0b1) B(0b1) B(0b1) Gpr(AllowZr):0 B(0b0) B(0b000) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = Sf):1);
+    LDADDB [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b00) B(0b111000) B(0b0) B(0b0) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b000) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDADDAB [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b00) B(0b111000) B(0b1) B(0b0) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b000) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDADDLB [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b00) B(0b111000) B(0b0) B(0b1) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b000) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDADDALB [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b00) B(0b111000) B(0b1) B(0b1) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b000) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDADDH [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b01) B(0b111000) B(0b0) B(0b0) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b000) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDADDAH [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b01) B(0b111000) B(0b1) B(0b0) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b000) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDADDLH [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b01) B(0b111000) B(0b0) B(0b1) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b000) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDADDALH [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b01) B(0b111000) B(0b1) B(0b1) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b000) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDCLR [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b111000) B(0b0) B(0b0) B(0b1) Gpr(AllowZr):0 B(0b0) B(0b001) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = Sf):1);
+    LDCLRA [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b111000) B(0b1) B(0b0) B(0b1) Gpr(AllowZr):0 B(0b0) B(0b001) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = Sf):1);
+    LDCLRL [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b111000) B(0b0) B(0b1) B(0b1) Gpr(AllowZr):0 B(0b0) B(0b001) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = Sf):1);
+    LDCLRAL [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b111000) B(0b1) B(0b1) B(0b1) Gpr(AllowZr):0 B(0b0) B(0b001) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = Sf):1);
+    LDCLRB [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b00) B(0b111000) B(0b0) B(0b0) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b001) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDCLRAB [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b00) B(0b111000) B(0b1) B(0b0) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b001) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDCLRLB [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b00) B(0b111000) B(0b0) B(0b1) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b001) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDCLRALB [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b00) B(0b111000) B(0b1) B(0b1) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b001) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDCLRH [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b01) B(0b111000) B(0b0) B(0b0) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b001) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDCLRAH [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b01) B(0b111000) B(0b1) B(0b0) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b001) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDCLRLH [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b01) B(0b111000) B(0b0) B(0b1) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b001) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDCLRALH [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b01) B(0b111000) B(0b1) B(0b1) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b001) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDEOR [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b111000) B(0b0) B(0b0) B(0b1) Gpr(AllowZr):0 B(0b0) B(0b010) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = Sf):1);
+    LDEORA [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b111000) B(0b1) B(0b0) B(0b1) Gpr(AllowZr):0 B(0b0) B(0b010) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = Sf):1);
+    LDEORL [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b111000) B(0b0) B(0b1) B(0b1) Gpr(AllowZr):0 B(0b0) B(0b010) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = Sf):1);
+    LDEORAL [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b111000) B(0b1) B(0b1) B(0b1) Gpr(AllowZr):0 B(0b0) B(0b010) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = Sf):1);
+    LDEORB [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b00) B(0b111000) B(0b0) B(0b0) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b010) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDEORAB [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b00) B(0b111000) B(0b1) B(0b0) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b010) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDEORLB [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b00) B(0b111000) B(0b0) B(0b1) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b010) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDEORALB [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b00) B(0b111000) B(0b1) B(0b1) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b010) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDEORH [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b01) B(0b111000) B(0b0) B(0b0) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b010) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDEORAH [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b01) B(0b111000) B(0b1) B(0b0) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b010) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDEORLH [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b01) B(0b111000) B(0b0) B(0b1) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b010) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDEORALH [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b01) B(0b111000) B(0b1) B(0b1) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b010) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDSET [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b111000) B(0b0) B(0b0) B(0b1) Gpr(AllowZr):0 B(0b0) B(0b011) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = Sf):1);
+    LDSETA [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b111000) B(0b1) B(0b0) B(0b1) Gpr(AllowZr):0 B(0b0) B(0b011) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = Sf):1);
+    LDSETL [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b111000) B(0b0) B(0b1) B(0b1) Gpr(AllowZr):0 B(0b0) B(0b011) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = Sf):1);
+    LDSETAL [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b111000) B(0b1) B(0b1) B(0b1) Gpr(AllowZr):0 B(0b0) B(0b011) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = Sf):1);
+    LDSETB [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b00) B(0b111000) B(0b0) B(0b0) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b011) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDSETAB [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b00) B(0b111000) B(0b1) B(0b0) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b011) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDSETLB [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b00) B(0b111000) B(0b0) B(0b1) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b011) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDSETALB [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b00) B(0b111000) B(0b1) B(0b1) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b011) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDSETH [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b01) B(0b111000) B(0b0) B(0b0) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b011) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDSETAH [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b01) B(0b111000) B(0b1) B(0b0) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b011) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDSETLH [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b01) B(0b111000) B(0b0) B(0b1) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b011) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDSETALH [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b01) B(0b111000) B(0b1) B(0b1) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b011) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDSMAX [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b111000) B(0b0) B(0b0) B(0b1) Gpr(AllowZr):0 B(0b0) B(0b100) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = Sf):1);
+    LDSMAXA [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b111000) B(0b1) B(0b0) B(0b1) Gpr(AllowZr):0 B(0b0) B(0b100) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = Sf):1);
+    LDSMAXL [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b111000) B(0b0) B(0b1) B(0b1) Gpr(AllowZr):0 B(0b0) B(0b100) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = Sf):1);
+    LDSMAXAL [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b111000) B(0b1) B(0b1) B(0b1) Gpr(AllowZr):0 B(0b0) B(0b100) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = Sf):1);
+    LDSMAXB [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b00) B(0b111000) B(0b0) B(0b0) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b100) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDSMAXAB [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b00) B(0b111000) B(0b1) B(0b0) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b100) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDSMAXLB [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b00) B(0b111000) B(0b0) B(0b1) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b100) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDSMAXALB [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b00) B(0b111000) B(0b1) B(0b1) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b100) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDSMAXH [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b01) B(0b111000) B(0b0) B(0b0) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b100) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDSMAXAH [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b01) B(0b111000) B(0b1) B(0b0) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b100) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDSMAXLH [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b01) B(0b111000) B(0b0) B(0b1) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b100) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDSMAXALH [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b01) B(0b111000) B(0b1) B(0b1) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b100) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDSMIN [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b111000) B(0b0) B(0b0) B(0b1) Gpr(AllowZr):0 B(0b0) B(0b101) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = Sf):1);
+    LDSMINA [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b111000) B(0b1) B(0b0) B(0b1) Gpr(AllowZr):0 B(0b0) B(0b101) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = Sf):1);
+    LDSMINL [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b111000) B(0b0) B(0b1) B(0b1) Gpr(AllowZr):0 B(0b0) B(0b101) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = Sf):1);
+    LDSMINAL [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b111000) B(0b1) B(0b1) B(0b1) Gpr(AllowZr):0 B(0b0) B(0b101) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = Sf):1);
+    LDSMINB [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b00) B(0b111000) B(0b0) B(0b0) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b101) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDSMINAB [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b00) B(0b111000) B(0b1) B(0b0) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b101) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDSMINLB [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b00) B(0b111000) B(0b0) B(0b1) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b101) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDSMINALB [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b00) B(0b111000) B(0b1) B(0b1) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b101) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDSMINH [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b01) B(0b111000) B(0b0) B(0b0) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b101) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDSMINAH [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b01) B(0b111000) B(0b1) B(0b0) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b101) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDSMINLH [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b01) B(0b111000) B(0b0) B(0b1) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b101) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDSMINALH [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b01) B(0b111000) B(0b1) B(0b1) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b101) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDUMAX [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b111000) B(0b0) B(0b0) B(0b1) Gpr(AllowZr):0 B(0b0) B(0b110) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = Sf):1);
+    LDUMAXA [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b111000) B(0b1) B(0b0) B(0b1) Gpr(AllowZr):0 B(0b0) B(0b110) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = Sf):1);
+    LDUMAXL [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b111000) B(0b0) B(0b1) B(0b1) Gpr(AllowZr):0 B(0b0) B(0b110) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = Sf):1);
+    LDUMAXAL [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b111000) B(0b1) B(0b1) B(0b1) Gpr(AllowZr):0 B(0b0) B(0b110) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = Sf):1);
+    LDUMAXB [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b00) B(0b111000) B(0b0) B(0b0) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b110) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDUMAXAB [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b00) B(0b111000) B(0b1) B(0b0) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b110) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDUMAXLB [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b00) B(0b111000) B(0b0) B(0b1) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b110) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDUMAXALB [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b00) B(0b111000) B(0b1) B(0b1) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b110) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDUMAXH [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b01) B(0b111000) B(0b0) B(0b0) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b110) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDUMAXAH [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b01) B(0b111000) B(0b1) B(0b0) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b110) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDUMAXLH [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b01) B(0b111000) B(0b0) B(0b1) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b110) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDUMAXALH [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b01) B(0b111000) B(0b1) B(0b1) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b110) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDUMIN [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b111000) B(0b0) B(0b0) B(0b1) Gpr(AllowZr):0 B(0b0) B(0b111) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = Sf):1);
+    LDUMINA [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b111000) B(0b1) B(0b0) B(0b1) Gpr(AllowZr):0 B(0b0) B(0b111) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = Sf):1);
+    LDUMINL [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b111000) B(0b0) B(0b1) B(0b1) Gpr(AllowZr):0 B(0b0) B(0b111) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = Sf):1);
+    LDUMINAL [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b111000) B(0b1) B(0b1) B(0b1) Gpr(AllowZr):0 B(0b0) B(0b111) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = Sf):1);
+    LDUMINB [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b00) B(0b111000) B(0b0) B(0b0) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b111) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDUMINAB [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b00) B(0b111000) B(0b1) B(0b0) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b111) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDUMINLB [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b00) B(0b111000) B(0b0) B(0b1) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b111) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDUMINALB [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b00) B(0b111000) B(0b1) B(0b1) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b111) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDUMINH [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b01) B(0b111000) B(0b0) B(0b0) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b111) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDUMINAH [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b01) B(0b111000) B(0b1) B(0b0) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b111) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDUMINLH [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b01) B(0b111000) B(0b0) B(0b1) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b111) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    LDUMINALH [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b01) B(0b111000) B(0b1) B(0b1) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b0) B(0b111) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    SWP [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b111000) B(0b0) B(0b0) B(0b1) Gpr(AllowZr):0 B(0b1) B(0b000) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = Sf):1);
+    SWPA [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b111000) B(0b1) B(0b0) B(0b1) Gpr(AllowZr):0 B(0b1) B(0b000) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = Sf):1);
+    SWPL [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b111000) B(0b0) B(0b1) B(0b1) Gpr(AllowZr):0 B(0b1) B(0b000) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = Sf):1);
+    SWPAL [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b1) Sf():0 B(0b111000) B(0b1) B(0b1) B(0b1) Gpr(AllowZr):0 B(0b1) B(0b000) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = Sf):1);
+    SWPB [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b00) B(0b111000) B(0b0) B(0b0) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b1) B(0b000) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    SWPAB [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b00) B(0b111000) B(0b1) B(0b0) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b1) B(0b000) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    SWPLB [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b00) B(0b111000) B(0b0) B(0b1) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b1) B(0b000) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    SWPALB [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b00) B(0b111000) B(0b1) B(0b1) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b1) B(0b000) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    SWPH [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b01) B(0b111000) B(0b0) B(0b0) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b1) B(0b000) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    SWPAH [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b01) B(0b111000) B(0b1) B(0b0) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b1) B(0b000) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    SWPLH [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b01) B(0b111000) B(0b0) B(0b1) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b1) B(0b000) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    SWPALH [LSE]
+        (Gpr() Gpr() AddrImm())
+        (B(0b01) B(0b111000) B(0b1) B(0b1) B(0b1) Gpr(AllowZr, Size = 4):0 B(0b1) B(0b000) B(0b00) Addr(BaseOnly):2 Gpr(AllowZr, Size = 4):1);
+    // CRC32 of Wn updated with the low bytes of Rm, `C` uses the Castagnoli polynomial
+    CRC32B [CRC] (Gpr() Gpr() Gpr())
+        (B(0b0) B(0b0011010110) Gpr(AllowZr, Size = 4):2 B(0b010) B(0b0) B(0b00) Gpr(AllowZr, Size = 4):1 Gpr(AllowZr, Size = 4):0);
+    CRC32H [CRC] (Gpr() Gpr() Gpr())
+        (B(0b0) B(0b0011010110) Gpr(AllowZr, Size = 4):2 B(0b010) B(0b0) B(0b01) Gpr(AllowZr, Size = 4):1 Gpr(AllowZr, Size = 4):0);
+    CRC32W [CRC] (Gpr() Gpr() Gpr())
+        (B(0b0) B(0b0011010110) Gpr(AllowZr, Size = 4):2 B(0b010) B(0b0) B(0b10) Gpr(AllowZr, Size = 4):1 Gpr(AllowZr, Size = 4):0);
+    CRC32X [CRC] (Gpr() Gpr() Gpr())
+        (B(0b1) B(0b0011010110) Gpr(AllowZr, Size = 8):2 B(0b010) B(0b0) B(0b11) Gpr(AllowZr, Size = 4):1 Gpr(AllowZr, Size = 4):0);
+    CRC32CB [CRC] (Gpr() Gpr() Gpr())
+        (B(0b0) B(0b0011010110) Gpr(AllowZr, Size = 4):2 B(0b010) B(0b1) B(0b00) Gpr(AllowZr, Size = 4):1 Gpr(AllowZr, Size = 4):0);
+    CRC32CH [CRC] (Gpr() Gpr() Gpr())
+        (B(0b0) B(0b0011010110) Gpr(AllowZr, Size = 4):2 B(0b010) B(0b1) B(0b01) Gpr(AllowZr, Size = 4):1 Gpr(AllowZr, Size = 4):0);
+    CRC32CW [CRC] (Gpr() Gpr() Gpr())
+        (B(0b0) B(0b0011010110) Gpr(AllowZr, Size = 4):2 B(0b010) B(0b1) B(0b10) Gpr(AllowZr, Size = 4):1 Gpr(AllowZr, Size = 4):0);
+    CRC32CX [CRC] (Gpr() Gpr() Gpr())
+        (B(0b1) B(0b0011010110) Gpr(AllowZr, Size = 8):2 B(0b010) B(0b1) B(0b11) Gpr(AllowZr, Size = 4):1 Gpr(AllowZr, Size = 4):0);
+    // one AES round on a 16b state, or one column mixing step
+    AESE [AES] (Vr() Vr())
+        (B(0b01001110) B(0b00) B(0b10100) B(0b00100) B(0b10) Vr(Size = 1, Lanes = 16):1 Vr(Size = 1, Lanes = 16):0);
+    AESD [AES] (Vr() Vr())
+        (B(0b01001110) B(0b00) B(0b10100) B(0b00101) B(0b10) Vr(Size = 1, Lanes = 16):1 Vr(Size = 1, Lanes = 16):0);
+    AESMC [AES] (Vr() Vr())
+        (B(0b01001110) B(0b00) B(0b10100) B(0b00110) B(0b10) Vr(Size = 1, Lanes = 16):1 Vr(Size = 1, Lanes = 16):0);
+    AESIMC [AES] (Vr() Vr())
+        (B(0b01001110) B(0b00) B(0b10100) B(0b00111) B(0b10) Vr(Size = 1, Lanes = 16):1 Vr(Size = 1, Lanes = 16):0);
+    // SHA hash updates, Qd holds the hash and Vm the schedule, SHA1 reads E from Sn
+    SHA1C [SHA2] (Dpr() Dpr() Vr())
+        (B(0b01011110) B(0b000) Vr(Size = 4, Lanes = 16):2 B(0b0) B(0b000) B(0b00) Dpr(Size = 4):1 Dpr(Size = 16):0);
+    SHA1P [SHA2] (Dpr() Dpr() Vr())
+        (B(0b01011110) B(0b000) Vr(Size = 4, Lanes = 16):2 B(0b0) B(0b001) B(0b00) Dpr(Size = 4):1 Dpr(Size = 16):0);
+    SHA1M [SHA2] (Dpr() Dpr() Vr())
+        (B(0b01011110) B(0b000) Vr(Size = 4, Lanes = 16):2 B(0b0) B(0b010) B(0b00) Dpr(Size = 4):1 Dpr(Size = 16):0);
+    SHA1SU0 [SHA2] (Vr() Vr() Vr())
+        (B(0b01011110) B(0b000) Vr(Size = 4, Lanes = 16):2 B(0b0) B(0b011) B(0b00) Vr(Size = 4, Lanes = 16):1 Vr(Size = 4, Lanes = 16):0);
+    SHA256H [SHA2] (Dpr() Dpr() Vr())
+        (B(0b01011110) B(0b000) Vr(Size = 4, Lanes = 16):2 B(0b0) B(0b100) B(0b00) Dpr(Size = 16):1 Dpr(Size = 16):0);
+    SHA256H2 [SHA2] (Dpr() Dpr() Vr())
+        (B(0b01011110) B(0b000) Vr(Size = 4, Lanes = 16):2 B(0b0) B(0b101) B(0b00) Dpr(Size = 16):1 Dpr(Size = 16):0);
+    SHA256SU1 [SHA2] (Vr() Vr() Vr())
+        (B(0b01011110) B(0b000) Vr(Size = 4, Lanes = 16):2 B(0b0) B(0b110) B(0b00) Vr(Size = 4, Lanes = 16):1 Vr(Size = 4, Lanes = 16):0);
+    SHA1H [SHA2] (Dpr() Dpr())
+        (B(0b01011110) B(0b00) B(0b10100) B(0b00000) B(0b10) Dpr(Size = 4):1 Dpr(Size = 4):0);
+    SHA1SU1 [SHA2] (Vr() Vr())
+        (B(0b01011110) B(0b00) B(0b10100) B(0b00001) B(0b10) Vr(Size = 4, Lanes = 16):1 Vr(Size = 4, Lanes = 16):0);
+    SHA256SU0 [SHA2] (Vr() Vr())
+        (B(0b01011110) B(0b00) B(0b10100) B(0b00010) B(0b10) Vr(Size = 4, Lanes = 16):1 Vr(Size = 4, Lanes = 16):0);
+    // polynomial multiply long of the lower or upper half, the 1q form needs AES
+    PMULL [SIMD] (Vr() Vr() Vr())
+        (B(0b0) Q(Lower):1 B(0b001110) PmullSize():1 B(0b1) Vr(Same = 1):2 B(0b111000) Vr():1 Vr(Long = 1):0);
+    PMULL2 [SIMD] (Vr() Vr() Vr())
+        (B(0b0) Q(Upper):1 B(0b001110) PmullSize():1 B(0b1) Vr(Same = 1):2 B(0b111000) Vr():1 Vr(Long = 1):0);
 }
 
 trait Instr {
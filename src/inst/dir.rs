@@ -2,6 +2,7 @@
     code::{self, Span},
     enum_str,
     inst::{
+        label,
         meta::*,
         op,
         util::{is_variant, Param},
@@ -10,17 +11,199 @@
 };
 
 #[derive(Debug)]
-pub enum Error {}
+pub enum Error {
+    /// value refers to a label that is not defined yet
+    NotConstant,
+    /// `.else`, `.elseif` or `.endif` without an open `.if`
+    NoIf,
+    /// `.else` or `.elseif` after the `.else` of the same block
+    AfterElse,
+    /// symbol is a label, or a constant defined with `.equ`
+    Redefined(label::Key),
+}
 type Result = std::result::Result<(), Error>;
 
+// `If`, `Else` and friends are keywords, names are matched case insensitively
 def_directs! {
     global(Label()),
+    equ(Label(), Imm()),
+    set(Label(), Imm()),
+    If(Imm()),
+    IfDef(Label()),
+    IfNDef(Label()),
+    ElseIf(Imm()),
+    Else(),
+    EndIf(),
+    arch_extension(ArchExtension()),
+    arch(Arch()),
+    cpu(Cpu()),
+}
+
+impl Name {
+    /// what the args of the directive have to be, told when they don't match
+    pub fn expects(self) -> &'static str {
+        match self {
+            Name::global | Name::IfDef | Name::IfNDef => "a symbol",
+            Name::equ | Name::set => "a symbol and an absolute expression",
+            Name::If | Name::ElseIf => "an absolute expression",
+            Name::Else | Name::EndIf => "no arguments",
+            Name::arch_extension => "an extension name",
+            Name::arch => "an architecture name",
+            Name::cpu => "a processor name",
+        }
+    }
 }
 
+struct Cond {
+    span: Span,
+    active: bool,
+    /// one of the branches was active
+    taken: bool,
+    has_else: bool,
+}
+
+/// blocks of conditional assembly that are open, the lines of a block that is not active are
+/// skipped by the parser
+#[derive(Default)]
+pub struct CondStack(Vec<Cond>);
+
+impl CondStack {
+    pub fn is_active(&self) -> bool {
+        self.0.last().map_or(true, |cond| cond.active)
+    }
+
+    /// `.if` of every block that is still open, innermost last
+    pub fn open(&self) -> impl Iterator<Item = Span> + '_ {
+        self.0.iter().map(|cond| cond.span)
+    }
+
+    /// number of blocks that are still open
+    pub fn depth(&self) -> usize {
+        self.0.len()
+    }
+
+    /// close the blocks opened after `depth`, when a macro they are in ends early
+    pub fn unwind(&mut self, depth: usize) {
+        self.0.truncate(depth);
+    }
+
+    fn begin(&mut self, span: Span, active: bool) {
+        self.0.push(Cond {
+            span,
+            active,
+            taken: active,
+            has_else: false,
+        });
+    }
+
+    /// `.elseif` and `.else`, `cond` is only evaluated if no branch was taken
+    fn branch(
+        &mut self,
+        is_else: bool,
+        cond: impl FnOnce() -> std::result::Result<bool, Error>,
+    ) -> Result {
+        let top = self.0.last_mut().ok_or(Error::NoIf)?;
+        if top.has_else {
+            return Err(Error::AfterElse);
+        }
+        top.has_else = is_else;
+        top.active = !top.taken && cond()?;
+        top.taken |= top.active;
+        Ok(())
+    }
+
+    fn end(&mut self) -> Result {
+        self.0.pop().map(|_| ()).ok_or(Error::NoIf)
+    }
+}
+
+fn known(imm: op::Imm) -> std::result::Result<bool, Error> {
+    match imm.deferred {
+        Some(..) => Err(Error::NotConstant),
+        None => Ok(imm.value != 0),
+    }
+}
+
+fn assign<E: Emitter>(e: &mut E, name: op::Label, value: op::Imm, reassignable: bool) -> Result {
+    if value.deferred.is_some() {
+        return Err(Error::NotConstant);
+    }
+    if !e.define_symbol(name.0, value.value, reassignable) {
+        return Err(Error::Redefined(name.0));
+    }
+    Ok(())
+}
+
+#[allow(non_snake_case)]
 mod def {
     use super::*;
 
     pub fn global<E: Emitter>(e: &mut E, label: op::Label) -> Result {
+        e.set_global(label.0);
+        Ok(())
+    }
+
+    pub fn equ<E: Emitter>(e: &mut E, name: op::Label, value: op::Imm) -> Result {
+        assign(e, name, value, false)
+    }
+
+    /// also `NAME = value`
+    pub fn set<E: Emitter>(e: &mut E, name: op::Label, value: op::Imm) -> Result {
+        assign(e, name, value, true)
+    }
+
+    pub fn If<E: Emitter>(e: &mut E, cond: op::Imm) -> Result {
+        let span = e.span();
+        let active = known(cond);
+        // still opens the block, so its `.endif` has something to match
+        e.conds().begin(span, *active.as_ref().unwrap_or(&false));
+        active.map(|_| ())
+    }
+
+    pub fn IfDef<E: Emitter>(e: &mut E, label: op::Label) -> Result {
+        let span = e.span();
+        let defined = e.resolve_label(label.0).is_some();
+        e.conds().begin(span, defined);
+        Ok(())
+    }
+
+    pub fn IfNDef<E: Emitter>(e: &mut E, label: op::Label) -> Result {
+        let span = e.span();
+        let defined = e.resolve_label(label.0).is_some();
+        e.conds().begin(span, !defined);
+        Ok(())
+    }
+
+    pub fn ElseIf<E: Emitter>(e: &mut E, cond: op::Imm) -> Result {
+        e.conds().branch(false, || known(cond))
+    }
+
+    pub fn Else<E: Emitter>(e: &mut E) -> Result {
+        e.conds().branch(true, || Ok(true))
+    }
+
+    pub fn EndIf<E: Emitter>(e: &mut E) -> Result {
+        e.conds().end()
+    }
+
+    /// `.arch_extension crc` enables the instructions of an extension, `nocrc` disables them
+    pub fn arch_extension<E: Emitter>(e: &mut E, ext: op::ArchExtension) -> Result {
+        match ext.enable {
+            true => e.features().enable(ext.features),
+            false => e.features().disable(ext.features),
+        }
+        Ok(())
+    }
+
+    /// `.arch armv8.1-a+crc` replaces every feature of the target
+    pub fn arch<E: Emitter>(e: &mut E, arch: op::Arch) -> Result {
+        *e.features() = arch.0;
+        Ok(())
+    }
+
+    /// `.cpu cortex-a53` replaces every feature of the target with those of the processor
+    pub fn cpu<E: Emitter>(e: &mut E, cpu: op::Cpu) -> Result {
+        *e.features() = cpu.0;
         Ok(())
     }
 }
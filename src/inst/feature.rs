@@ -0,0 +1,314 @@
+use crate::enum_str::EnumStr;
+
+crate::enum_str! {
+    /// optional extension of the architecture, named as in `.arch_extension crc`
+    #[allow(clippy::upper_case_acronyms)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[repr(u8)]
+    pub enum Feature {
+        FP,    // scalar floating point
+        SIMD,  // vectors, aka `neon`
+        CRC,   // CRC32 and CRC32C
+        LSE,   // atomic memory operations, CAS, LDADD and friends
+        AES,   // AES rounds and PMULL
+        SHA2,  // SHA1 and SHA256
+        FP16,  // half precision arithmetic on H registers
+        PAUTH, // pointer authentication
+        BTI,   // branch target identification
+        SVE,   // scalable vectors, none of its instructions are defined yet
+    }
+}
+
+impl Feature {
+    pub const ALL: &'static [Feature] = &[
+        Feature::FP,
+        Feature::SIMD,
+        Feature::CRC,
+        Feature::LSE,
+        Feature::AES,
+        Feature::SHA2,
+        Feature::FP16,
+        Feature::PAUTH,
+        Feature::BTI,
+        Feature::SVE,
+    ];
+
+    /// features that enabling this one enables too, and disabling them disables it
+    const fn requires(self) -> TargetFeatures {
+        use Feature::*;
+        match self {
+            SIMD | FP16 => TargetFeatures::of(&[FP]),
+            AES | SHA2 => TargetFeatures::of(&[FP, SIMD]),
+            SVE => TargetFeatures::of(&[FP, SIMD, FP16]),
+            FP | CRC | LSE | PAUTH | BTI => TargetFeatures::NONE,
+        }
+    }
+}
+
+/// features that each version of ARMv8-A adds to the one before it,
+/// ARMv9.x-A has those of ARMv8.(x+5)-A and SVE
+const V8_ADDS: [&[Feature]; 10] = {
+    use Feature::*;
+    [
+        &[FP, SIMD],
+        &[CRC, LSE],
+        &[],
+        &[PAUTH],
+        &[],
+        &[BTI],
+        &[],
+        &[],
+        &[],
+        &[],
+    ]
+};
+
+/// processors of `.cpu`, their architecture and the optional extensions they have
+const CPUS: &[(&str, &str, &[Feature])] = {
+    use Feature::*;
+    &[
+        ("generic", "armv8-a", &[]),
+        ("cortex-a53", "armv8-a", &[CRC]),
+        ("cortex-a57", "armv8-a", &[CRC]),
+        ("cortex-a72", "armv8-a", &[CRC]),
+        ("cortex-a73", "armv8-a", &[CRC]),
+        ("cortex-a55", "armv8.2-a", &[FP16]),
+        ("cortex-a75", "armv8.2-a", &[FP16]),
+        ("cortex-a76", "armv8.2-a", &[FP16]),
+        ("cortex-a77", "armv8.2-a", &[FP16]),
+        ("cortex-a78", "armv8.2-a", &[FP16]),
+        ("cortex-x1", "armv8.2-a", &[FP16]),
+        ("neoverse-n1", "armv8.2-a", &[FP16]),
+        ("neoverse-v1", "armv8.4-a", &[FP16, SVE]),
+        ("cortex-a510", "armv9-a", &[]),
+        ("cortex-a710", "armv9-a", &[]),
+        ("cortex-x2", "armv9-a", &[]),
+        ("neoverse-n2", "armv9-a", &[]),
+    ]
+};
+
+/// name in `.arch`, `.cpu` or on the command line that is not known
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnknownName<'a> {
+    /// architecture or processor before the first `+`
+    Base(&'a str),
+    /// extension after a `+`
+    Extension(&'a str),
+}
+
+/// features the target has, or that an instruction requires
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TargetFeatures(u64);
+
+/// `armv8-a`, what is assembled for if no target is given
+impl Default for TargetFeatures {
+    fn default() -> Self {
+        Self::of(V8_ADDS[0])
+    }
+}
+
+impl TargetFeatures {
+    pub const NONE: Self = Self(0);
+
+    pub const fn of(features: &[Feature]) -> Self {
+        let mut bits = 0;
+        let mut i = 0;
+        while i < features.len() {
+            bits |= 1 << features[i] as u64;
+            i += 1;
+        }
+        Self(bits)
+    }
+
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub const fn has(self, feature: Feature) -> bool {
+        self.contains(Self::of(&[feature]))
+    }
+
+    pub fn insert(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+
+    pub fn remove(&mut self, other: Self) {
+        self.0 &= !other.0;
+    }
+
+    /// `other` and the features they require
+    pub fn enable(&mut self, other: Self) {
+        for &feature in Feature::ALL {
+            if other.has(feature) {
+                self.insert(Self::of(&[feature]));
+                self.insert(feature.requires());
+            }
+        }
+    }
+
+    /// `other` and the features that require any of them
+    pub fn disable(&mut self, other: Self) {
+        for &feature in Feature::ALL {
+            if other.has(feature) || feature.requires().0 & other.0 != 0 {
+                self.remove(Self::of(&[feature]));
+            }
+        }
+    }
+
+    /// first of the features in `self` that `target` does not have
+    pub fn missing(self, target: Self) -> Option<Feature> {
+        Feature::ALL
+            .iter()
+            .copied()
+            .find(|&feature| self.has(feature) && !target.has(feature))
+    }
+
+    /// feature named in any case, `crypto` for both AES and SHA2, or `neon` for SIMD
+    pub fn extension(name: &str) -> Option<Self> {
+        if name.eq_ignore_ascii_case("crypto") {
+            return Some(Self::of(&[Feature::AES, Feature::SHA2]));
+        }
+        if name.eq_ignore_ascii_case("neon") {
+            return Some(Self::of(&[Feature::SIMD]));
+        }
+        Feature::from_str_mixed(name).map(|feature| Self::of(&[feature]))
+    }
+
+    /// `crc` to enable an extension or `nocrc` to disable it
+    pub fn toggle(name: &str) -> Option<(Self, bool)> {
+        match name.get(..2) {
+            Some(no) if no.eq_ignore_ascii_case("no") => {
+                Some((Self::extension(&name[2..])?, false))
+            }
+            _ => Some((Self::extension(name)?, true)),
+        }
+    }
+
+    /// `armv8.2-a+crc+nolse`, an architecture from `armv8-a` to `armv8.9-a` or `armv9-a` to
+    /// `armv9.4-a`, and the extensions enabled or disabled after it
+    pub fn arch(name: &str) -> Result<Self, UnknownName<'_>> {
+        Self::with_extensions(name, Self::arch_version)
+    }
+
+    /// `cortex-a53+crypto`, a processor and the extensions enabled or disabled after it
+    pub fn cpu(name: &str) -> Result<Self, UnknownName<'_>> {
+        Self::with_extensions(name, |name| {
+            let &(_, arch, extra) = CPUS
+                .iter()
+                .find(|(cpu, ..)| cpu.eq_ignore_ascii_case(name))?;
+            let mut features = Self::arch_version(arch)?;
+            features.enable(Self::of(extra));
+            Some(features)
+        })
+    }
+
+    fn arch_version(name: &str) -> Option<Self> {
+        let version = name
+            .get(..4)?
+            .eq_ignore_ascii_case("armv")
+            .then(|| &name[4..])?;
+        let version = version.strip_suffix(['a', 'A'])?.strip_suffix('-')?;
+        let (major, minor) = match version.split_once('.') {
+            Some((major, minor)) if minor.len() == 1 => (major, minor.parse::<usize>().ok()?),
+            Some(..) => return None,
+            None => (version, 0),
+        };
+        let (minor, sve) = match major {
+            "8" => (minor, false),
+            "9" => (minor + 5, true),
+            _ => return None,
+        };
+        let mut features = Self::NONE;
+        for adds in V8_ADDS.get(..=minor)? {
+            features.enable(Self::of(adds));
+        }
+        if sve {
+            features.enable(Self::of(&[Feature::SVE]));
+        }
+        Some(features)
+    }
+
+    fn with_extensions(
+        name: &str,
+        base: impl Fn(&str) -> Option<Self>,
+    ) -> Result<Self, UnknownName<'_>> {
+        let mut parts = name.split('+');
+        let name = parts.next().unwrap_or_default();
+        let mut features = base(name).ok_or(UnknownName::Base(name))?;
+        for ext in parts {
+            match Self::toggle(ext) {
+                Some((ext, true)) => features.enable(ext),
+                Some((ext, false)) => features.disable(ext),
+                None => return Err(UnknownName::Extension(ext)),
+            }
+        }
+        Ok(features)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_finds_missing_features() {
+        let crc = TargetFeatures::of(&[Feature::CRC]);
+        let mut target = TargetFeatures::extension("crypto").unwrap();
+        assert_eq!(crc.missing(target), Some(Feature::CRC));
+        assert_eq!(TargetFeatures::NONE.missing(target), None);
+        target.insert(TargetFeatures::extension("CRC").unwrap());
+        assert_eq!(crc.missing(target), None);
+        target.remove(TargetFeatures::of(&[Feature::AES]));
+        assert_eq!(
+            TargetFeatures::of(&[Feature::SHA2, Feature::AES]).missing(target),
+            Some(Feature::AES)
+        );
+        assert_eq!(TargetFeatures::extension("sve9"), None);
+    }
+
+    #[test]
+    fn it_parses_architectures_and_cpus() {
+        use Feature::*;
+        let v8 = TargetFeatures::arch("armv8-a").unwrap();
+        assert_eq!(v8, TargetFeatures::default());
+        assert_eq!(v8, TargetFeatures::of(&[FP, SIMD]));
+        let v81 = TargetFeatures::arch("armv8.1-a").unwrap();
+        assert!(v81.has(LSE) && v81.has(CRC) && !v81.has(PAUTH));
+        let v9 = TargetFeatures::arch("ARMv9-A").unwrap();
+        assert!(v9.has(BTI) && v9.has(SVE) && v9.has(FP16));
+        assert_eq!(
+            TargetFeatures::arch("armv9.4-a"),
+            TargetFeatures::arch("armv9-a")
+        );
+        assert_eq!(
+            TargetFeatures::arch("armv8.10-a"),
+            Err(UnknownName::Base("armv8.10-a"))
+        );
+        assert_eq!(
+            TargetFeatures::arch("armv7-a"),
+            Err(UnknownName::Base("armv7-a"))
+        );
+
+        let arch = TargetFeatures::arch("armv8.1-a+crypto+nolse").unwrap();
+        assert!(arch.has(AES) && arch.has(SHA2) && arch.has(CRC) && !arch.has(LSE));
+        assert_eq!(
+            TargetFeatures::arch("armv8-a+lse+bad"),
+            Err(UnknownName::Extension("bad"))
+        );
+
+        // disabling a feature disables the ones that need it
+        let none = TargetFeatures::arch("armv8-a+crypto+fp16+nofp").unwrap();
+        assert_eq!(none, TargetFeatures::NONE);
+        let simd = TargetFeatures::arch("armv8-a+nosimd+sha2").unwrap();
+        assert!(simd.has(SIMD) && simd.has(SHA2) && !simd.has(AES));
+
+        let a53 = TargetFeatures::cpu("cortex-a53+crypto").unwrap();
+        assert!(a53.has(CRC) && a53.has(AES) && !a53.has(LSE));
+        let n1 = TargetFeatures::cpu("neoverse-n1").unwrap();
+        assert!(n1.has(LSE) && n1.has(FP16) && !n1.has(PAUTH));
+        assert_eq!(
+            TargetFeatures::cpu("armv8-a"),
+            Err(UnknownName::Base("armv8-a"))
+        );
+    }
+}
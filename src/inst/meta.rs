@@ -15,28 +15,34 @@ macro_rules! _def_dec_instr {
         ( $( $arg_name:ident $arg_opts:tt )* )
         ( $( $enc_name:ident $enc_opts:tt $( : $enc_idx:tt )? )* )
     } => {
-        #[allow(non_camel_case_types)]
+        // placeholder until the decoder is generated from the table
+        #[allow(non_camel_case_types, dead_code, clippy::upper_case_acronyms)]
         pub struct $inst (u32);
     };
 }
 
 macro_rules! __def_inst_type {
     {
-        $mnem:ident $inst:ident $( $variant:ident )?
+        $mnem:ident $inst:ident $( $variant:ident )? [ $( $feature:ident )* ]
         ( $( $arg_name:ident $arg_opts:tt )* )
         ( $( $enc_name:ident $enc_opts:tt $( : $enc_idx:tt )? )* )
     } => {
-        #[allow(non_camel_case_types)]
+        #[allow(non_camel_case_types, clippy::upper_case_acronyms)]
         pub struct $inst (
-            $( _arg_type!($arg_name $arg_opts) ),*
+            $( pub _arg_type!($arg_name $arg_opts) ),*
         );
         impl super::EncInstr for $inst {
             const MNEM: super::Mnemonic = super::Mnemonic:: $mnem;
             $( const VARIANT: super::Variant = super::Variant:: $variant; )?
+            const FEATURES: $crate::inst::TargetFeatures = $crate::inst::TargetFeatures::of(&[
+                $( $crate::inst::Feature:: $feature ),*
+            ]);
             const ARGS: &'static [$crate::inst::util::Param] = &[
                 $( _arg_kind!($arg_name $arg_opts) ),*
             ];
 
+            // instructions without operands never touch `iter`
+            #[allow(unused_mut, unused_variables)]
             fn from_ops<'a, I>(mut iter: I) -> Self
             where
                 I: ::std::iter::Iterator<Item = &'a $crate::inst::operand::Ops>
@@ -46,18 +52,7 @@ fn from_ops<'a, I>(mut iter: I) -> Self
                 )
             }
             fn emit<E: $crate::inst::Emitter>(&self, e: &mut E) -> ::std::result::Result<(), $crate::inst::ErrorMacro> {
-                use $crate::inst::operand::Encoder;
-                use ::bit::IntN;
-                use $crate::inst::operand::enc;
-                e.begin_instr();
-                $(
-                    _arg_encode!( $enc_name $enc_opts self e $( $enc_idx )? )
-                        .map_err(|e| $crate::inst::ErrorMacro(e,
-                            concat!( stringify!($enc_name), stringify!($enc_opts) $(, stringify!( : $enc_idx ) )? )
-                        ))?;
-                )*
-                e.end_instr();
-                Ok(())
+                __emit_instr!( self e $( $enc_name $enc_opts $( : $enc_idx )? )* )
             }
         }
         impl ::std::fmt::Debug for $inst {
@@ -70,12 +65,32 @@ fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
     };
 }
 
+// one instruction word, or a pseudo instruction that `inst::pseudo::$func` expands
+macro_rules! __emit_instr {
+    { $s:tt $e:tt Expand($func:ident) } => {
+        $crate::inst::pseudo::$func($s, $e)
+    };
+    { $s:tt $e:tt $( $enc_name:ident $enc_opts:tt $( : $enc_idx:tt )? )* } => {{
+        use $crate::inst::operand::Encoder;
+        use $crate::inst::operand::enc;
+        $e.begin_instr();
+        $(
+            _arg_encode!( $enc_name $enc_opts $s $e $( $enc_idx )? )
+                .map_err(|e| $crate::inst::ErrorMacro(e,
+                    concat!( stringify!($enc_name), stringify!($enc_opts) $(, stringify!( : $enc_idx ) )? )
+                ))?;
+        )*
+        $e.end_instr();
+        Ok(())
+    }};
+}
+
 macro_rules! __def_insts {
     {
     $(
         $mnem:ident :
             $(
-                $inst:ident $( $variant:ident )?
+                $inst:ident $( $variant:ident )? [ $( $feature:ident )* ]
                 ( $( $args:tt )* )
                 ( $( $encode:tt )* )
             ),+
@@ -90,6 +105,8 @@ pub trait EncInstr
             const MNEM: Mnemonic;
             const VARIANT: Variant = Variant::Default;
             const VARIANTS: &'static [Variant] = &[Self::VARIANT];
+            /// extensions the target must have
+            const FEATURES: $crate::inst::TargetFeatures = $crate::inst::TargetFeatures::NONE;
             const ARGS: &'static [$crate::inst::util::Param];
 
             fn from_ops<'a, I>(iter: I) -> Self
@@ -102,7 +119,7 @@ pub mod enc {
             use $crate::inst::meta::*;
 
             $(  $( // mnem, variant
-                __def_inst_type!{ $mnem $inst $($variant)? ( $($args)* ) ( $($encode)* ) }
+                __def_inst_type!{ $mnem $inst $($variant)? [ $($feature)* ] ( $($args)* ) ( $($encode)* ) }
             )+  )+
         }
 
@@ -134,6 +151,22 @@ pub fn narrow_variant(mnem: Mnemonic) -> $crate::inst::NarrowVariant {
             }
         }
 
+        /// extensions that the variant of `mnem` picked by `narrow_variant` requires
+        pub fn required_features(mnem: Mnemonic, variant_idx: usize) -> $crate::inst::TargetFeatures {
+            match mnem {
+            $(
+                Mnemonic:: $mnem => {
+                    match variant_idx {
+                    $(
+                        ${index()} => <$crate::inst::def::enc:: $inst as EncInstr>::FEATURES,
+                    )+
+                        _ => ::std::unreachable!(),
+                    }
+                }
+            ),+
+            }
+        }
+
         pub fn get_variant_and_emit<'a, I, E>(mnem: Mnemonic, variant_idx: usize, iter: I, e: &mut E)
             -> ::std::result::Result<(), $crate::inst::ErrorMacro>
             where
@@ -147,7 +180,6 @@ pub fn get_variant_and_emit<'a, I, E>(mnem: Mnemonic, variant_idx: usize, iter:
                     $(
                         ${index()} => {
                             let instr = <$crate::inst::def::enc:: $inst as EncInstr>::from_ops(iter.clone());
-                            println!("{:#010X}: {:?}", e.pc(), instr);
                             return instr.emit(e);
                         }
                     ),+
@@ -165,6 +197,7 @@ macro_rules! def_instrs {
     $(
         $mnem:ident $(
             $( $variant:ident )?
+            $( [ $( $feature:ident )+ ] )?
             ( $( $args:tt )* )
             ( $( $encode:tt )* )
         ),+ ;
@@ -176,7 +209,7 @@ macro_rules! def_instrs {
             __def_insts!{
                 $(
                     $mnem : $(
-                        [<$mnem $(_ $variant)?>] $($variant)?
+                        [<$mnem $(_ $variant)?>] $($variant)? [ $( $( $feature )+ )? ]
                         ( $($args)* )
                         ( $($encode)* )
                     ),+ ;
@@ -190,6 +223,7 @@ macro_rules! def_directs {
     { $( $name:ident ( $( $arg_name:ident $arg_opts:tt ),* ) ),* $(,)? } => {
         enum_str! {
             #[allow(non_camel_case_types)]
+            #[derive(Debug, Clone, Copy, PartialEq, Eq)]
             pub enum Name {
                 $( $name, )*
             }
@@ -200,7 +234,7 @@ pub fn narrow_variant(name: Name) -> $crate::inst::NarrowVariant {
             $(
                 Name:: $name => {
                     const EXPECT: &'static [$crate::inst::util::Param] =
-                        &[ $( $crate::inst::meta_operand::_arg_kind!($arg_name $arg_opts) )* ];
+                        &[ $( $crate::inst::meta_operand::_arg_kind!($arg_name $arg_opts) ),* ];
 
                     debug_assert!($crate::inst::util::rest_are_opt(EXPECT));
 
@@ -210,27 +244,29 @@ pub fn narrow_variant(name: Name) -> $crate::inst::NarrowVariant {
             }
         }
 
-        pub fn select_and_run<'a, E: Emitter, I>(e: &mut E, mut iter: I) -> std::result::Result<(), $crate::inst::dir::Error>
+        /// `iter` was already narrowed to the args of `name`
+        pub fn select_and_run<'a, E: Emitter, I>(e: &mut E, name: Name, mut iter: I) -> std::result::Result<(), $crate::inst::dir::Error>
             where I: Iterator<Item = &'a Ops> + Clone,
         {
+            match name {
             $(
-                {
+                Name:: $name => {
                     const EXPECT: &'static [$crate::inst::util::Param] =
-                        &[ $( $crate::inst::meta_operand::_arg_kind!($arg_name $arg_opts) )* ];
-                    if $crate::inst::util::is_variant(iter.clone(), EXPECT) {
-                        return $crate::inst::dir::def:: $name (e,
-                            $( $crate::inst::meta_operand::_arg_parse!($arg_name iter), )*
-                        );
-                    }
+                        &[ $( $crate::inst::meta_operand::_arg_kind!($arg_name $arg_opts) ),* ];
+                    debug_assert!($crate::inst::util::is_variant(iter.clone(), EXPECT));
+                    $crate::inst::dir::def:: $name (e,
+                        $( $crate::inst::meta_operand::_arg_parse!($arg_name iter), )*
+                    )
                 }
             )*
-            todo!()
+            }
         }
     }
 }
 
 pub(super) use super::meta_operand::*;
 pub(super) use __def_inst_type;
+pub(super) use __emit_instr;
 pub(super) use __def_insts;
 pub(super) use __def_mnemonic;
 pub(super) use _def_dec_instr;
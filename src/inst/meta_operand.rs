@@ -8,6 +8,9 @@ macro_rules! _arg_encode_impl {
     (Sf()) => {
         enc::Sf
     };
+    (Sf(Not)) => {
+        enc::NotSf
+    };
     (Gpr()) => {
         enc::Gpr
     };
@@ -17,6 +20,15 @@ macro_rules! _arg_encode_impl {
     (Gpr(AllowZr)) => {
         enc::GprOrZr
     };
+    (Gpr(AllowZr, Size = $size:literal)) => {
+        enc::SizedGpr<enc::GprOrZr, $size>
+    };
+    (Gpr(AllowSp, Size = $size:literal)) => {
+        enc::SizedGpr<enc::GprOrSp, $size>
+    };
+    (Gpr(AllowZr, Size = $size:literal, Default = $default:literal)) => {
+        enc::OrDefault<enc::SizedGpr<enc::GprOrZr, $size>, $default>
+    };
     (UImm($bits:literal, Align = $align:literal)) => {
         enc::UImmAlign<$bits, $align>
     };
@@ -32,23 +44,169 @@ macro_rules! _arg_encode_impl {
     (Shift(Kind)) => {
         enc::ShiftKind
     };
+    (Shift(Kind, AllowRor)) => {
+        enc::ShiftKindOrRor
+    };
     (Shift(Amount($bits:literal))) => {
         enc::ShiftAmount<$bits>
     };
+    (Shift(Amount, Size = $size:literal)) => {
+        enc::SizedShiftAmount<$size>
+    };
     (ShiftConst($kind:ident, $amt:literal)) => {
         enc::ShiftConst<{$crate::inst::operand::ShiftKind::$kind as u8}, $amt>
     };
     (Extend(Shift)) => {
         enc::ExtendLShift
     };
+    (Dpr()) => {
+        enc::Dpr
+    };
+    (Dpr(Size = $size:literal)) => {
+        enc::SizedDpr<$size>
+    };
+    (Ftype()) => {
+        enc::Ftype
+    };
+    (FpImm()) => {
+        enc::FpImm
+    };
+    (FpImm(Zero)) => {
+        enc::FpZero
+    };
+    (FpImm(Fmov)) => {
+        enc::FmovImm
+    };
+    (Vr()) => {
+        enc::Vr
+    };
+    (Vr(Size = $size:literal, Lanes = $lanes:literal)) => {
+        enc::ArrangedVr<$size, $lanes>
+    };
+    (Vr(Size = $size:literal)) => {
+        enc::SizedVr<$size>
+    };
+    (Q()) => {
+        enc::Q
+    };
+    (Q(Lower)) => {
+        enc::Half<false>
+    };
+    (Q(Upper)) => {
+        enc::Half<true>
+    };
+    (VecSize(Max = $max:literal)) => {
+        enc::VecSize<$max>
+    };
+    (FpVecSize()) => {
+        enc::FpVecSize
+    };
+    (FpElemSize()) => {
+        enc::FpElemSize
+    };
+    (PmullSize()) => {
+        enc::PmullSize
+    };
+    (VrList(Opcode)) => {
+        enc::ListOpcode
+    };
+    (VrList(TableLen)) => {
+        enc::TableLen
+    };
+    (Lane(Imm4)) => {
+        enc::LaneImm4
+    };
+    (Lane(High)) => {
+        enc::LaneHigh
+    };
+    (Lane(Vr)) => {
+        enc::LaneVr
+    };
     (Cond()) => {
         enc::Cond
     };
+    (Cond(Invert)) => {
+        enc::InvertedCond
+    };
+    (SysReg()) => {
+        enc::SysReg
+    };
+    (Barrier()) => {
+        enc::Barrier
+    };
+    (Barrier(Isb)) => {
+        enc::IsbBarrier
+    };
+    (BtiTarget()) => {
+        enc::BtiTarget
+    };
+    (LogicalImm(Size = $size:literal)) => {
+        enc::LogicalImm<$size, false>
+    };
+    (LogicalImm(Not, Size = $size:literal)) => {
+        enc::LogicalImm<$size, true>
+    };
+    (WideShift(Size = $size:literal)) => {
+        enc::WideShift<$size>
+    };
+    (MovImm(Size = $size:literal)) => {
+        enc::MovImm<$size>
+    };
+    (AdrImm()) => {
+        enc::AdrImm
+    };
+    (AddSubOp()) => {
+        enc::AddSubOp<false>
+    };
+    (AddSubOp(Sub)) => {
+        enc::AddSubOp<true>
+    };
+    (AddSubImm()) => {
+        enc::AddSubImm
+    };
+    (Lo12($name:ident $opts:tt)) => {
+        enc::Lo12<$crate::inst::meta_operand::_arg_encode_impl!($name $opts)>
+    };
+    (BitNum(Size = $size:literal)) => {
+        enc::BitNum<$size>
+    };
+    (BitfieldShift(Left, Size = $size:literal)) => {
+        enc::BitfieldShift<$size, true>
+    };
+    (BitfieldShift(Right, Size = $size:literal)) => {
+        enc::BitfieldShift<$size, false>
+    };
+    (TestBit(Low)) => {
+        enc::TestBitLow
+    };
     (Label($name:ident $opts:tt)) => {
         enc::Label<
             $crate::inst::meta_operand::_arg_encode_impl!($name $opts),
         >
     };
+    (Label($name:ident $opts:tt, Reloc = $reloc:ident)) => {
+        enc::Label<
+            $crate::inst::meta_operand::_arg_encode_impl!($name $opts),
+            { $crate::obj::RelocKind::$reloc as u32 },
+        >
+    };
+    (AddrLabel($name:ident $opts:tt, Reloc = $reloc:ident)) => {
+        enc::Label<
+            $crate::inst::meta_operand::_arg_encode_impl!($name $opts),
+            { $crate::obj::RelocKind::$reloc as u32 },
+        >
+    };
+    (AddrLabel($name:ident $opts:tt)) => {
+        enc::Label<
+            $crate::inst::meta_operand::_arg_encode_impl!($name $opts),
+        >
+    };
+    (Addr($part:ident)) => {
+        ::paste::paste!{ enc::[<Addr $part>] }
+    };
+    (Addr($part:ident, Scale = $scale:literal)) => {
+        ::paste::paste!{ enc::[<Addr $part>]<$scale> }
+    };
     ($ident:ident $opts:tt) => {
         compile_error!(concat!("unknown encode argument: ", stringify!($ident), stringify!($opts)));
     };
@@ -56,19 +214,271 @@ macro_rules! _arg_encode_impl {
 
 macro_rules! _arg_encode {
     (B($binary:literal) $s:tt $e:tt) => {
-        Ok($e.push(IntN(
+        Ok($e.push(::bit::IntN(
             $binary,
             $crate::inst::util::binary_string(stringify!($binary)),
         )))
     };
-    (Extend(Kind) $s:tt $e:tt $i:tt) => {
+    // extends the register in arg `$rm` to the size of the register in arg 0
+    (Extend(Kind, Rm = $rm:tt) $s:tt $e:tt $i:tt) => {
         (|| {
-            enc::ExtendKind::valid_width(&$s.$i, &$s.2)?;
-            let value = enc::ExtendKind::encode(&$s.$i, $e)?;
+            enc::ExtendKind::valid_width(&$s.$i, &$s.0, &$s.$rm)?;
+            let value = enc::ExtendKind::encode_sized(&$s.$i, &$s.0, $e)?;
             $e.push_n(value);
             Ok(())
         })()
     };
+    (TestBit(High) $s:tt $e:tt $i:tt) => {
+        (|| {
+            enc::TestBitHigh::valid_width(&$s.$i, &$s.0)?;
+            let value = <enc::TestBitHigh as Encoder<_>>::encode(&$s.$i, $e)?;
+            $e.push_n(value);
+            Ok(())
+        })()
+    };
+    // same size as the register in arg 0
+    (Gpr($allow:ident, Size = Sf) $s:tt $e:tt $i:tt) => {
+        match $s.0.size {
+            $crate::inst::operand::GprSize::B4 => {
+                $crate::inst::meta_operand::_arg_encode!(Gpr($allow, Size = 4) $s $e $i)
+            }
+            $crate::inst::operand::GprSize::B8 => {
+                $crate::inst::meta_operand::_arg_encode!(Gpr($allow, Size = 8) $s $e $i)
+            }
+        }
+    };
+    // mask of the same size as the register in arg 0
+    (LogicalImm(Size = Sf) $s:tt $e:tt $i:tt) => {
+        match $s.0.size {
+            $crate::inst::operand::GprSize::B4 => {
+                $crate::inst::meta_operand::_arg_encode!(LogicalImm(Size = 32) $s $e $i)
+            }
+            $crate::inst::operand::GprSize::B8 => {
+                $crate::inst::meta_operand::_arg_encode!(LogicalImm(Size = 64) $s $e $i)
+            }
+        }
+    };
+    (LogicalImm(Not, Size = Sf) $s:tt $e:tt $i:tt) => {
+        match $s.0.size {
+            $crate::inst::operand::GprSize::B4 => {
+                $crate::inst::meta_operand::_arg_encode!(LogicalImm(Not, Size = 32) $s $e $i)
+            }
+            $crate::inst::operand::GprSize::B8 => {
+                $crate::inst::meta_operand::_arg_encode!(LogicalImm(Not, Size = 64) $s $e $i)
+            }
+        }
+    };
+    (WideShift(Size = Sf) $s:tt $e:tt $i:tt) => {
+        match $s.0.size {
+            $crate::inst::operand::GprSize::B4 => {
+                $crate::inst::meta_operand::_arg_encode!(WideShift(Size = 4) $s $e $i)
+            }
+            $crate::inst::operand::GprSize::B8 => {
+                $crate::inst::meta_operand::_arg_encode!(WideShift(Size = 8) $s $e $i)
+            }
+        }
+    };
+    (MovImm(Size = Sf) $s:tt $e:tt $i:tt) => {
+        match $s.0.size {
+            $crate::inst::operand::GprSize::B4 => {
+                $crate::inst::meta_operand::_arg_encode!(MovImm(Size = 32) $s $e $i)
+            }
+            $crate::inst::operand::GprSize::B8 => {
+                $crate::inst::meta_operand::_arg_encode!(MovImm(Size = 64) $s $e $i)
+            }
+        }
+    };
+    // bit number in the register in arg 0
+    (BitNum(Size = Sf) $s:tt $e:tt $i:tt) => {
+        match $s.0.size {
+            $crate::inst::operand::GprSize::B4 => {
+                $crate::inst::meta_operand::_arg_encode!(BitNum(Size = 4) $s $e $i)
+            }
+            $crate::inst::operand::GprSize::B8 => {
+                $crate::inst::meta_operand::_arg_encode!(BitNum(Size = 8) $s $e $i)
+            }
+        }
+    };
+    (BitfieldShift($dir:ident, Size = Sf) $s:tt $e:tt $i:tt) => {
+        match $s.0.size {
+            $crate::inst::operand::GprSize::B4 => {
+                $crate::inst::meta_operand::_arg_encode!(BitfieldShift($dir, Size = 4) $s $e $i)
+            }
+            $crate::inst::operand::GprSize::B8 => {
+                $crate::inst::meta_operand::_arg_encode!(BitfieldShift($dir, Size = 8) $s $e $i)
+            }
+        }
+    };
+    // field with the lsb in arg `$i` and the width in arg `$w`, in the register in arg 0
+    (Bitfield($kind:ident, Width = $w:tt, Size = Sf) $s:tt $e:tt $i:tt) => {
+        (|| {
+            let value = match $s.0.size {
+                $crate::inst::operand::GprSize::B4 => {
+                    ::paste::paste!{ enc::[<Bitfield $kind>]::<4>::encode(&$s.$i, &$s.$w)? }
+                }
+                $crate::inst::operand::GprSize::B8 => {
+                    ::paste::paste!{ enc::[<Bitfield $kind>]::<8>::encode(&$s.$i, &$s.$w)? }
+                }
+            };
+            $e.push_n(value);
+            Ok(())
+        })()
+    };
+    // same size as the scalar register in arg 0
+    (Dpr(Size = Ftype) $s:tt $e:tt $i:tt) => {
+        match $s.0.size {
+            $crate::inst::operand::Size::B2 => {
+                $crate::inst::meta_operand::_arg_encode!(Dpr(Size = 2) $s $e $i)
+            }
+            $crate::inst::operand::Size::B4 => {
+                $crate::inst::meta_operand::_arg_encode!(Dpr(Size = 4) $s $e $i)
+            }
+            $crate::inst::operand::Size::B8 => {
+                $crate::inst::meta_operand::_arg_encode!(Dpr(Size = 8) $s $e $i)
+            }
+            _ => Err($crate::inst::Error::InvalidDprWidth),
+        }
+    };
+    // precision of arg `$i` of FCVT, which converts arg 1 to the other precision of arg 0
+    (Ftype(Convert) $s:tt $e:tt $i:tt) => {
+        (|| {
+            enc::Ftype::valid_convert(&$s.0, &$s.1)?;
+            let value = <enc::ConvertFtype as Encoder<_>>::encode(&$s.$i, $e)?;
+            $e.push_n(value);
+            Ok(())
+        })()
+    };
+    // precision of arg `$i` that FMOV moves to or from the general register in arg `$g`
+    (Ftype(Gpr = $g:tt) $s:tt $e:tt $i:tt) => {
+        (|| {
+            enc::Ftype::valid_move(&$s.$g, &$s.$i)?;
+            let value = <enc::Ftype as Encoder<_>>::encode(&$s.$i, $e)?;
+            $e.push_n(value);
+            Ok(())
+        })()
+    };
+    // set when `#0.0` is moved from XZR into the D register in arg `$d`
+    (FpImm(Sf, Dpr = $d:tt) $s:tt $e:tt $i:tt) => {
+        Ok($e.push_n(enc::FmovImm::sf(&$s.$d, &$s.$i)))
+    };
+    // same arrangement as the vector in arg 0
+    (Vr(Same) $s:tt $e:tt $i:tt) => {
+        (|| {
+            enc::Vr::valid_same(&$s.0, &$s.$i)?;
+            let value = <enc::Vr as Encoder<_>>::encode(&$s.$i, $e)?;
+            $e.push_n(value);
+            Ok(())
+        })()
+    };
+    // same arrangement as the vector in arg `$o`
+    (Vr(Same = $o:tt) $s:tt $e:tt $i:tt) => {
+        (|| {
+            enc::Vr::valid_same(&$s.$o, &$s.$i)?;
+            let value = <enc::Vr as Encoder<_>>::encode(&$s.$i, $e)?;
+            $e.push_n(value);
+            Ok(())
+        })()
+    };
+    // vector of elements twice the size of those in arg `$o`
+    (Vr(Long = $o:tt) $s:tt $e:tt $i:tt) => {
+        (|| {
+            enc::Vr::valid_long(&$s.$i, &$s.$o)?;
+            let value = <enc::Vr as Encoder<_>>::encode(&$s.$i, $e)?;
+            $e.push_n(value);
+            Ok(())
+        })()
+    };
+    // lane in arg `$i` that is the element size of the vector or lane in arg `$o`
+    (Lane(Imm5, Same = $o:tt) $s:tt $e:tt $i:tt) => {
+        (|| {
+            enc::LaneImm5::valid_size($s.$o.size, $s.$i.size)?;
+            let value = <enc::LaneImm5 as Encoder<_>>::encode(&$s.$i, $e)?;
+            $e.push_n(value);
+            Ok(())
+        })()
+    };
+    // lane in arg `$i` that is moved to or from the general register in arg `$g`
+    (Lane(Imm5, Gpr = $g:tt) $s:tt $e:tt $i:tt) => {
+        (|| {
+            enc::LaneImm5::valid_gpr($s.$i.size, &$s.$g)?;
+            let value = <enc::LaneImm5 as Encoder<_>>::encode(&$s.$i, $e)?;
+            $e.push_n(value);
+            Ok(())
+        })()
+    };
+    // lane in arg `$i` that `MOV` moves to the general register in arg `$g`
+    (Lane(Imm5, Mov = $g:tt) $s:tt $e:tt $i:tt) => {
+        (|| {
+            enc::LaneImm5::valid_mov($s.$i.size, &$s.$g)?;
+            let value = <enc::LaneImm5 as Encoder<_>>::encode(&$s.$i, $e)?;
+            $e.push_n(value);
+            Ok(())
+        })()
+    };
+    // low index bit of the lane in arg `$i`, that is the element size of the vector in arg `$o`
+    (Lane(Low, Same = $o:tt) $s:tt $e:tt $i:tt) => {
+        (|| {
+            enc::LaneImm5::valid_size($s.$o.size, $s.$i.size)?;
+            let value = <enc::LaneLow as Encoder<_>>::encode(&$s.$i, $e)?;
+            $e.push_n(value);
+            Ok(())
+        })()
+    };
+    // post-index of arg `$i` that has to be the size of the list in arg `$l`
+    (Addr(ListOffset, List = $l:tt) $s:tt $e:tt $i:tt) => {
+        (|| {
+            let value = enc::AddrListOffset::encode(&$s.$i, &$s.$l)?;
+            $e.push_n(value);
+            Ok(())
+        })()
+    };
+    // same size as the register in arg `$r`
+    (Gpr($allow:ident, SizeOf = $r:tt) $s:tt $e:tt $i:tt) => {
+        match $s.$r.size {
+            $crate::inst::operand::GprSize::B4 => {
+                $crate::inst::meta_operand::_arg_encode!(Gpr($allow, Size = 4) $s $e $i)
+            }
+            $crate::inst::operand::GprSize::B8 => {
+                $crate::inst::meta_operand::_arg_encode!(Gpr($allow, Size = 8) $s $e $i)
+            }
+        }
+    };
+    // register in arg `$i` that must not overlap any of the args `$o`
+    (Gpr($allow:ident, Size = $size:tt, Distinct = [$( $o:tt )*]) $s:tt $e:tt $i:tt) => {
+        (|| {
+            $( enc::Distinct::valid(&$s.$i, &$s.$o)?; )*
+            $crate::inst::meta_operand::_arg_encode!(Gpr($allow, Size = $size) $s $e $i)
+        })()
+    };
+    // first of the pair of registers in arg `$i` and arg `$p`
+    (Gpr($allow:ident, Size = $size:tt, Pair = $p:tt) $s:tt $e:tt $i:tt) => {
+        (|| {
+            enc::GprPair::valid(&$s.$i, &$s.$p)?;
+            $crate::inst::meta_operand::_arg_encode!(Gpr($allow, Size = $size) $s $e $i)
+        })()
+    };
+    // less than the width of the register in arg 0
+    (Shift(Amount, Size = Sf) $s:tt $e:tt $i:tt) => {
+        match $s.0.size {
+            $crate::inst::operand::GprSize::B4 => {
+                $crate::inst::meta_operand::_arg_encode!(Shift(Amount, Size = 4) $s $e $i)
+            }
+            $crate::inst::operand::GprSize::B8 => {
+                $crate::inst::meta_operand::_arg_encode!(Shift(Amount, Size = 8) $s $e $i)
+            }
+        }
+    };
+    // scaled by the size of the register in arg 0, 4 or 8 bytes
+    (Addr($part:ident, Scale = Sf) $s:tt $e:tt $i:tt) => {
+        match $s.0.size {
+            $crate::inst::operand::GprSize::B4 => {
+                $crate::inst::meta_operand::_arg_encode!(Addr($part, Scale = 2) $s $e $i)
+            }
+            $crate::inst::operand::GprSize::B8 => {
+                $crate::inst::meta_operand::_arg_encode!(Addr($part, Scale = 3) $s $e $i)
+            }
+        }
+    };
     ($ident:ident $opts:tt $s:tt $e:tt $( $i:tt )?) => {
         <$crate::inst::meta_operand::_arg_encode_impl!($ident $opts)>
             ::encode(&$s.$($i)?, $e).map(|v| $e.push_n(v))
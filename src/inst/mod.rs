@@ -1,15 +1,23 @@
 pub mod dec;
 mod def;
 pub mod dir;
+pub mod feature;
 mod meta;
 mod meta_operand;
 pub mod operand;
+mod pseudo;
+pub mod sysreg;
 mod util;
 
-pub use def::{get_variant_and_emit, narrow_variant, EncInstr, EncInstrSet, Mnemonic, Variant};
+pub use def::{
+    get_variant_and_emit, narrow_variant, required_features, EncInstr, EncInstrSet, Mnemonic,
+    Variant,
+};
+pub use feature::{Feature, TargetFeatures, UnknownName};
 pub use operand::{op, Ops};
-pub use util::{NarrowError, NarrowVariant};
+pub use util::{NarrowError, NarrowVariant, Param};
 
+use crate::enum_str::EnumStr;
 use bit::{BitCt, Int, IntN, IntOfBits};
 
 #[derive(Debug)]
@@ -17,14 +25,98 @@ pub enum Error {
     Required,
     Resolve,
     Unexpected,
-    OutOfRange,
-    NotAligned,
+    /// value is not `min` to `max`, both included
+    OutOfRange {
+        min: i64,
+        max: i64,
+    },
+    /// value is not a multiple of `align`
+    NotAligned {
+        align: u32,
+    },
     InvalidGpr,
     MismatchedConstShift,
     InvalidExtendWidth,
+    InvalidGprWidth,
+    /// shift that the instruction does not take, like ROR for ADD
+    InvalidShiftKind,
+    /// address is pre or post-indexed, but the instruction only takes an offset
+    InvalidIndexMode,
+    /// immediate is not a repeating pattern of rotated ones, so it can't be a bitmask
+    InvalidLogicalImm,
+    /// immediate can't be moved by one MOVZ, MOVN or ORR, `LDI` can load it
+    InvalidMovImm,
+    /// instruction picks its expansion from the value, so it must be known
+    NotConstant,
+    /// AL or NV, which the aliases of conditional select can't invert
+    InvalidCond,
+    /// MRS and MSR only access registers with op0 2 or 3
+    InvalidSysReg,
+    /// ISB only takes SY
+    InvalidBarrier,
+    /// scalar register is not H, S or D, or not the size the other operands need
+    InvalidDprWidth,
+    /// vector arrangement that the instruction doesn't take, or that doesn't match the other
+    /// operands
+    InvalidArrangement,
+    /// FMOV immediate that is not `±n / 16 * 2^r`, n 16 to 31 and r -3 to 4,
+    /// or FCMP with anything but `#0.0`
+    InvalidFpImm,
+    /// register that has to differ from another operand, like the status register of STXR and
+    /// the data or base registers
+    OverlappingGpr,
+    /// CASP registers that are not an even register followed by the next one
+    InvalidGprPair,
+    /// `:lo12:` on an immediate that is not the offset of `ADD` or a load or store
+    InvalidReloc,
+    /// operands that need an extension the target does not have, like an H register without FP16
+    MissingFeature(Feature),
     UnmatchedVariant,
 }
 
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Required => f.write_str("operand is missing"),
+            Self::Resolve => f.write_str("label is not defined"),
+            Self::Unexpected => f.write_str("unexpected operand"),
+            Self::OutOfRange { min, max } if min == max => write!(f, "value must be {}", min),
+            Self::OutOfRange { min, max } => write!(f, "value must be {} to {}", min, max),
+            Self::NotAligned { align } => write!(f, "value must be a multiple of {}", align),
+            Self::InvalidGpr => f.write_str("register can't be used here"),
+            Self::MismatchedConstShift => f.write_str("shift is not one the instruction takes"),
+            Self::InvalidExtendWidth => f.write_str("extend doesn't match the register width"),
+            Self::InvalidGprWidth => f.write_str("register has the wrong width"),
+            Self::InvalidShiftKind => f.write_str("shift kind can't be used here"),
+            Self::InvalidIndexMode => f.write_str("address can't be pre or post-indexed"),
+            Self::InvalidLogicalImm => f.write_str("value is not a valid bitmask immediate"),
+            Self::InvalidMovImm => {
+                f.write_str("value can't be moved by one instruction, `ldi` can load it")
+            }
+            Self::NotConstant => f.write_str("value must not depend on a label defined later"),
+            Self::InvalidCond => f.write_str("condition can't be `al` or `nv`"),
+            Self::InvalidSysReg => f.write_str("system register can't be accessed by MRS or MSR"),
+            Self::InvalidBarrier => f.write_str("barrier option must be `sy`"),
+            Self::InvalidDprWidth => f.write_str("scalar register has the wrong size"),
+            Self::InvalidArrangement => f.write_str("vector arrangement can't be used here"),
+            Self::InvalidFpImm => f.write_str(
+                "value must be ±n / 16 * 2^r, n 16 to 31 and r -3 to 4, or `#0.0` for a compare",
+            ),
+            Self::OverlappingGpr => f.write_str("register must differ from the other operands"),
+            Self::InvalidGprPair => {
+                f.write_str("registers must be an even register followed by the next one")
+            }
+            Self::InvalidReloc => f.write_str("`:lo12:` can't be used here"),
+            Self::MissingFeature(feature) => {
+                write!(f, "requires the `{}` extension", feature.to_str_lower())
+            }
+            Self::UnmatchedVariant => {
+                f.write_str("operands don't match any form of the instruction")
+            }
+        }
+    }
+}
+
 /// Error and the arg index that caused error
 #[derive(Debug)]
 pub struct ErrorIdx(Error, u8);
@@ -33,6 +125,13 @@ pub enum Error {
 #[derive(Debug)]
 pub struct ErrorMacro(pub Error, pub &'static str);
 
+impl ErrorMacro {
+    /// index of the operand the field encodes, `None` if it encodes several or none
+    pub fn operand(&self) -> Option<usize> {
+        self.1.rsplit_once(": ")?.1.parse().ok()
+    }
+}
+
 pub type FixupFn<E, Value> = fn(Value, &mut E) -> Result<IntN, Error>;
 
 pub struct Fixup<E: Emitter + ?Sized, Key, Value> {
@@ -40,6 +139,8 @@ pub struct Fixup<E: Emitter + ?Sized, Key, Value> {
     bit_idx: u8,
     pc: u64,
     encode_fn: FixupFn<E, Value>,
+    /// left to the linker if the label is not defined, or always if it is not pc relative
+    reloc: Option<crate::obj::RelocKind>,
 }
 
 impl<E: Emitter + ?Sized, Key: std::fmt::Debug, Value> std::fmt::Debug for Fixup<E, Key, Value> {
@@ -59,12 +160,33 @@ fn clone(&self) -> Self {
             bit_idx: self.bit_idx,
             pc: self.pc,
             encode_fn: self.encode_fn,
+            reloc: self.reloc,
         }
     }
 }
 
 crate::typed_interner! { label; Key32 }
 
+/// operand expression that references a label before it was defined,
+/// evaluated again by the emitter when the fixup is applied
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExprKey(pub u32);
+
+impl<E: Emitter + ?Sized, Key: Copy, Value> Fixup<E, Key, Value> {
+    pub fn key(&self) -> Key {
+        self.key
+    }
+
+    /// address of the instruction
+    pub fn pc(&self) -> u64 {
+        self.pc
+    }
+
+    pub fn reloc(&self) -> Option<crate::obj::RelocKind> {
+        self.reloc
+    }
+}
+
 pub fn apply_label_fixup<E: Emitter>(
     e: &mut E,
     fixup: Fixup<E, label::Key, u64>,
@@ -76,6 +198,17 @@ pub fn apply_label_fixup<E: Emitter>(
     Ok(())
 }
 
+pub fn apply_expr_fixup<E: Emitter>(
+    e: &mut E,
+    fixup: Fixup<E, ExprKey, i64>,
+) -> Result<(), Error> {
+    let value = e.resolve_expr(fixup.key).ok_or(Error::Resolve)?;
+    e.set_pc(fixup.pc);
+    let encode = (fixup.encode_fn)(value, e)?;
+    e.insert(encode, fixup.bit_idx);
+    Ok(())
+}
+
 pub fn create_fixup<Key, Value, E: Emitter>(
     e: &E,
     key: Key,
@@ -86,6 +219,19 @@ pub fn create_fixup<Key, Value, E: Emitter>(
         bit_idx: e.bit_idx(),
         pc: e.pc(),
         encode_fn: fixup_fn,
+        reloc: None,
+    }
+}
+
+pub fn create_reloc_fixup<E: Emitter>(
+    e: &E,
+    key: label::Key,
+    fixup_fn: FixupFn<E, u64>,
+    reloc: crate::obj::RelocKind,
+) -> Fixup<E, label::Key, u64> {
+    Fixup {
+        reloc: Some(reloc),
+        ..create_fixup(e, key, fixup_fn)
     }
 }
 
@@ -101,6 +247,19 @@ pub trait Emitter {
 
     fn resolve_label(&mut self, key: label::Key) -> Option<u64>;
     fn push_label_fixup(&mut self, fixup: Fixup<Self, label::Key, u64>);
+
+    fn resolve_expr(&mut self, key: ExprKey) -> Option<i64>;
+    fn push_expr_fixup(&mut self, fixup: Fixup<Self, ExprKey, i64>);
+
+    /// `false` if the symbol is already defined and can't be assigned again
+    fn define_symbol(&mut self, key: label::Key, value: i64, reassignable: bool) -> bool;
+    fn set_global(&mut self, key: label::Key);
+
+    /// instruction or directive being emitted
+    fn span(&self) -> crate::code::Span;
+    fn conds(&mut self) -> &mut dir::CondStack;
+    /// extensions the target has
+    fn features(&mut self) -> &mut TargetFeatures;
 }
 
 pub struct Addr<const ALIGN: usize>(usize);
@@ -1,9 +1,9 @@
 use super::{
-    create_fixup, label,
+    create_fixup, create_reloc_fixup, label,
     util::{MaybeDisplay, OptionDisplay},
-    Emitter, Error,
+    Emitter, Error, ExprKey, Feature, TargetFeatures,
 };
-use crate::{enum_str::EnumStr, simpl, simpls};
+use crate::{enum_str::EnumStr, obj::RelocKind, simpl, simpls};
 use bit::{
     sint_in_range, sint_low_zeros, uint_in_range, uint_low_zeros, Align, BitCt, Int, IntN,
     IntOfBits,
@@ -17,14 +17,25 @@
 pub enum Kind {
     Gpr,
     Dpr,
+    Vr,
+    IdxVr,
+    VrList,
     Imm,
+    FpImm,
     Label,
     AddrImm,
     AddrReg,
+    AddrPostReg,
     AddrLabel,
     Shift,
     Extend,
     Cond,
+    SysReg,
+    Barrier,
+    BtiTarget,
+    ArchExtension,
+    Arch,
+    Cpu,
     Error,
 }
 
@@ -39,8 +50,55 @@ pub enum Ops {
         reg: u8,
         size: Size,
     },
-    Imm(i64),
+    /// `v0.4s`, elements of `size` that fill `lanes`, a 64 or 128-bit vector
+    Vr {
+        reg: u8,
+        size: VecSize,
+        lanes: VecLanes,
+    },
+    /// `v0.s[1]`, one element of a vector register
+    IdxVr {
+        reg: u8,
+        size: VecSize,
+        index: u8,
+    },
+    /// `{v0.4s-v3.4s}`, `count` consecutive registers from `reg`, v0 follows v31
+    VrList {
+        reg: u8,
+        count: u8,
+        size: VecSize,
+        lanes: VecLanes,
+    },
+    Imm {
+        value: i64,
+        /// expression that references a label that was not yet defined,
+        /// `value` is a placeholder until the fixup is applied
+        deferred: Option<ExprKey>,
+        /// `:lo12:label`, the low 12 bits of the address of the label, filled in by the linker
+        lo12: Option<label::Key>,
+    },
+    /// `#1.5`, encoded in 8 bits by FMOV or compared with by FCMP
+    FpImm(f64),
     Label(label::Key),
+    /// `[xN, #imm]`, `[xN, #imm]!` or `[xN], #imm`
+    AddrImm {
+        base: GprKind,
+        offset: op::Imm,
+        mode: IndexMode,
+    },
+    /// `[xN, xM]`, `[xN, xM, LSL #3]` or `[xN, wM, SXTW #2]`
+    AddrReg {
+        base: GprKind,
+        index: op::Gpr,
+        extend: Option<op::Extend>,
+    },
+    /// `[xN], xM`, post-indexed by a register, only LD1 and ST1 take it
+    AddrPostReg {
+        base: GprKind,
+        index: op::Gpr,
+    },
+    /// pc relative literal, `LDR x0, label`
+    AddrLabel(label::Key),
     Shift {
         kind: ShiftKind,
         amount: u8,
@@ -50,6 +108,21 @@ pub enum Ops {
         left_shift_amount: Option<u8>,
     },
     Cond(CondKind),
+    /// `op0:op1:CRn:CRm:op2` of a named system register or `S3_3_C13_C0_2`
+    SysReg(u16),
+    /// option of DMB, DSB and ISB
+    Barrier(BarrierKind),
+    /// branches that BTI lets land on it
+    BtiTarget(BtiKind),
+    /// `crc` or `nocrc` of `.arch_extension`
+    ArchExtension {
+        features: TargetFeatures,
+        enable: bool,
+    },
+    /// `armv8.2-a+crc` of `.arch`, the features of the architecture and its extensions
+    Arch(TargetFeatures),
+    /// `cortex-a53+crypto` of `.cpu`, the features of the processor and its extensions
+    Cpu(TargetFeatures),
     Error,
 }
 pub use operands as op;
@@ -59,16 +132,51 @@ pub fn kind(&self) -> Kind {
         match self {
             Ops::Gpr { .. } => Kind::Gpr,
             Ops::Dpr { .. } => Kind::Dpr,
-            Ops::Imm(..) => Kind::Imm,
+            Ops::Vr { .. } => Kind::Vr,
+            Ops::IdxVr { .. } => Kind::IdxVr,
+            Ops::VrList { .. } => Kind::VrList,
+            Ops::Imm { .. } => Kind::Imm,
+            Ops::FpImm(..) => Kind::FpImm,
             Ops::Label(..) => Kind::Label,
+            Ops::AddrImm { .. } => Kind::AddrImm,
+            Ops::AddrReg { .. } => Kind::AddrReg,
+            Ops::AddrPostReg { .. } => Kind::AddrPostReg,
+            Ops::AddrLabel(..) => Kind::AddrLabel,
             Ops::Shift { .. } => Kind::Shift,
             Ops::Extend { .. } => Kind::Extend,
             Ops::Cond { .. } => Kind::Cond,
+            Ops::SysReg(..) => Kind::SysReg,
+            Ops::Barrier(..) => Kind::Barrier,
+            Ops::BtiTarget(..) => Kind::BtiTarget,
+            Ops::ArchExtension { .. } => Kind::ArchExtension,
+            Ops::Arch(..) => Kind::Arch,
+            Ops::Cpu(..) => Kind::Cpu,
             Ops::Error => Kind::Error,
         }
     }
 }
 
+impl op::Imm {
+    pub const fn new(value: i64) -> Self {
+        Self {
+            value,
+            deferred: None,
+            lo12: None,
+        }
+    }
+}
+
+/// how an address updates its base register
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndexMode {
+    /// `[xN, #imm]`, not updated
+    Offset,
+    /// `[xN, #imm]!`, updated before the access
+    Pre,
+    /// `[xN], #imm`, updated after the access
+    Post,
+}
+
 /// size in bytes
 #[subenum(GprSize, VecSize, VecLanes)]
 #[derive(PartialEq, Eq, Clone, Copy, Debug)]
@@ -86,7 +194,7 @@ pub enum Size {
     #[subenum(VecSize, GprSize, VecLanes)]
     B8 = 8,
     /// vector, 128 bits, aka scalar `Qn` or vector `Vn`
-    #[subenum(VecLanes)]
+    #[subenum(VecSize, VecLanes)]
     B16 = 16,
 }
 
@@ -104,6 +212,7 @@ pub enum ShiftKind {
         LSL = 0b00, // logical shift left
         LSR = 0b01, // logical shift right (zeros)
         ASR = 0b10, // arithmetic shift right (maintain sign)
+        ROR = 0b11, // rotate right, only for logical instructions
     }
 }
 
@@ -167,6 +276,48 @@ pub enum CondKind {
     }
 }
 
+crate::enum_str! {
+    /// shareability domain and accesses that a barrier orders
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[repr(u8)]
+    pub enum BarrierKind {
+        OSHLD = 0b0001, // outer shareable, loads
+        OSHST = 0b0010, // outer shareable, stores
+        OSH = 0b0011,   // outer shareable
+        NSHLD = 0b0101, // non-shareable, loads
+        NSHST = 0b0110, // non-shareable, stores
+        NSH = 0b0111,   // non-shareable
+        ISHLD = 0b1001, // inner shareable, loads
+        ISHST = 0b1010, // inner shareable, stores
+        ISH = 0b1011,   // inner shareable
+        LD = 0b1101,    // full system, loads
+        ST = 0b1110,    // full system, stores
+        SY = 0b1111,    // full system
+    }
+}
+
+crate::enum_str! {
+    /// indirect branches that BTI accepts, none if there is no target
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[repr(u8)]
+    pub enum BtiKind {
+        C = 0b01,  // calls, BLR
+        J = 0b10,  // jumps, BR
+        JC = 0b11, // both
+    }
+}
+
+impl CondKind {
+    /// opposite condition, the low bit flips it,
+    /// `None` for AL and NV which are both always true
+    pub fn invert(self) -> Option<Self> {
+        match self {
+            Self::AL | Self::NV => None,
+            cond => Self::try_from(cond as u8 ^ 1).ok(),
+        }
+    }
+}
+
 impl TryFrom<u8> for CondKind {
     type Error = ();
     fn try_from(value: u8) -> Result<Self, Self::Error> {
@@ -178,27 +329,6 @@ fn try_from(value: u8) -> Result<Self, Self::Error> {
     }
 }
 
-//     // data processing register, aka scalar register
-//     pub struct Dpr {
-//         pub reg: Reg,
-//         pub size: Size,
-//     }
-
-//     // simd vector register
-//     pub struct Vr {
-//         pub reg: Reg,
-//         pub size: VecSize,
-//         pub lanes: VecLanes,
-//     }
-
-//     // index scalar in vector register
-//     pub struct IdxVr {
-//         pub reg: Reg,
-//         pub size: VecSize,
-//         // index infers the lanes
-//         pub index: u8,
-//     }
-
 pub trait Encoder<T> {
     type Int: IntOfBits<u32>;
     fn encode<E: Emitter>(v: &T, e: &mut E) -> Result<Self::Int, Error>;
@@ -231,17 +361,150 @@ pub mod enc {
     pub struct GprOrZr;
     pub struct SImm<const BITS: BitCt>;
     pub struct UImm<const BITS: BitCt>;
+    /// LSL, LSR or ASR
     pub struct ShiftKind;
+    /// also ROR
+    pub struct ShiftKindOrRor;
     pub struct ShiftAmount<const BITS: BitCt>;
+    /// amount less than the width of a `SIZE` byte register
+    pub struct SizedShiftAmount<const SIZE: u8>;
     pub struct ShiftConst<const KIND: u8, const AMT: u8>;
     pub struct ExtendKind;
     pub struct ExtendLShift;
     pub struct Sf;
+    /// `1` for a W register, the opposite of `Sf`
+    pub struct NotSf;
+    /// register that has to be `SIZE` bytes, 4 for W or 8 for X
+    pub struct SizedGpr<EC, const SIZE: u8>(pub PhantomData<EC>);
+    /// register that must not overlap the other operands it is checked against
+    pub struct Distinct;
+    /// even register that is followed by the next register, like the pairs of CASP
+    pub struct GprPair;
+    /// optional operand that is encoded as `DEFAULT` when left out
+    pub struct OrDefault<EC, const DEFAULT: u32>(pub PhantomData<EC>);
     /// signed immediate that is right shifted by `RS` and then stored in `BITS` bits
     pub struct SImmAlign<const BITS: BitCt, const RS: BitCt>;
     pub struct UImmAlign<const BITS: BitCt, const RS: BitCt>;
-    pub struct Label<EC>(pub PhantomData<EC>);
+    /// `RELOC` is the `obj::RelocKind` the linker applies if the label is not defined,
+    /// 0 if the label has to be defined
+    pub struct Label<EC, const RELOC: u32 = 0>(pub PhantomData<EC>);
+    /// `immlo:10000:immhi`, the byte offset of `ADR` is split around the opcode
+    pub struct AdrImm;
+    /// `:lo12:label` in `ADD`, otherwise the immediate is encoded with `EC`
+    pub struct Lo12<EC>(pub PhantomData<EC>);
+    /// `op` of add and subtract by an immediate, a negative one flips it to the other
+    pub struct AddSubOp<const SUB: bool>;
+    /// `imm12` of add and subtract, the magnitude of a negative immediate
+    pub struct AddSubImm;
     pub struct Cond;
+    /// inverse of the condition, for the aliases of conditional select
+    pub struct InvertedCond;
+    /// `o0:op1:CRn:CRm:op2`, op0 is 2 or 3 so only its low bit is encoded
+    pub struct SysReg;
+    /// `CRm` of DMB and DSB
+    pub struct Barrier;
+    /// `CRm` of ISB, SY if there is no option
+    pub struct IsbBarrier;
+    /// high bits of `op2` of BTI, zero if there is no target
+    pub struct BtiTarget;
+    /// `0:imm9:MODE`, the unscaled offset and index mode of an address
+    pub struct SImmIndex<const MODE: u8>;
+    /// `b5`, high bit of the bit number to test
+    pub struct TestBitHigh;
+    /// `b40`, low 5 bits of the bit number to test
+    pub struct TestBitLow;
+    /// `N:immr:imms`, a `SIZE` bit mask, inverted first if `NOT`
+    pub struct LogicalImm<const SIZE: u8, const NOT: bool>;
+    /// `hw`, LSL by a multiple of 16 less than the width of a `SIZE` byte register
+    pub struct WideShift<const SIZE: u8>;
+    /// everything but `sf` and `Rd` of the MOVZ, MOVN or ORR that moves a `SIZE` bit immediate
+    pub struct MovImm<const SIZE: u8>;
+    /// `immr`, `imms` or `lsb`, a bit number in a `SIZE` byte register
+    pub struct BitNum<const SIZE: u8>;
+    /// `immr:imms` of the bitfield move that is LSL, or LSR and ASR, by an immediate
+    pub struct BitfieldShift<const SIZE: u8, const LEFT: bool>;
+    /// `immr:imms` of UBFX, SBFX and BFXIL, the field at `lsb` is moved to bit 0
+    pub struct BitfieldExtract<const SIZE: u8>;
+    /// `immr:imms` of BFI, UBFIZ and SBFIZ, the field at bit 0 is moved to `lsb`
+    pub struct BitfieldInsert<const SIZE: u8>;
+    /// scalar FP register, `Bn` to `Qn`
+    pub struct Dpr;
+    /// scalar FP register that has to be `SIZE` bytes, 2 for H, 4 for S or 8 for D
+    pub struct SizedDpr<const SIZE: u8>;
+    /// `ftype` of an H, S or D register, `11`, `00` or `01`, H needs FP16
+    pub struct Ftype;
+    /// `ftype` or `opc` of FCVT, which converts to and from H without FP16
+    pub struct ConvertFtype;
+    /// `imm8` of FMOV, `sign:exp3:frac4`
+    pub struct FpImm;
+    /// `imm8:100:00000` of FMOV, or `00:111:000000:11111` for `#0.0`, which is moved from ZR
+    pub struct FmovImm;
+    /// `Rm` of FCMP with `#0.0`, which is always zero
+    pub struct FpZero;
+    /// SIMD register, of a vector, a lane or the first of a list
+    pub struct Vr;
+    /// vector that has to be arranged as `LANES` bytes of `SIZE` byte elements
+    pub struct ArrangedVr<const SIZE: u8, const LANES: u8>;
+    /// vector of `SIZE` byte elements, 64 or 128 bits
+    pub struct SizedVr<const SIZE: u8>;
+    /// `Q`, set for a 128-bit vector, a vector of one `1d` or `1q` is reserved
+    pub struct Q;
+    /// `Q` of a long instruction, set for the `2` form that reads the upper half
+    pub struct Half<const UPPER: bool>;
+    /// `size` of the elements, at most `MAX` bytes
+    pub struct VecSize<const MAX: u8>;
+    /// `sz` of a vector of S or D floats
+    pub struct FpVecSize;
+    /// `size` of an FP instruction by element, `00` for H, which needs FP16, or `1:sz`
+    pub struct FpElemSize;
+    /// `size` of PMULL, `00` for bytes and `11` for the D to `1q` form, which needs AES
+    pub struct PmullSize;
+    /// `opcode` of LD1 and ST1, the number of registers in the list
+    pub struct ListOpcode;
+    /// `len` of TBL, one to four 16b registers
+    pub struct TableLen;
+    /// `imm5`, the size of a lane and its index above the lowest set bit,
+    /// lane 0 for a whole vector
+    pub struct LaneImm5;
+    /// `imm4` of INS, the index of the source lane
+    pub struct LaneImm4;
+    /// `L` of an instruction by element, the low bit of the index of an S lane or the middle
+    /// bit of that of an H
+    pub struct LaneLow;
+    /// `H` of an instruction by element, the high bit of the index of an H or S lane or that of
+    /// a D
+    pub struct LaneHigh;
+    /// `M:Rm` of an instruction by element, an H lane only has `v0` to `v15` and `M` is the low
+    /// bit of its index
+    pub struct LaneVr;
+
+    /// `Rn`, the base register of an address
+    pub struct AddrBase;
+    /// `Rn` of an address that can't have an offset other than `#0`, like that of LDXR
+    pub struct AddrBaseOnly;
+    /// set if the offset fits the unsigned `imm12` that is scaled by the access size,
+    /// `SCALE` is log2 of the access size in bytes
+    pub struct AddrScaled<const SCALE: BitCt>;
+    /// scaled `imm12` if `AddrScaled` is set, otherwise `SImmIndex`
+    pub struct AddrOffset<const SCALE: BitCt>;
+    /// offset of `LDUR` and `STUR`, never scaled
+    pub struct AddrUnscaled;
+    /// `Rm`, the index register of an address
+    pub struct AddrIndex;
+    /// `option`, how the index register is extended
+    pub struct AddrExtend;
+    /// `S`, set if the index register is shifted by the access size
+    pub struct AddrShift<const SCALE: BitCt>;
+    /// index mode of a pair, offset, pre or post-indexed
+    pub struct AddrPairMode;
+    /// index mode of a non-temporal pair, which can only have an offset
+    pub struct AddrNoWriteback;
+    /// signed `imm7` scaled by the access size
+    pub struct AddrPairOffset<const SCALE: BitCt>;
+    /// post-index bit of LD1 and ST1, which can't be pre-indexed
+    pub struct AddrListMode;
+    /// `Rm` of LD1 and ST1, zero without an offset or all ones for a post-index by the list size
+    pub struct AddrListOffset;
 }
 
 fn fixup_label_fn<E: Emitter, EC: Encoder<Option<op::Imm>>>(
@@ -250,11 +513,49 @@ fn fixup_label_fn<E: Emitter, EC: Encoder<Option<op::Imm>>>(
 ) -> Result<IntN, Error> {
     let current_instr_addr = e.pc() as i64;
     let offset = label_addr as i64 - current_instr_addr;
-    let value = EC::encode(&Some(op::Imm(offset)), e);
+    let value = EC::encode(&Some(op::Imm::new(offset)), e);
     let n = <EC::Int as IntOfBits<u32>>::N as u8;
     value.map(|v| IntN(v.get(), n))
 }
 
+fn fixup_imm_fn<E: Emitter, EC: ImmEncoder>(value: i64, e: &mut E) -> Result<IntN, Error> {
+    let n = <EC::Int as IntOfBits<u32>>::N as u8;
+    EC::encode_imm(value, e).map(|v| IntN(v.get(), n))
+}
+
+/// encoder of an immediate value,
+/// a deferred `op::Imm` is encoded as zero and fixed up once its expression resolves
+pub trait ImmEncoder {
+    type Int: IntOfBits<u32>;
+    fn encode_imm<E: Emitter>(v: i64, e: &mut E) -> Result<Self::Int, Error>;
+}
+
+impl<EC: ImmEncoder> Encoder<op::Imm> for EC {
+    type Int = EC::Int;
+    fn encode<E: Emitter>(v: &op::Imm, e: &mut E) -> Result<Self::Int, Error> {
+        if v.lo12.is_some() {
+            return Err(Error::InvalidReloc);
+        }
+        match v.deferred {
+            None => EC::encode_imm(v.value, e),
+            Some(key) => {
+                let fixup = create_fixup(e, key, fixup_imm_fn::<E, EC>);
+                e.push_expr_fixup(fixup);
+                Ok(Self::Int::new(0))
+            }
+        }
+    }
+}
+impl<EC: ImmEncoder> Encoder<Option<op::Imm>> for EC {
+    type Int = EC::Int;
+    fn encode<E: Emitter>(v: &Option<op::Imm>, e: &mut E) -> Result<Self::Int, Error> {
+        match v {
+            Some(v) => <EC as Encoder<op::Imm>>::encode(v, e),
+            None => Ok(Self::Int::new(0)),
+        }
+    }
+}
+
 impl Encoder<op::Gpr> for enc::Gpr {
     type Int = Int<5>;
     fn encode<E: Emitter>(v: &op::Gpr, _: &mut E) -> Result<Self::Int, Error> {
@@ -302,7 +603,41 @@ fn encode<E: Emitter>(v: &op::Gpr, _: &mut E) -> Result<Self::Int, Error> {
         Ok(Int((v.size == GprSize::B8) as u32))
     }
 }
+impl Encoder<op::Gpr> for enc::NotSf {
+    type Int = Int<1>;
+    fn encode<E: Emitter>(v: &op::Gpr, _: &mut E) -> Result<Self::Int, Error> {
+        Ok(Int((v.size == GprSize::B4) as u32))
+    }
+}
+impl<EC: Encoder<op::Gpr>, const SIZE: u8> Encoder<op::Gpr> for enc::SizedGpr<EC, SIZE> {
+    type Int = EC::Int;
+    fn encode<E: Emitter>(v: &op::Gpr, e: &mut E) -> Result<Self::Int, Error> {
+        if v.size as u8 == SIZE {
+            EC::encode(v, e)
+        } else {
+            Err(Error::InvalidGprWidth)
+        }
+    }
+}
+impl<T, EC: Encoder<T>, const DEFAULT: u32> Encoder<Option<T>> for enc::OrDefault<EC, DEFAULT> {
+    type Int = EC::Int;
+    fn encode<E: Emitter>(v: &Option<T>, e: &mut E) -> Result<Self::Int, Error> {
+        match v {
+            Some(v) => EC::encode(v, e),
+            None => Ok(Self::Int::new(DEFAULT)),
+        }
+    }
+}
 impl Encoder<op::Shift> for enc::ShiftKind {
+    type Int = Int<2>;
+    fn encode<E: Emitter>(v: &op::Shift, _: &mut E) -> Result<Self::Int, Error> {
+        match v.kind {
+            ShiftKind::ROR => Err(Error::InvalidShiftKind),
+            kind => Ok(Int(kind as u32)),
+        }
+    }
+}
+impl Encoder<op::Shift> for enc::ShiftKindOrRor {
     type Int = Int<2>;
     fn encode<E: Emitter>(v: &op::Shift, _: &mut E) -> Result<Self::Int, Error> {
         Ok(Int(v.kind as u32))
@@ -312,80 +647,573 @@ impl<const BITS: BitCt> Encoder<op::Shift> for enc::ShiftAmount<BITS> {
     type Int = Int<BITS>;
     fn encode<E: Emitter>(v: &op::Shift, _: &mut E) -> Result<Self::Int, Error> {
         debug_assert!(BITS <= 32);
-        Ok(Int(v.amount as u32))
+        if uint_in_range::<BITS>(v.amount as u64) {
+            Ok(Int(v.amount as u32))
+        } else {
+            Err(uint_range(BITS))
+        }
     }
 }
-impl<EC: Encoder<Option<op::Imm>>> Encoder<op::Label> for enc::Label<EC> {
+impl<const SIZE: u8> Encoder<op::Shift> for enc::SizedShiftAmount<SIZE> {
+    type Int = Int<6>;
+    fn encode<E: Emitter>(v: &op::Shift, _: &mut E) -> Result<Self::Int, Error> {
+        if v.amount < SIZE * 8 {
+            Ok(Int(v.amount as u32))
+        } else {
+            Err(Error::OutOfRange {
+                min: 0,
+                max: SIZE as i64 * 8 - 1,
+            })
+        }
+    }
+}
+impl<EC: Encoder<Option<op::Imm>>, const RELOC: u32> Encoder<op::Label> for enc::Label<EC, RELOC> {
     type Int = EC::Int;
     fn encode<E: Emitter>(v: &op::Label, e: &mut E) -> Result<Self::Int, Error> {
-        match e.resolve_label(v.0) {
+        let reloc = RelocKind::try_from(RELOC).ok();
+        // whether a label is global is only known at the end, so anything the linker could
+        // relocate waits until then
+        let label_addr = match reloc {
+            Some(_) => None,
+            None => e.resolve_label(v.0),
+        };
+        match label_addr {
             Some(label_addr) => fixup_label_fn::<E, EC>(label_addr, e).map(|v| Self::Int::new(v.0)),
             None => {
-                let fixup = create_fixup(e, v.0, fixup_label_fn::<E, EC>);
+                let fixup = match reloc {
+                    Some(kind) => create_reloc_fixup(e, v.0, fixup_label_fn::<E, EC>, kind),
+                    None => create_fixup(e, v.0, fixup_label_fn::<E, EC>),
+                };
                 e.push_label_fixup(fixup);
                 EC::encode(&None, e)
             }
         }
     }
 }
-impl<const BITS: BitCt> Encoder<i64> for enc::SImm<BITS> {
-    type Int = Int<BITS>;
-    fn encode<E: Emitter>(v: &i64, _: &mut E) -> Result<Self::Int, Error> {
-        if sint_in_range::<BITS>(*v) {
-            Ok(Int(*v as u32))
-        } else {
-            Err(Error::OutOfRange)
+impl Encoder<Option<op::Imm>> for enc::AdrImm {
+    type Int = Int<26>;
+    fn encode<E: Emitter>(v: &Option<op::Imm>, e: &mut E) -> Result<Self::Int, Error> {
+        let imm = match v {
+            Some(v) => <enc::SImm<21> as Encoder<op::Imm>>::encode(v, e)?.0 & 0x1fffff,
+            None => 0,
+        };
+        Ok(Int((imm & 0b11) << 24 | 0b10000 << 19 | imm >> 2))
+    }
+}
+/// the linker fills in `:lo12:`, even if the label is defined the address is not known yet
+fn push_lo12_fixup<E: Emitter>(e: &mut E, key: label::Key, reloc: RelocKind) {
+    let fixup = create_reloc_fixup(e, key, |_, _| Err(Error::Resolve), reloc);
+    e.push_label_fixup(fixup);
+}
+impl<EC: Encoder<op::Imm>> Encoder<op::Imm> for enc::Lo12<EC> {
+    type Int = EC::Int;
+    fn encode<E: Emitter>(v: &op::Imm, e: &mut E) -> Result<Self::Int, Error> {
+        match v.lo12 {
+            Some(key) => {
+                push_lo12_fixup(e, key, RelocKind::AddAbsLo12Nc);
+                Ok(Self::Int::new(0))
+            }
+            None => EC::encode(v, e),
         }
     }
 }
-impl<const BITS: BitCt> Encoder<op::Imm> for enc::SImm<BITS> {
-    type Int = Int<BITS>;
+/// immediate that is known to be negative, a deferred one is encoded as it is
+fn is_negative(v: &op::Imm) -> bool {
+    v.deferred.is_none() && v.lo12.is_none() && v.value < 0
+}
+impl<const SUB: bool> Encoder<op::Imm> for enc::AddSubOp<SUB> {
+    type Int = Int<1>;
+    fn encode<E: Emitter>(v: &op::Imm, _: &mut E) -> Result<Self::Int, Error> {
+        Ok(Int((SUB != is_negative(v)) as u32))
+    }
+}
+impl Encoder<op::Imm> for enc::AddSubImm {
+    type Int = Int<12>;
     fn encode<E: Emitter>(v: &op::Imm, e: &mut E) -> Result<Self::Int, Error> {
-        Self::encode(&v.0, e)
+        if v.deferred.is_some() || v.lo12.is_some() {
+            return <enc::UImm<12> as Encoder<op::Imm>>::encode(v, e);
+        }
+        match v.value.unsigned_abs() {
+            imm @ 0..=4095 => Ok(Int(imm as u32)),
+            _ => Err(Error::OutOfRange {
+                min: -4095,
+                max: 4095,
+            }),
+        }
+    }
+}
+impl<EC: Encoder<Option<op::Imm>>, const RELOC: u32> Encoder<op::AddrLabel>
+    for enc::Label<EC, RELOC>
+{
+    type Int = EC::Int;
+    fn encode<E: Emitter>(v: &op::AddrLabel, e: &mut E) -> Result<Self::Int, Error> {
+        <Self as Encoder<op::Label>>::encode(&op::Label(v.0), e)
+    }
+}
+/// values of a signed `bits` field
+fn sint_range(bits: BitCt) -> Error {
+    Error::OutOfRange {
+        min: -(1 << (bits - 1)),
+        max: (1 << (bits - 1)) - 1,
     }
 }
-impl<const RS: BitCt, const BITS: BitCt> Encoder<op::Imm> for enc::SImmAlign<BITS, RS> {
+/// values of an unsigned `bits` field
+fn uint_range(bits: BitCt) -> Error {
+    Error::OutOfRange {
+        min: 0,
+        max: (1 << bits) - 1,
+    }
+}
+/// range of a field that holds the value shifted right by `rs`
+fn scaled(e: Error, rs: BitCt) -> Error {
+    match e {
+        Error::OutOfRange { min, max } => Error::OutOfRange {
+            min: min << rs,
+            max: max << rs,
+        },
+        e => e,
+    }
+}
+
+impl<const BITS: BitCt> ImmEncoder for enc::SImm<BITS> {
     type Int = Int<BITS>;
-    fn encode<E: Emitter>(v: &op::Imm, e: &mut E) -> Result<Self::Int, Error> {
-        if sint_low_zeros::<RS>(v.0) {
-            let shifted = v.0 >> RS;
-            enc::SImm::<BITS>::encode(&shifted, e)
+    fn encode_imm<E: Emitter>(v: i64, _: &mut E) -> Result<Self::Int, Error> {
+        if sint_in_range::<BITS>(v) {
+            Ok(Int(v as u32))
         } else {
-            Err(Error::NotAligned)
+            Err(sint_range(BITS))
         }
     }
 }
-
-impl<const BITS: BitCt> Encoder<u64> for enc::UImm<BITS> {
+impl<const RS: BitCt, const BITS: BitCt> ImmEncoder for enc::SImmAlign<BITS, RS> {
     type Int = Int<BITS>;
-    fn encode<E: Emitter>(v: &u64, _: &mut E) -> Result<Self::Int, Error> {
-        if uint_in_range::<BITS>(*v) {
-            Ok(Int(*v as u32))
+    fn encode_imm<E: Emitter>(v: i64, e: &mut E) -> Result<Self::Int, Error> {
+        if sint_low_zeros::<RS>(v) {
+            let shifted = v >> RS;
+            enc::SImm::<BITS>::encode_imm(shifted, e).map_err(|e| scaled(e, RS))
         } else {
-            Err(Error::OutOfRange)
+            Err(Error::NotAligned { align: 1 << RS })
         }
     }
 }
-impl<const BITS: BitCt> Encoder<op::Imm> for enc::UImm<BITS> {
+
+impl<const BITS: BitCt> ImmEncoder for enc::UImm<BITS> {
     type Int = Int<BITS>;
-    fn encode<E: Emitter>(v: &op::Imm, e: &mut E) -> Result<Self::Int, Error> {
-        let uval = v.0 as u64;
-        Self::encode(&uval, e)
+    fn encode_imm<E: Emitter>(v: i64, _: &mut E) -> Result<Self::Int, Error> {
+        let uval = v as u64;
+        if uint_in_range::<BITS>(uval) {
+            Ok(Int(uval as u32))
+        } else {
+            Err(uint_range(BITS))
+        }
     }
 }
-impl<const RS: BitCt, const BITS: BitCt> Encoder<op::Imm> for enc::UImmAlign<BITS, RS> {
+impl<const RS: BitCt, const BITS: BitCt> ImmEncoder for enc::UImmAlign<BITS, RS> {
     type Int = Int<BITS>;
-    fn encode<E: Emitter>(v: &op::Imm, e: &mut E) -> Result<Self::Int, Error> {
-        let uval = v.0 as u64;
+    fn encode_imm<E: Emitter>(v: i64, e: &mut E) -> Result<Self::Int, Error> {
+        let uval = v as u64;
         if uint_low_zeros::<RS>(uval) {
-            let shifted = uval >> RS;
-            enc::UImm::<BITS>::encode(&shifted, e)
+            enc::UImm::<BITS>::encode_imm((uval >> RS) as i64, e).map_err(|e| scaled(e, RS))
+        } else {
+            Err(Error::NotAligned { align: 1 << RS })
+        }
+    }
+}
+
+impl<const MODE: u8> ImmEncoder for enc::SImmIndex<MODE> {
+    type Int = Int<12>;
+    fn encode_imm<E: Emitter>(v: i64, _: &mut E) -> Result<Self::Int, Error> {
+        if sint_in_range::<9>(v) {
+            Ok(Int(((v as u32 & 0x1FF) << 2) | MODE as u32))
+        } else {
+            Err(sint_range(9))
+        }
+    }
+}
+
+impl ImmEncoder for enc::TestBitHigh {
+    type Int = Int<1>;
+    fn encode_imm<E: Emitter>(v: i64, _: &mut E) -> Result<Self::Int, Error> {
+        match u32::try_from(v) {
+            Ok(bit @ 0..=63) => Ok(Int(bit >> 5)),
+            _ => Err(Error::OutOfRange { min: 0, max: 63 }),
+        }
+    }
+}
+impl ImmEncoder for enc::TestBitLow {
+    type Int = Int<5>;
+    fn encode_imm<E: Emitter>(v: i64, _: &mut E) -> Result<Self::Int, Error> {
+        match u32::try_from(v) {
+            Ok(bit @ 0..=63) => Ok(Int(bit & 0b11111)),
+            _ => Err(Error::OutOfRange { min: 0, max: 63 }),
+        }
+    }
+}
+impl<const SIZE: u8, const NOT: bool> ImmEncoder for enc::LogicalImm<SIZE, NOT> {
+    type Int = Int<13>;
+    fn encode_imm<E: Emitter>(v: i64, _: &mut E) -> Result<Self::Int, Error> {
+        let v = if NOT { !v } else { v };
+        let imm = sized_imm(v, SIZE as u32).ok_or(SIZED_IMM_RANGE)?;
+        logical_imm(imm, SIZE as u32)
+            .map(Int)
+            .ok_or(Error::InvalidLogicalImm)
+    }
+}
+impl<const SIZE: u8> ImmEncoder for enc::MovImm<SIZE> {
+    type Int = Int<26>;
+    fn encode_imm<E: Emitter>(v: i64, _: &mut E) -> Result<Self::Int, Error> {
+        let imm = sized_imm(v, SIZE as u32).ok_or(SIZED_IMM_RANGE)?;
+        mov_imm(imm, SIZE as u32)
+            .map(Int)
+            .ok_or(Error::InvalidMovImm)
+    }
+}
+
+impl<const SIZE: u8> ImmEncoder for enc::BitNum<SIZE> {
+    type Int = Int<6>;
+    fn encode_imm<E: Emitter>(v: i64, _: &mut E) -> Result<Self::Int, Error> {
+        match u32::try_from(v) {
+            Ok(bit) if bit < SIZE as u32 * 8 => Ok(Int(bit)),
+            _ => Err(Error::OutOfRange {
+                min: 0,
+                max: SIZE as i64 * 8 - 1,
+            }),
+        }
+    }
+}
+impl<const SIZE: u8, const LEFT: bool> ImmEncoder for enc::BitfieldShift<SIZE, LEFT> {
+    type Int = Int<12>;
+    fn encode_imm<E: Emitter>(v: i64, _: &mut E) -> Result<Self::Int, Error> {
+        let width = SIZE as u32 * 8;
+        let shift = match u32::try_from(v) {
+            Ok(shift) if shift < width => shift,
+            _ => {
+                return Err(Error::OutOfRange {
+                    min: 0,
+                    max: width as i64 - 1,
+                })
+            }
+        };
+        // LSL is UBFM with immr = -shift mod width, imms = width - 1 - shift
+        let (immr, imms) = if LEFT {
+            ((width - shift) % width, width - 1 - shift)
+        } else {
+            (shift, width - 1)
+        };
+        Ok(Int(immr << 6 | imms))
+    }
+}
+
+/// `lsb` and `width` of a bitfield alias, the field has to fit in a `size` byte register
+fn bitfield(lsb: &op::Imm, width: &op::Imm, size: u8) -> Result<(u32, u32), Error> {
+    if lsb.deferred.is_some() || width.deferred.is_some() {
+        return Err(Error::NotConstant);
+    }
+    let bits = size as i64 * 8;
+    if !(0..bits).contains(&lsb.value) {
+        return Err(Error::OutOfRange {
+            min: 0,
+            max: bits - 1,
+        });
+    }
+    if !(1..=bits - lsb.value).contains(&width.value) {
+        return Err(Error::OutOfRange {
+            min: 1,
+            max: bits - lsb.value,
+        });
+    }
+    Ok((lsb.value as u32, width.value as u32))
+}
+impl<const SIZE: u8> enc::BitfieldExtract<SIZE> {
+    pub fn encode(lsb: &op::Imm, width: &op::Imm) -> Result<Int<12>, Error> {
+        let (lsb, width) = bitfield(lsb, width, SIZE)?;
+        Ok(Int(lsb << 6 | (lsb + width - 1)))
+    }
+}
+impl<const SIZE: u8> enc::BitfieldInsert<SIZE> {
+    pub fn encode(lsb: &op::Imm, width: &op::Imm) -> Result<Int<12>, Error> {
+        let (lsb, width) = bitfield(lsb, width, SIZE)?;
+        let bits = SIZE as u32 * 8;
+        Ok(Int(((bits - lsb) % bits) << 6 | (width - 1)))
+    }
+}
+
+/// values that `sized_imm` takes for a 32-bit register
+pub const SIZED_IMM_RANGE: Error = Error::OutOfRange {
+    min: -(1 << 31),
+    max: u32::MAX as i64,
+};
+
+/// `v` as a `size` bit pattern, a 32-bit one can also be written as a negative number
+pub fn sized_imm(v: i64, size: u32) -> Option<u64> {
+    if size == 32 && !(-(1 << 31)..=u32::MAX as i64).contains(&v) {
+        return None;
+    }
+    Some(v as u64 & (u64::MAX >> (64 - size)))
+}
+
+/// `hw:imm16` if only one 16-bit chunk of `imm` is not zero
+fn wide_imm(imm: u64, size: u32) -> Option<u32> {
+    let hw = (0..size / 16).find(|hw| imm & !(0xffff << (hw * 16)) == 0)?;
+    Some((hw << 16) | (imm >> (hw * 16)) as u32)
+}
+
+/// MOVZ, MOVN or ORR with ZR, in the order llvm prefers them
+pub fn mov_imm(imm: u64, size: u32) -> Option<u32> {
+    let mask = u64::MAX >> (64 - size);
+    if let Some(hw_imm16) = wide_imm(imm, size) {
+        Some((0b10_100101 << 18) | hw_imm16)
+    } else if let Some(hw_imm16) = wide_imm(!imm & mask, size) {
+        Some((0b00_100101 << 18) | hw_imm16)
+    } else {
+        logical_imm(imm, size).map(|bitmask| (0b01_100100 << 18) | (bitmask << 5) | 0b11111)
+    }
+}
+
+/// `imm` is a repeating element of 2, 4 .. `size` bits, where each element is a rotated run of
+/// ones, same as llvm's `processLogicalImmediate`
+fn logical_imm(imm: u64, size: u32) -> Option<u32> {
+    let is_mask = |v: u64| v != 0 && v.wrapping_add(1) & v == 0;
+    let is_shifted_mask = |v: u64| v != 0 && is_mask((v - 1) | v);
+    if imm == 0 || imm == u64::MAX >> (64 - size) {
+        return None;
+    }
+    // smallest element that repeats
+    let mut elem = size;
+    loop {
+        let half = elem / 2;
+        let mask = (1u64 << half) - 1;
+        if imm & mask != (imm >> half) & mask {
+            break;
+        }
+        elem = half;
+        if elem == 2 {
+            break;
+        }
+    }
+    let mask = u64::MAX >> (64 - elem);
+    let imm = imm & mask;
+    // rotation that makes the element `0..01..1`, and the count of ones
+    let (rotate, ones) = if is_shifted_mask(imm) {
+        let rotate = imm.trailing_zeros();
+        (rotate, (imm >> rotate).trailing_ones())
+    } else {
+        let imm = imm | !mask;
+        if !is_shifted_mask(!imm) {
+            return None;
+        }
+        let leading = imm.leading_ones();
+        (64 - leading, leading + imm.trailing_ones() - (64 - elem))
+    };
+    let immr = (elem - rotate) & (elem - 1);
+    // element size is encoded as the leading ones of `N:NOT(imms)`
+    let n_imms = (!(elem as u64 - 1) << 1) | (ones as u64 - 1);
+    let n = ((n_imms >> 6) & 1) ^ 1;
+    Some(((n as u32) << 12) | (immr << 6) | (n_imms as u32 & 0x3f))
+}
+
+impl enc::TestBitHigh {
+    /// a W register only has 32 bits to test
+    pub fn valid_width(bit: &op::Imm, gpr: &op::Gpr) -> Result<(), Error> {
+        if bit.deferred.is_none() && gpr.size == GprSize::B4 && bit.value >= 32 {
+            Err(Error::OutOfRange { min: 0, max: 31 })
         } else {
-            Err(Error::NotAligned)
+            Ok(())
+        }
+    }
+}
+
+/// general register that an operand names, for the overlap checks of `enc::Distinct`
+pub trait GprNum {
+    /// `None` if it can't overlap a general register, like `sp` as a base
+    fn gpr_num(&self) -> Option<u8>;
+}
+impl GprNum for op::Gpr {
+    fn gpr_num(&self) -> Option<u8> {
+        match self.reg {
+            GprKind::R(idx) => Some(idx.into()),
+            GprKind::ZR => Some(31),
+            GprKind::SP => None,
+        }
+    }
+}
+impl GprNum for op::AddrImm {
+    fn gpr_num(&self) -> Option<u8> {
+        match self.base {
+            GprKind::R(idx) => Some(idx.into()),
+            GprKind::SP | GprKind::ZR => None,
+        }
+    }
+}
+
+impl enc::Distinct {
+    /// `reg` is not the same register as `other`, W and X of the same number overlap
+    pub fn valid(reg: &impl GprNum, other: &impl GprNum) -> Result<(), Error> {
+        match (reg.gpr_num(), other.gpr_num()) {
+            (Some(reg), Some(other)) if reg == other => Err(Error::OverlappingGpr),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl enc::GprPair {
+    /// `first` is even and `second` is the next register, of the same size
+    pub fn valid(first: &op::Gpr, second: &op::Gpr) -> Result<(), Error> {
+        if first.size != second.size {
+            return Err(Error::InvalidGprWidth);
+        }
+        match (first.gpr_num(), second.gpr_num()) {
+            (Some(first), Some(second)) if first % 2 == 0 && second == first + 1 => Ok(()),
+            _ => Err(Error::InvalidGprPair),
+        }
+    }
+}
+
+impl op::AddrImm {
+    /// an offset that is not known yet is assumed to fit, `:lo12:` is always scaled
+    fn is_scaled<const SCALE: BitCt>(&self) -> bool {
+        let value = self.offset.value;
+        self.mode == IndexMode::Offset
+            && (self.offset.deferred.is_some()
+                || self.offset.lo12.is_some()
+                || (value >= 0
+                    && uint_low_zeros::<SCALE>(value as u64)
+                    && uint_in_range::<12>(value as u64 >> SCALE)))
+    }
+}
+
+fn encode_base(base: GprKind) -> Result<Int<5>, Error> {
+    match base {
+        GprKind::R(idx) => Ok(Int(u8::from(idx) as u32)),
+        GprKind::SP => Ok(Int(31)),
+        GprKind::ZR => Err(Error::InvalidGpr),
+    }
+}
+impl Encoder<op::AddrImm> for enc::AddrBase {
+    type Int = Int<5>;
+    fn encode<E: Emitter>(v: &op::AddrImm, _: &mut E) -> Result<Self::Int, Error> {
+        encode_base(v.base)
+    }
+}
+impl Encoder<op::AddrReg> for enc::AddrBase {
+    type Int = Int<5>;
+    fn encode<E: Emitter>(v: &op::AddrReg, _: &mut E) -> Result<Self::Int, Error> {
+        encode_base(v.base)
+    }
+}
+impl Encoder<op::AddrImm> for enc::AddrBaseOnly {
+    type Int = Int<5>;
+    fn encode<E: Emitter>(v: &op::AddrImm, _: &mut E) -> Result<Self::Int, Error> {
+        let offset = &v.offset;
+        if v.mode != IndexMode::Offset {
+            return Err(Error::InvalidIndexMode);
+        }
+        if offset.deferred.is_some() || offset.lo12.is_some() || offset.value != 0 {
+            return Err(Error::OutOfRange { min: 0, max: 0 });
+        }
+        encode_base(v.base)
+    }
+}
+impl<const SCALE: BitCt> Encoder<op::AddrImm> for enc::AddrScaled<SCALE> {
+    type Int = Int<1>;
+    fn encode<E: Emitter>(v: &op::AddrImm, _: &mut E) -> Result<Self::Int, Error> {
+        Ok(Int(v.is_scaled::<SCALE>() as u32))
+    }
+}
+impl<const SCALE: BitCt> Encoder<op::AddrImm> for enc::AddrOffset<SCALE> {
+    type Int = Int<12>;
+    fn encode<E: Emitter>(v: &op::AddrImm, e: &mut E) -> Result<Self::Int, Error> {
+        if v.is_scaled::<SCALE>() {
+            if let Some(key) = v.offset.lo12 {
+                push_lo12_fixup(e, key, RelocKind::ldst_lo12(SCALE as u8).unwrap());
+                return Ok(Int(0));
+            }
+            return <enc::UImmAlign<12, SCALE> as Encoder<op::Imm>>::encode(&v.offset, e);
+        }
+        match v.mode {
+            IndexMode::Offset => <enc::SImmIndex<0b00> as Encoder<op::Imm>>::encode(&v.offset, e),
+            IndexMode::Pre => <enc::SImmIndex<0b11> as Encoder<op::Imm>>::encode(&v.offset, e),
+            IndexMode::Post => <enc::SImmIndex<0b01> as Encoder<op::Imm>>::encode(&v.offset, e),
+        }
+    }
+}
+impl Encoder<op::AddrImm> for enc::AddrUnscaled {
+    type Int = Int<12>;
+    fn encode<E: Emitter>(v: &op::AddrImm, e: &mut E) -> Result<Self::Int, Error> {
+        match v.mode {
+            IndexMode::Offset => <enc::SImmIndex<0b00> as Encoder<op::Imm>>::encode(&v.offset, e),
+            IndexMode::Pre | IndexMode::Post => Err(Error::InvalidIndexMode),
+        }
+    }
+}
+impl Encoder<op::AddrReg> for enc::AddrIndex {
+    type Int = Int<5>;
+    fn encode<E: Emitter>(v: &op::AddrReg, e: &mut E) -> Result<Self::Int, Error> {
+        enc::GprOrZr::encode(&v.index, e)
+    }
+}
+impl Encoder<op::AddrPostReg> for enc::AddrBase {
+    type Int = Int<5>;
+    fn encode<E: Emitter>(v: &op::AddrPostReg, _: &mut E) -> Result<Self::Int, Error> {
+        encode_base(v.base)
+    }
+}
+impl Encoder<op::AddrPostReg> for enc::AddrIndex {
+    type Int = Int<5>;
+    fn encode<E: Emitter>(v: &op::AddrPostReg, e: &mut E) -> Result<Self::Int, Error> {
+        // `Rm` of 31 is the post-index by the list size
+        enc::SizedGpr::<enc::Gpr, 8>::encode(&v.index, e)
+    }
+}
+impl Encoder<op::AddrReg> for enc::AddrExtend {
+    type Int = Int<3>;
+    fn encode<E: Emitter>(v: &op::AddrReg, _: &mut E) -> Result<Self::Int, Error> {
+        // no extend is `LSL`, which is encoded as `UXTX`
+        let kind = v.extend.as_ref().map_or(ExtendKind::UXTX, |ext| ext.kind);
+        Ok(Int(kind as u32))
+    }
+}
+impl<const SCALE: BitCt> Encoder<op::AddrReg> for enc::AddrShift<SCALE> {
+    type Int = Int<1>;
+    fn encode<E: Emitter>(v: &op::AddrReg, _: &mut E) -> Result<Self::Int, Error> {
+        // `LSL #0` is still encoded as shifted for a byte access
+        match v.extend.as_ref().and_then(|ext| ext.left_shift_amount) {
+            Some(amount) if amount as BitCt == SCALE => Ok(Int(1)),
+            None | Some(0) => Ok(Int(0)),
+            Some(..) => Err(Error::OutOfRange {
+                min: SCALE as i64,
+                max: SCALE as i64,
+            }),
         }
     }
 }
 
+impl Encoder<op::AddrImm> for enc::AddrPairMode {
+    type Int = Int<2>;
+    fn encode<E: Emitter>(v: &op::AddrImm, _: &mut E) -> Result<Self::Int, Error> {
+        Ok(Int(match v.mode {
+            IndexMode::Offset => 0b10,
+            IndexMode::Pre => 0b11,
+            IndexMode::Post => 0b01,
+        }))
+    }
+}
+impl Encoder<op::AddrImm> for enc::AddrNoWriteback {
+    type Int = Int<2>;
+    fn encode<E: Emitter>(v: &op::AddrImm, _: &mut E) -> Result<Self::Int, Error> {
+        match v.mode {
+            IndexMode::Offset => Ok(Int(0b00)),
+            IndexMode::Pre | IndexMode::Post => Err(Error::InvalidIndexMode),
+        }
+    }
+}
+impl<const SCALE: BitCt> Encoder<op::AddrImm> for enc::AddrPairOffset<SCALE> {
+    type Int = Int<7>;
+    fn encode<E: Emitter>(v: &op::AddrImm, e: &mut E) -> Result<Self::Int, Error> {
+        <enc::SImmAlign<7, SCALE> as Encoder<op::Imm>>::encode(&v.offset, e)
+    }
+}
+
 impl<const KIND: u8, const AMT: u8> Encoder<Option<op::Shift>> for enc::ShiftConst<KIND, AMT> {
     type Int = Int<1>;
     fn encode<E: Emitter>(v: &Option<op::Shift>, _: &mut E) -> Result<Self::Int, Error> {
@@ -410,15 +1238,437 @@ fn encode<E: Emitter>(v: &op::Extend, _: &mut E) -> Result<Self::Int, Error> {
 impl Encoder<op::Extend> for enc::ExtendLShift {
     type Int = Int<3>;
     fn encode<E: Emitter>(v: &op::Extend, _: &mut E) -> Result<Self::Int, Error> {
-        Ok(Int(v.left_shift_amount.unwrap_or(0) as u32))
+        match v.left_shift_amount.unwrap_or(0) {
+            amount @ 0..=4 => Ok(Int(amount as u32)),
+            _ => Err(Error::OutOfRange { min: 0, max: 4 }),
+        }
+    }
+}
+impl Encoder<op::Dpr> for enc::Dpr {
+    type Int = Int<5>;
+    fn encode<E: Emitter>(v: &op::Dpr, _: &mut E) -> Result<Self::Int, Error> {
+        debug_assert!(v.reg <= 31);
+        Ok(Int(v.reg as u32))
+    }
+}
+impl<const SIZE: u8> Encoder<op::Dpr> for enc::SizedDpr<SIZE> {
+    type Int = Int<5>;
+    fn encode<E: Emitter>(v: &op::Dpr, e: &mut E) -> Result<Self::Int, Error> {
+        if v.size as u8 == SIZE {
+            enc::Dpr::encode(v, e)
+        } else {
+            Err(Error::InvalidDprWidth)
+        }
+    }
+}
+impl Encoder<op::Dpr> for enc::Ftype {
+    type Int = Int<2>;
+    fn encode<E: Emitter>(v: &op::Dpr, e: &mut E) -> Result<Self::Int, Error> {
+        if v.size == Size::B2 && !e.features().has(Feature::FP16) {
+            return Err(Error::MissingFeature(Feature::FP16));
+        }
+        enc::ConvertFtype::encode(v, e)
+    }
+}
+impl Encoder<op::Dpr> for enc::ConvertFtype {
+    type Int = Int<2>;
+    fn encode<E: Emitter>(v: &op::Dpr, _: &mut E) -> Result<Self::Int, Error> {
+        match v.size {
+            Size::B2 => Ok(Int(0b11)),
+            Size::B4 => Ok(Int(0b00)),
+            Size::B8 => Ok(Int(0b01)),
+            Size::B1 | Size::B16 => Err(Error::InvalidDprWidth),
+        }
+    }
+}
+impl Encoder<op::FpImm> for enc::FpImm {
+    type Int = Int<8>;
+    fn encode<E: Emitter>(v: &op::FpImm, _: &mut E) -> Result<Self::Int, Error> {
+        (0..=0xff)
+            .find(|&imm8| fp_imm_value(imm8) == v.0)
+            .map(Int)
+            .ok_or(Error::InvalidFpImm)
+    }
+}
+impl Encoder<op::FpImm> for enc::FmovImm {
+    type Int = Int<16>;
+    fn encode<E: Emitter>(v: &op::FpImm, e: &mut E) -> Result<Self::Int, Error> {
+        if enc::FmovImm::is_zero(v) {
+            return Ok(Int(0b0011_1000_0001_1111));
+        }
+        let imm8 = enc::FpImm::encode(v, e)?;
+        Ok(Int(imm8.0 << 8 | 0b100_00000))
+    }
+}
+impl enc::FmovImm {
+    /// `+0.0`, which has no `imm8`
+    pub fn is_zero(v: &op::FpImm) -> bool {
+        v.0 == 0.0 && v.0.is_sign_positive()
+    }
+
+    /// `sf` of FMOV from ZR, XZR for D and WZR for S or H
+    pub fn sf(dpr: &op::Dpr, v: &op::FpImm) -> Int<1> {
+        Int(u32::from(enc::FmovImm::is_zero(v) && dpr.size == Size::B8))
+    }
+}
+impl Encoder<op::FpImm> for enc::FpZero {
+    type Int = Int<5>;
+    fn encode<E: Emitter>(v: &op::FpImm, _: &mut E) -> Result<Self::Int, Error> {
+        if v.0 == 0.0 {
+            Ok(Int(0))
+        } else {
+            Err(Error::InvalidFpImm)
+        }
+    }
+}
+
+impl enc::Ftype {
+    /// FCVT converts `src` to a different precision than it already is
+    pub fn valid_convert(dst: &op::Dpr, src: &op::Dpr) -> Result<(), Error> {
+        if dst.size == src.size {
+            Err(Error::InvalidDprWidth)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// FMOV between a W and an S register or an X and a D register, H goes with either
+    pub fn valid_move(gpr: &op::Gpr, dpr: &op::Dpr) -> Result<(), Error> {
+        match (gpr.size, dpr.size) {
+            (GprSize::B4, Size::B4) | (GprSize::B8, Size::B8) | (_, Size::B2) => Ok(()),
+            _ => Err(Error::InvalidDprWidth),
+        }
+    }
+}
+
+/// `(-1)^sign * (16 + frac4) / 16 * 2^exp` where `exp` is -3 to 4, same as VFPExpandImm
+fn fp_imm_value(imm8: u32) -> f64 {
+    let sign = if imm8 & 0x80 != 0 { -1.0 } else { 1.0 };
+    let low = (imm8 >> 4 & 0b11) as i32;
+    let exp = if imm8 & 0x40 != 0 { low - 3 } else { low + 1 };
+    let frac = (16 + (imm8 & 0xf)) as f64 / 16.0;
+    sign * frac * 2f64.powi(exp)
+}
+
+impl Encoder<op::Vr> for enc::Vr {
+    type Int = Int<5>;
+    fn encode<E: Emitter>(v: &op::Vr, _: &mut E) -> Result<Self::Int, Error> {
+        Ok(Int(v.reg as u32))
+    }
+}
+impl Encoder<op::IdxVr> for enc::Vr {
+    type Int = Int<5>;
+    fn encode<E: Emitter>(v: &op::IdxVr, _: &mut E) -> Result<Self::Int, Error> {
+        Ok(Int(v.reg as u32))
+    }
+}
+impl Encoder<op::VrList> for enc::Vr {
+    type Int = Int<5>;
+    fn encode<E: Emitter>(v: &op::VrList, _: &mut E) -> Result<Self::Int, Error> {
+        Ok(Int(v.reg as u32))
+    }
+}
+impl Encoder<op::Vr> for enc::Q {
+    type Int = Int<1>;
+    fn encode<E: Emitter>(v: &op::Vr, _: &mut E) -> Result<Self::Int, Error> {
+        match (v.size, v.lanes) {
+            (VecSize::B8, VecLanes::B8) | (VecSize::B16, _) => Err(Error::InvalidArrangement),
+            (_, lanes) => Ok(Int((lanes == VecLanes::B16) as u32)),
+        }
+    }
+}
+impl<const SIZE: u8, const LANES: u8> Encoder<op::Vr> for enc::ArrangedVr<SIZE, LANES> {
+    type Int = Int<5>;
+    fn encode<E: Emitter>(v: &op::Vr, e: &mut E) -> Result<Self::Int, Error> {
+        if v.size as u8 == SIZE && v.lanes as u8 == LANES {
+            enc::Vr::encode(v, e)
+        } else {
+            Err(Error::InvalidArrangement)
+        }
+    }
+}
+impl<const SIZE: u8> Encoder<op::Vr> for enc::SizedVr<SIZE> {
+    type Int = Int<5>;
+    fn encode<E: Emitter>(v: &op::Vr, e: &mut E) -> Result<Self::Int, Error> {
+        if v.size as u8 == SIZE {
+            enc::Vr::encode(v, e)
+        } else {
+            Err(Error::InvalidArrangement)
+        }
+    }
+}
+impl<const UPPER: bool> Encoder<op::Vr> for enc::Half<UPPER> {
+    type Int = Int<1>;
+    fn encode<E: Emitter>(v: &op::Vr, _: &mut E) -> Result<Self::Int, Error> {
+        let lanes = if UPPER { VecLanes::B16 } else { VecLanes::B8 };
+        if v.lanes == lanes {
+            Ok(Int(UPPER as u32))
+        } else {
+            Err(Error::InvalidArrangement)
+        }
+    }
+}
+impl Encoder<op::VrList> for enc::Q {
+    type Int = Int<1>;
+    fn encode<E: Emitter>(v: &op::VrList, _: &mut E) -> Result<Self::Int, Error> {
+        Ok(Int((v.lanes == VecLanes::B16) as u32))
+    }
+}
+impl<const MAX: u8> Encoder<op::Vr> for enc::VecSize<MAX> {
+    type Int = Int<2>;
+    fn encode<E: Emitter>(v: &op::Vr, _: &mut E) -> Result<Self::Int, Error> {
+        if v.size as u8 <= MAX {
+            Ok(Int(v.size.encode()))
+        } else {
+            Err(Error::InvalidArrangement)
+        }
+    }
+}
+impl<const MAX: u8> Encoder<op::VrList> for enc::VecSize<MAX> {
+    type Int = Int<2>;
+    fn encode<E: Emitter>(v: &op::VrList, _: &mut E) -> Result<Self::Int, Error> {
+        if v.size as u8 <= MAX {
+            Ok(Int(v.size.encode()))
+        } else {
+            Err(Error::InvalidArrangement)
+        }
+    }
+}
+impl Encoder<op::Vr> for enc::FpVecSize {
+    type Int = Int<1>;
+    fn encode<E: Emitter>(v: &op::Vr, _: &mut E) -> Result<Self::Int, Error> {
+        match v.size {
+            VecSize::B4 => Ok(Int(0)),
+            VecSize::B8 => Ok(Int(1)),
+            VecSize::B1 | VecSize::B2 | VecSize::B16 => Err(Error::InvalidArrangement),
+        }
+    }
+}
+impl Encoder<op::Vr> for enc::FpElemSize {
+    type Int = Int<2>;
+    fn encode<E: Emitter>(v: &op::Vr, e: &mut E) -> Result<Self::Int, Error> {
+        if v.size == VecSize::B2 {
+            if !e.features().has(Feature::FP16) {
+                return Err(Error::MissingFeature(Feature::FP16));
+            }
+            return Ok(Int(0b00));
+        }
+        enc::FpVecSize::encode(v, e).map(|sz| Int(0b10 | sz.0))
+    }
+}
+impl Encoder<op::Vr> for enc::PmullSize {
+    type Int = Int<2>;
+    fn encode<E: Emitter>(v: &op::Vr, e: &mut E) -> Result<Self::Int, Error> {
+        match v.size {
+            VecSize::B1 => Ok(Int(0b00)),
+            VecSize::B8 if !e.features().has(Feature::AES) => {
+                Err(Error::MissingFeature(Feature::AES))
+            }
+            VecSize::B8 => Ok(Int(0b11)),
+            _ => Err(Error::InvalidArrangement),
+        }
+    }
+}
+impl Encoder<op::VrList> for enc::ListOpcode {
+    type Int = Int<4>;
+    fn encode<E: Emitter>(v: &op::VrList, _: &mut E) -> Result<Self::Int, Error> {
+        match v.count {
+            1 => Ok(Int(0b0111)),
+            2 => Ok(Int(0b1010)),
+            3 => Ok(Int(0b0110)),
+            4 => Ok(Int(0b0010)),
+            _ => Err(Error::OutOfRange { min: 1, max: 4 }),
+        }
+    }
+}
+impl Encoder<op::VrList> for enc::TableLen {
+    type Int = Int<2>;
+    fn encode<E: Emitter>(v: &op::VrList, _: &mut E) -> Result<Self::Int, Error> {
+        if v.size != VecSize::B1 || v.lanes != VecLanes::B16 {
+            return Err(Error::InvalidArrangement);
+        }
+        match v.count {
+            count @ 1..=4 => Ok(Int(count as u32 - 1)),
+            _ => Err(Error::OutOfRange { min: 1, max: 4 }),
+        }
+    }
+}
+impl Encoder<op::IdxVr> for enc::LaneImm5 {
+    type Int = Int<5>;
+    fn encode<E: Emitter>(v: &op::IdxVr, _: &mut E) -> Result<Self::Int, Error> {
+        let size = v.size.encode();
+        Ok(Int(((v.index as u32) << 1 | 1) << size))
+    }
+}
+impl Encoder<op::Vr> for enc::LaneImm5 {
+    type Int = Int<5>;
+    fn encode<E: Emitter>(v: &op::Vr, _: &mut E) -> Result<Self::Int, Error> {
+        Ok(Int(1 << v.size.encode()))
+    }
+}
+impl Encoder<op::IdxVr> for enc::LaneImm4 {
+    type Int = Int<4>;
+    fn encode<E: Emitter>(v: &op::IdxVr, _: &mut E) -> Result<Self::Int, Error> {
+        Ok(Int((v.index as u32) << v.size.encode()))
+    }
+}
+impl Encoder<op::IdxVr> for enc::LaneLow {
+    type Int = Int<1>;
+    fn encode<E: Emitter>(v: &op::IdxVr, _: &mut E) -> Result<Self::Int, Error> {
+        match v.size {
+            VecSize::B2 => Ok(Int(v.index as u32 >> 1 & 1)),
+            VecSize::B4 => Ok(Int(v.index as u32 & 1)),
+            VecSize::B8 => Ok(Int(0)),
+            VecSize::B1 | VecSize::B16 => Err(Error::InvalidArrangement),
+        }
+    }
+}
+impl Encoder<op::IdxVr> for enc::LaneHigh {
+    type Int = Int<1>;
+    fn encode<E: Emitter>(v: &op::IdxVr, _: &mut E) -> Result<Self::Int, Error> {
+        match v.size {
+            VecSize::B2 => Ok(Int(v.index as u32 >> 2)),
+            VecSize::B4 => Ok(Int(v.index as u32 >> 1)),
+            VecSize::B8 => Ok(Int(v.index as u32)),
+            VecSize::B1 | VecSize::B16 => Err(Error::InvalidArrangement),
+        }
+    }
+}
+impl Encoder<op::IdxVr> for enc::LaneVr {
+    type Int = Int<5>;
+    fn encode<E: Emitter>(v: &op::IdxVr, _: &mut E) -> Result<Self::Int, Error> {
+        match v.size {
+            VecSize::B2 if v.reg > 15 => Err(Error::OutOfRange { min: 0, max: 15 }),
+            VecSize::B2 => Ok(Int((v.index as u32 & 1) << 4 | v.reg as u32)),
+            _ => Ok(Int(v.reg as u32)),
+        }
+    }
+}
+
+impl enc::Vr {
+    /// same arrangement as `vd`
+    pub fn valid_same(vd: &op::Vr, vn: &op::Vr) -> Result<(), Error> {
+        if vd.size == vn.size && vd.lanes == vn.lanes {
+            Ok(())
+        } else {
+            Err(Error::InvalidArrangement)
+        }
+    }
+
+    /// elements twice the size of those of `vn`, filling a 128-bit `vd`
+    pub fn valid_long(vd: &op::Vr, vn: &op::Vr) -> Result<(), Error> {
+        if vd.size as u8 == 2 * vn.size as u8 && vd.lanes == VecLanes::B16 {
+            Ok(())
+        } else {
+            Err(Error::InvalidArrangement)
+        }
+    }
+}
+
+impl enc::LaneImm5 {
+    /// lane of the same size as the elements of the other vector or lane
+    pub fn valid_size(size: VecSize, lane: VecSize) -> Result<(), Error> {
+        if size == lane {
+            Ok(())
+        } else {
+            Err(Error::InvalidArrangement)
+        }
+    }
+
+    /// a D lane is moved to or from an X register, the smaller lanes a W register
+    pub fn valid_gpr(size: VecSize, gpr: &op::Gpr) -> Result<(), Error> {
+        if (size == VecSize::B8) == (gpr.size == GprSize::B8) {
+            Ok(())
+        } else {
+            Err(Error::InvalidGprWidth)
+        }
+    }
+
+    /// `MOV` is only an alias of `UMOV` for an S lane to a W register or a D lane to an X
+    pub fn valid_mov(size: VecSize, gpr: &op::Gpr) -> Result<(), Error> {
+        match size {
+            VecSize::B4 | VecSize::B8 => Self::valid_gpr(size, gpr),
+            VecSize::B1 | VecSize::B2 | VecSize::B16 => Err(Error::InvalidArrangement),
+        }
+    }
+}
+
+impl Encoder<op::AddrImm> for enc::AddrListMode {
+    type Int = Int<1>;
+    fn encode<E: Emitter>(v: &op::AddrImm, _: &mut E) -> Result<Self::Int, Error> {
+        match v.mode {
+            IndexMode::Offset => Ok(Int(0)),
+            IndexMode::Post => Ok(Int(1)),
+            IndexMode::Pre => Err(Error::InvalidIndexMode),
+        }
     }
 }
+impl enc::AddrListOffset {
+    pub fn encode(addr: &op::AddrImm, list: &op::VrList) -> Result<Int<5>, Error> {
+        let offset = &addr.offset;
+        if offset.deferred.is_some() || offset.lo12.is_some() {
+            return Err(Error::NotConstant);
+        }
+        let (expect, rm) = match addr.mode {
+            IndexMode::Post => (list.count as i64 * list.lanes as i64, 0b11111),
+            IndexMode::Offset | IndexMode::Pre => (0, 0),
+        };
+        if offset.value == expect {
+            Ok(Int(rm))
+        } else {
+            Err(Error::OutOfRange {
+                min: expect,
+                max: expect,
+            })
+        }
+    }
+}
+
 impl Encoder<op::Cond> for enc::Cond {
     type Int = Int<4>;
     fn encode<E: Emitter>(v: &op::Cond, _: &mut E) -> Result<Self::Int, Error> {
         Ok(Int(v.0 as u32))
     }
 }
+impl Encoder<op::SysReg> for enc::SysReg {
+    type Int = Int<15>;
+    fn encode<E: Emitter>(v: &op::SysReg, _: &mut E) -> Result<Self::Int, Error> {
+        if v.0 >> 14 >= 2 {
+            Ok(Int(v.0 as u32 & 0x7fff))
+        } else {
+            Err(Error::InvalidSysReg)
+        }
+    }
+}
+impl Encoder<op::Barrier> for enc::Barrier {
+    type Int = Int<4>;
+    fn encode<E: Emitter>(v: &op::Barrier, _: &mut E) -> Result<Self::Int, Error> {
+        Ok(Int(v.0 as u32))
+    }
+}
+impl Encoder<Option<op::Barrier>> for enc::IsbBarrier {
+    type Int = Int<4>;
+    fn encode<E: Emitter>(v: &Option<op::Barrier>, _: &mut E) -> Result<Self::Int, Error> {
+        match v {
+            None | Some(op::Barrier(BarrierKind::SY)) => Ok(Int(BarrierKind::SY as u32)),
+            Some(..) => Err(Error::InvalidBarrier),
+        }
+    }
+}
+impl Encoder<Option<op::BtiTarget>> for enc::BtiTarget {
+    type Int = Int<2>;
+    fn encode<E: Emitter>(v: &Option<op::BtiTarget>, _: &mut E) -> Result<Self::Int, Error> {
+        Ok(Int(v.as_ref().map_or(0, |target| target.0 as u32)))
+    }
+}
+impl Encoder<op::Cond> for enc::InvertedCond {
+    type Int = Int<4>;
+    fn encode<E: Emitter>(v: &op::Cond, _: &mut E) -> Result<Self::Int, Error> {
+        v.0.invert()
+            .map(|cond| Int(cond as u32))
+            .ok_or(Error::InvalidCond)
+    }
+}
 
 impl Encoder<Option<op::Shift>> for enc::ShiftKind {
     type Int = Int<2>;
@@ -426,24 +1676,44 @@ fn encode<E: Emitter>(v: &Option<op::Shift>, e: &mut E) -> Result<Self::Int, Err
         Self::opt(v, e, Int(0))
     }
 }
-impl<const BITS: BitCt> Encoder<Option<op::Shift>> for enc::ShiftAmount<BITS> {
-    type Int = Int<BITS>;
+impl Encoder<Option<op::Shift>> for enc::ShiftKindOrRor {
+    type Int = Int<2>;
     fn encode<E: Emitter>(v: &Option<op::Shift>, e: &mut E) -> Result<Self::Int, Error> {
         Self::opt(v, e, Int(0))
     }
 }
-impl<const BITS: BitCt> Encoder<Option<op::Imm>> for enc::UImm<BITS> {
+impl<const BITS: BitCt> Encoder<Option<op::Shift>> for enc::ShiftAmount<BITS> {
     type Int = Int<BITS>;
-    fn encode<E: Emitter>(v: &Option<op::Imm>, e: &mut E) -> Result<Self::Int, Error> {
+    fn encode<E: Emitter>(v: &Option<op::Shift>, e: &mut E) -> Result<Self::Int, Error> {
         Self::opt(v, e, Int(0))
     }
 }
-impl<const RS: BitCt, const BITS: BitCt> Encoder<Option<op::Imm>> for enc::SImmAlign<BITS, RS> {
-    type Int = Int<BITS>;
-    fn encode<E: Emitter>(v: &Option<op::Imm>, e: &mut E) -> Result<Self::Int, Error> {
+impl<const SIZE: u8> Encoder<Option<op::Shift>> for enc::SizedShiftAmount<SIZE> {
+    type Int = Int<6>;
+    fn encode<E: Emitter>(v: &Option<op::Shift>, e: &mut E) -> Result<Self::Int, Error> {
         Self::opt(v, e, Int(0))
     }
 }
+impl<const SIZE: u8> Encoder<Option<op::Shift>> for enc::WideShift<SIZE> {
+    type Int = Int<2>;
+    fn encode<E: Emitter>(v: &Option<op::Shift>, _: &mut E) -> Result<Self::Int, Error> {
+        let Some(shift) = v else {
+            return Ok(Int(0));
+        };
+        if shift.kind != ShiftKind::LSL {
+            Err(Error::InvalidShiftKind)
+        } else if shift.amount % 16 != 0 {
+            Err(Error::NotAligned { align: 16 })
+        } else if shift.amount >= SIZE * 8 {
+            Err(Error::OutOfRange {
+                min: 0,
+                max: SIZE as i64 * 8 - 16,
+            })
+        } else {
+            Ok(Int(shift.amount as u32 / 16))
+        }
+    }
+}
 impl Encoder<Option<op::Extend>> for enc::ExtendKind {
     type Int = Int<3>;
     fn encode<E: Emitter>(v: &Option<op::Extend>, e: &mut E) -> Result<Self::Int, Error> {
@@ -458,15 +1728,35 @@ fn encode<E: Emitter>(v: &Option<op::Extend>, e: &mut E) -> Result<Self::Int, Er
 }
 
 impl enc::ExtendKind {
-    pub fn valid_width(ext: &Option<op::Extend>, gpr: &op::Gpr) -> Result<(), Error> {
-        let Some(ext) = ext else {
-            return Ok(());
-        };
-        if (ext.kind == ExtendKind::SXTX || ext.kind == ExtendKind::UXTX) && gpr.size != GprSize::B8
-        {
-            Err(Error::InvalidExtendWidth)
-        } else {
+    /// `rm` is an X register for the 64-bit extends of a 64-bit `rd`, otherwise a W register
+    pub fn valid_width(ext: &Option<op::Extend>, rd: &op::Gpr, rm: &op::Gpr) -> Result<(), Error> {
+        if rd.size == GprSize::B4 && rm.size == GprSize::B8 {
+            return Err(Error::InvalidGprWidth);
+        }
+        let is_x = rd.size == GprSize::B8
+            && ext.as_ref().map_or(true, |ext| {
+                matches!(ext.kind, ExtendKind::SXTX | ExtendKind::UXTX)
+            });
+        if is_x == (rm.size == GprSize::B8) {
             Ok(())
+        } else {
+            Err(Error::InvalidExtendWidth)
+        }
+    }
+
+    /// no extend, or `LSL`, is UXTX or UXTW depending on the size of `rd`
+    pub fn encode_sized<E: Emitter>(
+        ext: &Option<op::Extend>,
+        rd: &op::Gpr,
+        e: &mut E,
+    ) -> Result<Int<3>, Error> {
+        let lsl = match rd.size {
+            GprSize::B4 => ExtendKind::UXTW,
+            GprSize::B8 => ExtendKind::UXTX,
+        };
+        match ext {
+            Some(ext) if ext.kind != ExtendKind::UXTX => Self::encode(ext, e),
+            _ => Ok(Int(lsl as u32)),
         }
     }
 }
@@ -480,23 +1770,26 @@ pub fn get(self) -> u8 {
     }
 }
 
-// impl IdxVr {
-//     pub fn new(reg: Reg, size: VecSize, index: u8) -> Option<Self> {
-//         if !Self::index_in_range(index, size) {
-//             return None;
-//         }
-//         Some(Self { reg, size, index })
-//     }
-//     pub const fn index_in_range(index: u8, size: VecSize) -> bool {
-//         index < ((Size::B16 as u8) / (size as u8))
-//     }
-// }
+impl VecSize {
+    /// `size` field of a vector instruction, log2 of the bytes
+    pub fn encode(self) -> u32 {
+        (self as u8).trailing_zeros()
+    }
+}
+
+impl op::IdxVr {
+    /// a lane of `size` is one of the elements of a 128-bit vector
+    pub const fn index_in_range(index: u8, size: VecSize) -> bool {
+        index < ((Size::B16 as u8) / (size as u8))
+    }
+}
 
 simpls! {
     { Default |op::Shift| op::Shift{ kind: ShiftKind::LSL, amount: 0 } }
     { Default |op::Extend| op::Extend{ kind: ExtendKind::UXTB, left_shift_amount: None } }
     { Default |op::Cond| op::Cond(CondKind::AL) }
-    { Default |op::Imm| op::Imm(0) }
+    { Default |op::Imm| op::Imm::new(0) }
+    { Default |op::FpImm| op::FpImm(0.0) }
     { Default |op::Gpr| op::Gpr{ reg: GprKind::ZR, size: GprSize::B8 } }
     { Default |op::Dpr| op::Dpr{ reg: 0, size: Size::B8 } }
 
@@ -520,7 +1813,7 @@ pub fn get(self) -> u8 {
         None => write!(f, "{}", self.kind)
     } }
     { Display |self: op::Cond| "{}", self.0.to_str() }
-    { Display |self: op::Imm| "#{}", self.0 }
+    { Display |self: op::Imm| "#{}", self.value }
     { Display |self: op::Label| "<label:{:?}>", self.0 }
 
     // Maybe display is unnecessary, use Debug printing instead
@@ -542,3 +1835,44 @@ pub fn get(self) -> u8 {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn logical_imm_works() {
+        assert_eq!(logical_imm(0xff, 32), Some(0b0_000000_000111));
+        assert_eq!(logical_imm(0x8000_0001, 32), Some(0b0_000001_000001));
+        assert_eq!(
+            logical_imm(0xff00_ff00_ff00_ff00, 64),
+            Some(0b0_001000_100111)
+        );
+        assert_eq!(
+            logical_imm(0x5555_5555_5555_5555, 64),
+            Some(0b0_000000_111100)
+        );
+        assert_eq!(
+            logical_imm(0x7fff_ffff_ffff_ffff, 64),
+            Some(0b1_000000_111110)
+        );
+        assert_eq!(logical_imm(0, 64), None);
+        assert_eq!(logical_imm(u64::MAX, 64), None);
+        assert_eq!(logical_imm(0xffff_ffff, 32), None);
+        assert_eq!(logical_imm(0x1234, 64), None);
+    }
+
+    #[test]
+    fn fp_imm_works() {
+        assert_eq!(fp_imm_value(0x70), 1.0);
+        assert_eq!(fp_imm_value(0x78), 1.5);
+        assert_eq!(fp_imm_value(0x40), 0.125);
+        assert_eq!(fp_imm_value(0x3f), 31.0);
+        assert_eq!(fp_imm_value(0x84), -2.5);
+        let encode = |v| (0..=0xff).find(|&imm8| fp_imm_value(imm8) == v);
+        assert_eq!(encode(10.0), Some(0x24));
+        assert_eq!(encode(0.0), None);
+        assert_eq!(encode(0.1), None);
+        assert_eq!(encode(32.0), None);
+    }
+}
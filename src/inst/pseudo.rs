@@ -0,0 +1,50 @@
+use crate::inst::{
+    def::enc,
+    op,
+    operand::{mov_imm, sized_imm, GprSize, ShiftKind, SIZED_IMM_RANGE},
+    Emitter, EncInstr, Error, ErrorMacro,
+};
+
+/// `LDI Rd, #imm`, one MOV if it can make `imm`, otherwise the shortest MOVZ or MOVN followed by
+/// a MOVK for every 16-bit chunk that it leaves wrong
+pub fn ldi<E: Emitter>(instr: &enc::LDI, e: &mut E) -> Result<(), ErrorMacro> {
+    let enc::LDI(rd, imm) = instr;
+    let err = |err| ErrorMacro(err, "Expand(ldi)");
+    if imm.deferred.is_some() {
+        return Err(err(Error::NotConstant));
+    }
+    let size = match rd.size {
+        GprSize::B4 => 32,
+        GprSize::B8 => 64,
+    };
+    let value = sized_imm(imm.value, size).ok_or(err(SIZED_IMM_RANGE))?;
+    if mov_imm(value, size).is_some() {
+        return enc::MOV_Immediate(rd.clone(), imm.clone()).emit(e);
+    }
+
+    let chunk = |hw: u32| (value >> (hw * 16)) & 0xffff;
+    let lsl = |hw: u32| {
+        Some(op::Shift {
+            kind: ShiftKind::LSL,
+            amount: (hw * 16) as u8,
+        })
+    };
+    let ones = (0..size / 16).filter(|&hw| chunk(hw) == 0xffff).count();
+    let zeros = (0..size / 16).filter(|&hw| chunk(hw) == 0).count();
+    // MOVN leaves the other chunks all ones, MOVZ leaves them zero
+    let fill = if ones > zeros { 0xffff } else { 0 };
+
+    let mut first = true;
+    for hw in (0..size / 16).filter(|&hw| chunk(hw) != fill) {
+        let rd = rd.clone();
+        if !first {
+            enc::MOVK(rd, op::Imm::new(chunk(hw) as i64), lsl(hw)).emit(e)?;
+        } else if fill == 0 {
+            enc::MOVZ(rd, op::Imm::new(chunk(hw) as i64), lsl(hw)).emit(e)?;
+        } else {
+            enc::MOVN(rd, op::Imm::new((chunk(hw) ^ 0xffff) as i64), lsl(hw)).emit(e)?;
+        }
+        first = false;
+    }
+    Ok(())
+}
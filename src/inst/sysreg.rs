@@ -0,0 +1,122 @@
+use crate::enum_str::EnumStr;
+
+/// `op0:op1:CRn:CRm:op2`, how MRS and MSR name a system register
+const fn sys(op0: u16, op1: u16, crn: u16, crm: u16, op2: u16) -> u16 {
+    (op0 << 14) | (op1 << 11) | (crn << 7) | (crm << 3) | op2
+}
+
+crate::enum_str! {
+    /// system registers that can be named, any other is written `S3_3_C13_C0_2`
+    #[allow(non_camel_case_types)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    #[repr(u16)]
+    pub enum SysReg {
+        // identification
+        MIDR_EL1 = sys(3, 0, 0, 0, 0),
+        MPIDR_EL1 = sys(3, 0, 0, 0, 5),
+        ID_AA64PFR0_EL1 = sys(3, 0, 0, 4, 0),
+        ID_AA64ISAR0_EL1 = sys(3, 0, 0, 6, 0),
+        ID_AA64ISAR1_EL1 = sys(3, 0, 0, 6, 1),
+        ID_AA64MMFR0_EL1 = sys(3, 0, 0, 7, 0),
+        CTR_EL0 = sys(3, 3, 0, 0, 1),
+        DCZID_EL0 = sys(3, 3, 0, 0, 7),
+        // process state
+        NZCV = sys(3, 3, 4, 2, 0),
+        DAIF = sys(3, 3, 4, 2, 1),
+        CurrentEL = sys(3, 0, 4, 2, 2),
+        SPSel = sys(3, 0, 4, 2, 0),
+        SP_EL0 = sys(3, 0, 4, 1, 0),
+        SP_EL1 = sys(3, 4, 4, 1, 0),
+        FPCR = sys(3, 3, 4, 4, 0),
+        FPSR = sys(3, 3, 4, 4, 1),
+        // thread pointers
+        TPIDR_EL0 = sys(3, 3, 13, 0, 2),
+        TPIDRRO_EL0 = sys(3, 3, 13, 0, 3),
+        TPIDR_EL1 = sys(3, 0, 13, 0, 4),
+        TPIDR_EL2 = sys(3, 4, 13, 0, 2),
+        // generic timer
+        CNTFRQ_EL0 = sys(3, 3, 14, 0, 0),
+        CNTPCT_EL0 = sys(3, 3, 14, 0, 1),
+        CNTVCT_EL0 = sys(3, 3, 14, 0, 2),
+        CNTP_TVAL_EL0 = sys(3, 3, 14, 2, 0),
+        CNTP_CTL_EL0 = sys(3, 3, 14, 2, 1),
+        CNTP_CVAL_EL0 = sys(3, 3, 14, 2, 2),
+        CNTV_TVAL_EL0 = sys(3, 3, 14, 3, 0),
+        CNTV_CTL_EL0 = sys(3, 3, 14, 3, 1),
+        CNTV_CVAL_EL0 = sys(3, 3, 14, 3, 2),
+        CNTKCTL_EL1 = sys(3, 0, 14, 1, 0),
+        // exceptions and memory system, EL1
+        SCTLR_EL1 = sys(3, 0, 1, 0, 0),
+        CPACR_EL1 = sys(3, 0, 1, 0, 2),
+        TTBR0_EL1 = sys(3, 0, 2, 0, 0),
+        TTBR1_EL1 = sys(3, 0, 2, 0, 1),
+        TCR_EL1 = sys(3, 0, 2, 0, 2),
+        SPSR_EL1 = sys(3, 0, 4, 0, 0),
+        ELR_EL1 = sys(3, 0, 4, 0, 1),
+        ESR_EL1 = sys(3, 0, 5, 2, 0),
+        FAR_EL1 = sys(3, 0, 6, 0, 0),
+        PAR_EL1 = sys(3, 0, 7, 4, 0),
+        MAIR_EL1 = sys(3, 0, 10, 2, 0),
+        VBAR_EL1 = sys(3, 0, 12, 0, 0),
+        CONTEXTIDR_EL1 = sys(3, 0, 13, 0, 1),
+        // EL2 and EL3
+        SCTLR_EL2 = sys(3, 4, 1, 0, 0),
+        HCR_EL2 = sys(3, 4, 1, 1, 0),
+        SPSR_EL2 = sys(3, 4, 4, 0, 0),
+        ELR_EL2 = sys(3, 4, 4, 0, 1),
+        ESR_EL2 = sys(3, 4, 5, 2, 0),
+        VBAR_EL2 = sys(3, 4, 12, 0, 0),
+        SCTLR_EL3 = sys(3, 6, 1, 0, 0),
+        SCR_EL3 = sys(3, 6, 1, 1, 0),
+        SPSR_EL3 = sys(3, 6, 4, 0, 0),
+        ELR_EL3 = sys(3, 6, 4, 0, 1),
+        VBAR_EL3 = sys(3, 6, 12, 0, 0),
+    }
+}
+
+/// named register in any case, or the generic `S<op0>_<op1>_C<n>_C<m>_<op2>`
+pub fn parse(name: &str) -> Option<u16> {
+    if let Some(reg) = SysReg::from_str_mixed(name) {
+        return Some(reg as u16);
+    }
+    let rest = name.strip_prefix(['S', 's'])?;
+    let mut fields = rest.split('_');
+    let mut field = |prefix: bool, max: u16| -> Option<u16> {
+        let s = fields.next()?;
+        let s = if prefix {
+            s.strip_prefix(['C', 'c'])?
+        } else {
+            s
+        };
+        // a number, no sign or leading `+`
+        if !s.bytes().all(|c| c.is_ascii_digit()) {
+            return None;
+        }
+        s.parse().ok().filter(|&v| v <= max)
+    };
+    let reg = sys(
+        field(false, 3)?,
+        field(false, 7)?,
+        field(true, 15)?,
+        field(true, 15)?,
+        field(false, 7)?,
+    );
+    fields.next().is_none().then_some(reg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_parses_system_registers() {
+        assert_eq!(parse("tpidr_el0"), Some(sys(3, 3, 13, 0, 2)));
+        assert_eq!(parse("CurrentEL"), parse("currentel"));
+        assert_eq!(parse("S3_3_C13_C0_2"), parse("TPIDR_EL0"));
+        assert_eq!(parse("s2_0_c0_c15_7"), Some(sys(2, 0, 0, 15, 7)));
+        assert_eq!(parse("S3_8_C0_C0_0"), None);
+        assert_eq!(parse("S3_0_C0_C0"), None);
+        assert_eq!(parse("S3_0_C0_C0_0_1"), None);
+        assert_eq!(parse("x0"), None);
+    }
+}
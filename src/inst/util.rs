@@ -53,7 +53,8 @@ pub(super) fn is_variant<'a, I>(mut iter: I, expect: &'static [Param]) -> bool
 #[derive(Debug, PartialEq, Eq)]
 pub enum NarrowError {
     Required(Kind),
-    Multiple,
+    /// bitflags of the variants that all take the operands, in the order they are defined
+    Multiple(u32),
     None,
 }
 
@@ -116,10 +117,9 @@ pub fn check_next(&mut self, kind: Kind) {
         }
         self.index += 1;
     }
-    // only called in finish()
-    fn check_end(&self) -> (usize, usize) {
-        let mut count = 0;
-        let mut variant_idx = 0;
+    // only called in finish(), bitflags of the variants that can end here
+    fn check_end(&self) -> u32 {
+        let mut matched = 0;
         for (i, variant) in self.variants.iter().copied().enumerate() {
             if self.is_fail(i) {
                 continue;
@@ -127,19 +127,18 @@ fn check_end(&self) -> (usize, usize) {
             if let Some(Param::Req(req)) = variant.get(self.index) {
                 // would self.fail(i) but unnecessary
             } else {
-                count += 1;
-                // variant_idx only gets used if count == 1
-                variant_idx = i;
+                matched |= 1 << i;
             }
         }
-        (count, variant_idx)
+        matched
     }
     pub fn finish(self) -> Result<usize, NarrowError> {
-        let (count, variant_idx) = self.check_end();
-        if count == 0 {
+        let matched = self.check_end();
+        let variant_idx = matched.trailing_zeros() as usize;
+        if matched == 0 {
             Err(NarrowError::None)
-        } else if count > 1 {
-            Err(NarrowError::Multiple)
+        } else if matched.count_ones() > 1 {
+            Err(NarrowError::Multiple(matched))
         } else if let Some(Param::Req(req)) = self.variants[variant_idx].get(self.index) {
             Err(NarrowError::Required(*req))
         } else {
@@ -33,6 +33,7 @@
 pub mod probably;
 //pub mod scan;
 pub mod inst;
+pub mod obj;
 pub mod stream;
 
 mod addr;
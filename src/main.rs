@@ -1,16 +1,187 @@
-use std::{fs, io, path::Path};
+use std::{fs, io, path::PathBuf, process::ExitCode};
 
-use armventure::assembler;
+use armventure::{
+    assembler::{self, SourceManager},
+    inst::{TargetFeatures, UnknownName},
+    obj::GnuProperty,
+};
 
-// pub mod assembler;
-// pub mod multipeekable;
-// pub mod scanner;
-// pub mod source_file;
+const USAGE: &str = "\
+usage: armventure [options] <file.s>
+  -o <file>                 write the object to <file> instead of a.out
+  -march=<arch>[+ext...]    assemble for an architecture, like armv8.2-a+crc+nolse
+  -mcpu=<cpu>[+ext...]      assemble for the architecture and extensions of a processor
+  -mbranch-protection=<p>   mark the object as using `bti`, `pac-ret`, `standard` for both,
+                            or `none`, in its .note.gnu.property
+  -mmark-bti-property       mark the object as using BTI, as -mbranch-protection=bti
+  -I <dir>                  search <dir> for files named by .include";
 
-fn read_file(path: &Path) -> io::Result<Vec<u8>> {
-    return fs::read(path);
+struct Args {
+    input: PathBuf,
+    output: PathBuf,
+    features: TargetFeatures,
+    include: Vec<PathBuf>,
+    gnu_property: GnuProperty,
 }
 
-fn main() {
-    println!("Hello, world!");
+/// features of `-march=` or `-mcpu=`, which replace the target
+fn parse_target(arg: &str) -> Option<Result<TargetFeatures, String>> {
+    let (target, what) = if let Some(arch) = arg.strip_prefix("-march=") {
+        (TargetFeatures::arch(arch), "architecture")
+    } else {
+        (
+            TargetFeatures::cpu(arg.strip_prefix("-mcpu=")?),
+            "processor",
+        )
+    };
+    Some(target.map_err(|unknown| match unknown {
+        UnknownName::Base(name) => format!("unknown {what} `{name}`"),
+        UnknownName::Extension(name) => format!("unknown architecture extension `{name}`"),
+    }))
+}
+
+/// `bti+pac-ret` of `-mbranch-protection=`, the protections the code was written with
+fn parse_branch_protection(arg: &str) -> Result<GnuProperty, String> {
+    let mut property = GnuProperty::default();
+    for name in arg.split('+') {
+        match name {
+            "none" => property = GnuProperty::default(),
+            "standard" => {
+                property.bti = true;
+                property.pac = true;
+            }
+            "bti" => property.bti = true,
+            "pac-ret" => property.pac = true,
+            _ => return Err(format!("unknown branch protection `{name}`")),
+        }
+    }
+    Ok(property)
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, String> {
+    let mut input = None;
+    let mut output = PathBuf::from("a.out");
+    let mut features = TargetFeatures::default();
+    let mut include = Vec::new();
+    let mut gnu_property = GnuProperty::default();
+    while let Some(arg) = args.next() {
+        if let Some(target) = parse_target(&arg) {
+            features = target?;
+            continue;
+        }
+        if let Some(protection) = arg.strip_prefix("-mbranch-protection=") {
+            gnu_property = parse_branch_protection(protection)?;
+            continue;
+        }
+        match arg.as_str() {
+            "-o" => output = args.next().ok_or("missing file after -o")?.into(),
+            "-I" => include.push(args.next().ok_or("missing directory after -I")?.into()),
+            "-mmark-bti-property" => gnu_property.bti = true,
+            _ if arg.starts_with('-') => return Err(format!("unknown option `{arg}`")),
+            _ if input.is_some() => return Err("only one input file is assembled".into()),
+            _ => input = Some(PathBuf::from(arg)),
+        }
+    }
+    Ok(Args {
+        input: input.ok_or("no input file")?,
+        output,
+        features,
+        include,
+        gnu_property,
+    })
+}
+
+fn run(args: Args) -> io::Result<bool> {
+    let mut sources = SourceManager::new();
+    for dir in args.include {
+        sources.add_search_path(dir);
+    }
+    let file = sources.load(args.input.clone()).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::Other,
+            format!("{}: {e}", args.input.display()),
+        )
+    })?;
+    let mut obj = assembler::assemble_for(&sources, file, args.features);
+    if sources.error_count() != 0 {
+        return Ok(false);
+    }
+    obj.gnu_property = args.gnu_property;
+    let mut elf = Vec::new();
+    obj.write_elf(&mut elf)?;
+    fs::write(&args.output, elf)?;
+    Ok(true)
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("armventure: {e}\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+    match run(args) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("armventure: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Result<Args, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn it_marks_branch_protection() {
+        let property = |list: &[&str]| {
+            let GnuProperty { bti, pac } = args(list).unwrap().gnu_property;
+            (bti, pac)
+        };
+        assert_eq!(property(&["a.s"]), (false, false));
+        assert_eq!(property(&["-mmark-bti-property", "a.s"]), (true, false));
+        assert_eq!(
+            property(&["-mbranch-protection=pac-ret", "a.s"]),
+            (false, true)
+        );
+        assert_eq!(
+            property(&["-mbranch-protection=bti+pac-ret", "a.s"]),
+            (true, true)
+        );
+        assert_eq!(
+            property(&["-mbranch-protection=standard+none", "a.s"]),
+            (false, false)
+        );
+        assert!(args(&["-mbranch-protection=gcs", "a.s"]).is_err());
+
+        // the note of the object has `GNU_PROPERTY_AARCH64_FEATURE_1_AND` with BTI and PAC
+        let dir = std::env::temp_dir().join(format!("armventure-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let (input, output) = (dir.join("bti.s"), dir.join("bti.o"));
+        fs::write(&input, "f:\nbti c\npaciasp\nautiasp\nret\n").unwrap();
+        let list = [
+            "-mbranch-protection=standard",
+            input.to_str().unwrap(),
+            "-o",
+            output.to_str().unwrap(),
+        ];
+        assert!(run(args(&list).unwrap()).unwrap());
+        let elf = fs::read(&output).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+        let property = [
+            b"GNU\0".as_slice(),
+            &0xc0000000u32.to_le_bytes(),
+            &4u32.to_le_bytes(),
+            &3u32.to_le_bytes(),
+        ]
+        .concat();
+        assert!(elf.windows(property.len()).any(|w| w == property));
+    }
 }
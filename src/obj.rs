@@ -0,0 +1,157 @@
+use crate::{elf, sparsebin::SparseBin};
+use std::io;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolValue {
+    /// address in the code
+    Text(u64),
+    /// `.equ`, `.set` or `=`, not relative to any section
+    Absolute(i64),
+    /// referenced but not defined, the linker finds it in another object
+    Undefined,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Symbol {
+    pub name: String,
+    pub value: SymbolValue,
+    /// named by `.global`, visible to other objects
+    pub global: bool,
+}
+
+/// AArch64 ELF relocation types, named after `R_AARCH64_*`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u32)]
+pub enum RelocKind {
+    /// `LDR` literal, 19-bit word offset
+    LdPrelLo19 = 273,
+    /// `ADR`, 21-bit byte offset
+    AdrPrelLo21 = 274,
+    /// `ADRP` and `:pg_hi21:`, 21-bit offset of the 4KB page
+    AdrPrelPgHi21 = 275,
+    /// `:lo12:` in `ADD`
+    AddAbsLo12Nc = 277,
+    /// `:lo12:` in a load or store of a byte
+    Ldst8AbsLo12Nc = 278,
+    /// `TBZ` and `TBNZ`, 14-bit word offset
+    TstBr14 = 279,
+    /// `B.cond`, `CBZ` and `CBNZ`, 19-bit word offset
+    CondBr19 = 280,
+    /// `B`, 26-bit word offset
+    Jump26 = 282,
+    /// `BL`, 26-bit word offset
+    Call26 = 283,
+    /// `:lo12:` in a load or store of 2 bytes, scaled by 2
+    Ldst16AbsLo12Nc = 284,
+    /// `:lo12:` in a load or store of 4 bytes, scaled by 4
+    Ldst32AbsLo12Nc = 285,
+    /// `:lo12:` in a load or store of 8 bytes, scaled by 8
+    Ldst64AbsLo12Nc = 286,
+    /// `:lo12:` in a load or store of 16 bytes, scaled by 16
+    Ldst128AbsLo12Nc = 299,
+}
+
+impl RelocKind {
+    /// `:lo12:` of a load or store, `scale` is log2 of the access size in bytes
+    pub fn ldst_lo12(scale: u8) -> Option<Self> {
+        Some(match scale {
+            0 => Self::Ldst8AbsLo12Nc,
+            1 => Self::Ldst16AbsLo12Nc,
+            2 => Self::Ldst32AbsLo12Nc,
+            3 => Self::Ldst64AbsLo12Nc,
+            4 => Self::Ldst128AbsLo12Nc,
+            _ => return None,
+        })
+    }
+
+    /// pc relative, so the assembler resolves it if the label is defined in this object,
+    /// the others depend on where the code is loaded and are always left to the linker
+    pub fn is_pc_relative(self) -> bool {
+        matches!(
+            self,
+            Self::LdPrelLo19
+                | Self::AdrPrelLo21
+                | Self::TstBr14
+                | Self::CondBr19
+                | Self::Jump26
+                | Self::Call26
+        )
+    }
+}
+
+impl TryFrom<u32> for RelocKind {
+    type Error = ();
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        [
+            Self::LdPrelLo19,
+            Self::AdrPrelLo21,
+            Self::AdrPrelPgHi21,
+            Self::AddAbsLo12Nc,
+            Self::Ldst8AbsLo12Nc,
+            Self::TstBr14,
+            Self::CondBr19,
+            Self::Jump26,
+            Self::Call26,
+            Self::Ldst16AbsLo12Nc,
+            Self::Ldst32AbsLo12Nc,
+            Self::Ldst64AbsLo12Nc,
+            Self::Ldst128AbsLo12Nc,
+        ]
+        .into_iter()
+        .find(|&kind| kind as u32 == value)
+        .ok_or(())
+    }
+}
+
+/// value the linker writes into an instruction once it knows the address of `symbol`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Relocation {
+    /// address of the instruction in `text`
+    pub offset: u64,
+    pub kind: RelocKind,
+    /// `None` for a label defined in this object, relative to the start of `text`
+    pub symbol: Option<String>,
+    pub addend: i64,
+}
+
+/// features of the code that the `.note.gnu.property` section declares,
+/// the linker only marks a program with a feature if every object it links has it
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct GnuProperty {
+    /// indirect branches only land on BTI
+    pub bti: bool,
+    /// return addresses are signed with PACIASP or PACIBSP
+    pub pac: bool,
+}
+
+impl GnuProperty {
+    /// `GNU_PROPERTY_AARCH64_FEATURE_1_AND`, BTI is bit 0 and PAC bit 1
+    pub fn feature_1_and(self) -> u32 {
+        self.bti as u32 | (self.pac as u32) << 1
+    }
+}
+
+/// output of the assembler, the code and the symbols defined in it
+pub struct Object {
+    pub text: SparseBin,
+    /// bytes of code in `text`, starting at address 0
+    pub text_size: u64,
+    /// in the order they were defined, then the undefined symbols in the order they were used
+    pub symbols: Vec<Symbol>,
+    pub relocations: Vec<Relocation>,
+    /// none by default, the note is only written if a feature is set
+    pub gnu_property: GnuProperty,
+}
+
+impl Object {
+    pub fn text_bytes(&mut self) -> Vec<u8> {
+        (0..self.text_size as usize)
+            .map(|addr| self.text.get_u8(addr))
+            .collect()
+    }
+
+    /// ELF relocatable object, for the linker
+    pub fn write_elf<W: io::Write>(&mut self, file: &mut W) -> io::Result<()> {
+        elf::write_object_64le(self, file)
+    }
+}
@@ -209,7 +209,7 @@ unsafe fn try_get<T: Sized + Copy, const ALIGN: u32>(&mut self, addr: usize) ->
     /// get sequential
     unsafe fn get<T: Sized + Copy, const ALIGN: u32>(&mut self, addr: usize) -> T {
         let SA { index, offset } = split_addr::<T, ALIGN>(addr);
-        let page = self.page_entry_mut(addr);
+        let page = self.page_entry_mut(index);
         let ptr = page.as_ptr().add(extract_lo::<PAGE_BITS>(addr));
         let ptr_t = ptr.cast::<T>();
 